@@ -5,7 +5,7 @@
 //! so functions in this module that accept _row_ and _column*_ are presumed to be
 //! `0`-based and silently add `1` to values.
 
-use crate::color::Color;
+use crate::color::{self, Attrs, Color, ColorCode};
 use crate::size::Point;
 
 pub fn alt_screen(on: bool) -> &'static str {
@@ -40,6 +40,72 @@ pub fn set_cursor(p: Point) -> String {
     format!("\x1b[{};{}H", p.row + 1, p.col + 1)
 }
 
+pub fn reset_color() -> &'static str {
+    "\x1b[0m"
+}
+
+/// Returns an SGR sequence that sets all aspects of `color`, including attributes,
+/// irrespective of prior state.
 pub fn set_color(color: Color) -> String {
-    format!("\x1b[38;5;{}m\x1b[48;5;{}m", color.fg, color.bg)
+    let mut params = vec![
+        "0".to_string(),
+        color_param(38, color.fg),
+        color_param(48, color.bg),
+    ];
+    params.extend(attr_params(Attrs::NONE, color.attrs));
+    format!("\x1b[{}m", params.join(";"))
+}
+
+/// Returns an SGR sequence that changes only those aspects of `color` that differ
+/// from `prev_color`, or an empty string if the colors are the same.
+pub fn change_color(prev_color: Color, color: Color) -> String {
+    let mut params = Vec::new();
+    if color.fg != prev_color.fg {
+        params.push(color_param(38, color.fg));
+    }
+    if color.bg != prev_color.bg {
+        params.push(color_param(48, color.bg));
+    }
+    params.extend(attr_params(prev_color.attrs, color.attrs));
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("\x1b[{}m", params.join(";"))
+    }
+}
+
+/// Returns the parameters of an SGR sequence that selects `code`, where `select` is
+/// either `38` for foreground or `48` for background.
+///
+/// RGB colors are downsampled to the 8-bit palette unless the terminal supports
+/// 24-bit colors.
+fn color_param(select: u8, code: ColorCode) -> String {
+    match code {
+        ColorCode::Rgb(r, g, b) if color::is_truecolor() => format!("{select};2;{r};{g};{b}"),
+        _ => format!("{select};5;{}", code.to_index()),
+    }
+}
+
+/// Returns the parameters of an SGR sequence that turn off attributes in `prev_attrs`
+/// missing from `attrs`, and turn on attributes in `attrs` missing from
+/// `prev_attrs`.
+fn attr_params(prev_attrs: Attrs, attrs: Attrs) -> Vec<String> {
+    // Attributes with their corresponding on and off codes.
+    const ATTR_CODES: [(Attrs, u8, u8); 4] = [
+        (Attrs::BOLD, 1, 22),
+        (Attrs::ITALIC, 3, 23),
+        (Attrs::UNDERLINE, 4, 24),
+        (Attrs::REVERSE, 7, 27),
+    ];
+
+    ATTR_CODES
+        .iter()
+        .filter_map(
+            |(attr, on, off)| match (prev_attrs.contains(*attr), attrs.contains(*attr)) {
+                (false, true) => Some(on.to_string()),
+                (true, false) => Some(off.to_string()),
+                _ => None,
+            },
+        )
+        .collect()
 }
@@ -3,17 +3,38 @@
 //! The recognized set of keys that can be used in the formation of sequences is
 //! defined authoritatively in the map produced by [`init_key_map`](key::init_key_map),
 //! and similarly, the recognized set of editing operations is defined in the map
-//! produced by [`init_op_map`](op::init_op_map).
+//! produced by [`init_op_map`](op::init_op_map). User-defined commands, which are
+//! sequences of editing operations and literal text, may also be defined and then
+//! bound to key sequences in the same manner as predefined operations.
 
+use crate::env::Environment;
 use crate::error::{Error, Result};
 use crate::key::{self, Key, KeyMap};
-use crate::op::{self, OpFn, OpMap};
+use crate::op::{self, Action, CommandMap, OpFn, OpMap, Step};
 use std::collections::{HashMap, HashSet};
 
+/// An operation bound to a key sequence, which is either a predefined editing function
+/// or a user-defined command.
+pub enum Operation<'a> {
+    Function(OpFn),
+    Command(&'a [Step]),
+}
+
+impl Operation<'_> {
+    /// Carries out the operation in the context of `env`.
+    pub fn call(&self, env: &mut Environment) -> Option<Action> {
+        match self {
+            Operation::Function(op_fn) => op_fn(env),
+            Operation::Command(steps) => op::run_command(env, steps),
+        }
+    }
+}
+
 /// A mapping of [`Key`] sequences to editing functions.
 pub struct Bindings {
     key_map: KeyMap,
     op_map: OpMap,
+    command_map: CommandMap,
     bind_map: HashMap<Vec<Key>, String>,
     bind_prefixes: HashSet<Vec<Key>>,
     restricted_keys: HashSet<Vec<Key>>,
@@ -29,6 +50,7 @@ impl Bindings {
         let mut this = Bindings {
             key_map: key::init_key_map(),
             op_map: op::init_op_map(),
+            command_map: CommandMap::new(),
             bind_map: HashMap::new(),
             bind_prefixes: HashSet::new(),
             restricted_keys: Self::init_restricted_keys(),
@@ -40,6 +62,39 @@ impl Bindings {
         Ok(this)
     }
 
+    /// Defines a user command named `name` as the sequence of `steps`, where each step
+    /// is either the name of an editing operation or literal text.
+    ///
+    /// Operations referenced by `steps` may include previously defined commands, in
+    /// which case the steps of such commands are expanded in place. A command that
+    /// conflicts with the name of a predefined operation will result in an error,
+    /// though redefining a prior command is permitted.
+    pub fn define(&mut self, name: &str, steps: &[Step]) -> Result<()> {
+        if self.op_map.contains_key(name) {
+            Err(Error::redefined_op(name))
+        } else {
+            self.command_map.insert(name.to_string(), steps.to_vec());
+            Ok(())
+        }
+    }
+
+    /// Resolves the editing operation `op` as a sequence of steps suitable for
+    /// inclusion in a user-defined command.
+    pub fn resolve(&self, op: &str) -> Result<Vec<Step>> {
+        if let Some(op_fn) = self.op_map.get(op) {
+            Ok(vec![Step::Op(*op_fn)])
+        } else if let Some(steps) = self.command_map.get(op) {
+            Ok(steps.clone())
+        } else {
+            Err(Error::invalid_op(op))
+        }
+    }
+
+    /// Returns a reference to the user-defined commands in order of definition.
+    pub fn commands(&self) -> &CommandMap {
+        &self.command_map
+    }
+
     /// Binds the key sequence `key_seq` to the editing operation `op`, which will
     /// override an existing binding with an identical key sequence.
     ///
@@ -53,16 +108,16 @@ impl Bindings {
     /// when `strict` is `true`.
     fn bind_internal(&mut self, key_seq: &str, op: &str, strict: bool) -> Result<()> {
         self.to_keys(key_seq, strict).and_then(|keys| {
-            self.op_map
-                .get_key_value(op)
-                .map(|(op, _)| {
-                    self.bind_map.insert(keys.clone(), op.to_string());
-                    for n in 1..keys.len() {
-                        let prefix = &keys[0..n];
-                        self.bind_prefixes.insert(prefix.to_vec());
-                    }
-                })
-                .ok_or_else(|| Error::invalid_op(op))
+            if self.op_map.contains_key(op) || self.command_map.contains_key(op) {
+                self.bind_map.insert(keys.clone(), op.to_string());
+                for n in 1..keys.len() {
+                    let prefix = &keys[0..n];
+                    self.bind_prefixes.insert(prefix.to_vec());
+                }
+                Ok(())
+            } else {
+                Err(Error::invalid_op(op))
+            }
         })
     }
 
@@ -121,11 +176,18 @@ impl Bindings {
         &self.bind_map
     }
 
-    /// Returns the function pointer bound to `keys`, otherwise `None`.
-    pub fn find(&self, keys: &Vec<Key>) -> Option<&OpFn> {
-        self.bind_map
-            .get(keys)
-            .and_then(|op| self.op_map.get(op as &str))
+    /// Returns the operation bound to `keys`, otherwise `None`.
+    pub fn find(&self, keys: &Vec<Key>) -> Option<Operation<'_>> {
+        self.bind_map.get(keys).and_then(|op| {
+            self.op_map
+                .get(op as &str)
+                .map(|op_fn| Operation::Function(*op_fn))
+                .or_else(|| {
+                    self.command_map
+                        .get(op)
+                        .map(|steps| Operation::Command(steps))
+                })
+        })
     }
 
     /// Returns `true` if `keys` is a prefix of at least one key sequence bound to a
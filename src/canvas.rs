@@ -12,9 +12,9 @@
 use crate::color::Color;
 use crate::grid::{Cell, Grid};
 use crate::size::{Point, Size};
+use crate::width;
 use crate::writer::Writer;
 use std::cell::RefCell;
-use std::cmp;
 use std::ops::Range;
 use std::rc::Rc;
 
@@ -73,10 +73,36 @@ impl Canvas {
     }
 
     /// Sets the cell at (`row`, `col`) to the value `cell`.
+    ///
+    /// If the value of `cell` is a wide character, the cell at (`row`, `col + 1`) is
+    /// also occupied. However, if the wide character would extend beyond the end of
+    /// the row, a blank is shown instead.
     pub fn set_cell(&mut self, row: u32, col: u32, cell: Cell) {
         debug_assert!(row < self.size.rows);
         debug_assert!(col < self.size.cols);
-        self.back.set_cell(row, col, cell);
+        if width::char_width(cell.value) > 1 {
+            if col + 1 < self.size.cols {
+                self.back.set_cell(row, col, cell);
+                self.back.set_cell(row, col + 1, Cell::tail(cell.color));
+            } else {
+                self.back.set_cell(row, col, Cell::new(' ', cell.color));
+            }
+        } else {
+            self.back.set_cell(row, col, cell);
+        }
+    }
+
+    /// Returns the cell at (`row`, `col`) staged for the next [`draw`](Self::draw).
+    #[cfg(test)]
+    pub fn get_cell(&self, row: u32, col: u32) -> Cell {
+        self.back.get_cell(row, col)
+    }
+
+    /// Attaches the zero-width character `c` to the cell at (`row`, `col`).
+    pub fn add_mark(&mut self, row: u32, col: u32, c: char) {
+        debug_assert!(row < self.size.rows);
+        debug_assert!(col < self.size.cols);
+        self.back.add_mark(row, col, c);
     }
 
     /// Sets the cell at (`row`, `col`) to the value `c` using `color`.
@@ -85,34 +111,41 @@ impl Canvas {
         self.set_cell(row, col, Cell::new(c, color));
     }
 
-    /// Writes `c` using `color` at (`row`, `col`), returning `1` if the character
-    /// was written or `0` if `col` extends beyond the end of the row.
+    /// Writes `c` using `color` at (`row`, `col`), returning the number of columns
+    /// occupied by the character, or `0` if it would extend beyond the end of the
+    /// row.
+    #[inline]
     pub fn write_char(&mut self, row: u32, col: u32, c: char, color: Color) -> u32 {
-        if col < self.size.cols {
-            self.back.set_cell(row, col, Cell::new(c, color));
-            1
-        } else {
-            0
-        }
+        self.write(row, col, &[c], color)
     }
 
     /// Write `chars` using `color` in `row` starting at column `col`, returning the
-    /// actual number of characters written.
+    /// actual number of columns written.
     ///
-    /// Fewer than `chars.len()` may be written if doing so would extend beyond the
-    /// end of the row.
+    /// The number of columns written may differ from `chars.len()` since characters
+    /// may be wide or zero-width, and also because writing stops if the next character
+    /// would extend beyond the end of the row.
     pub fn write(&mut self, row: u32, col: u32, chars: &[char], color: Color) -> u32 {
         debug_assert!(row < self.size.rows);
-        let n = cmp::min(chars.len(), (self.size.cols - col) as usize);
-        for (i, c) in chars.iter().take(n).enumerate() {
-            self.back
-                .set_cell(row, col + i as u32, Cell::new(*c, color));
+        let mut n = 0;
+        for c in chars {
+            let c_cols = width::char_width(*c);
+            if c_cols == 0 {
+                if n > 0 {
+                    self.back.add_mark(row, col + n - 1, *c);
+                }
+            } else if col + n + c_cols <= self.size.cols {
+                self.set_cell(row, col + n, Cell::new(*c, color));
+                n += c_cols;
+            } else {
+                break;
+            }
         }
-        n as u32
+        n
     }
 
     /// Write `chars` using `color` in `row` starting at column `col`, returning the
-    /// actual number of characters written.
+    /// actual number of columns written.
     pub fn write_str(&mut self, row: u32, col: u32, chars: &str, color: Color) -> u32 {
         let chars = chars.chars().collect::<Vec<_>>();
         self.write(row, col, &chars, color)
@@ -170,8 +203,12 @@ impl Canvas {
             let mut hint = None;
             self.writer.hide_cursor();
             for (p, cell) in changes {
-                self.draw_cell(p, cell, hint);
-                hint = Some((p, cell));
+                // Trailing half of wide character is drawn as part of the preceding
+                // cell.
+                if !cell.tail {
+                    self.draw_cell(p, cell, hint);
+                    hint = Some((p, cell));
+                }
             }
             self.writer.show_cursor().send();
         }
@@ -190,17 +227,18 @@ impl Canvas {
     fn draw_cell(&mut self, p: Point, cell: Cell, hint: Option<(Point, Cell)>) {
         match hint {
             Some((prev_p, prev_cell)) => {
-                if p.row != prev_p.row || p.col != prev_p.col + 1 {
+                let prev_cols = width::char_width(prev_cell.value);
+                if p.row != prev_p.row || p.col != prev_p.col + prev_cols {
                     self.writer.set_cursor(p);
                 }
                 if cell.color != prev_cell.color {
-                    self.writer.set_color(cell.color);
+                    self.writer.change_color(prev_cell.color, cell.color);
                 }
             }
             None => {
                 self.writer.set_cursor(p).set_color(cell.color);
             }
         }
-        self.writer.write(cell.value);
+        self.writer.write_cell(cell);
     }
 }
@@ -1,44 +1,259 @@
-//! Contains everything related to colors.
+//! Contains everything related to colors and text attributes.
 //!
-//! Colors follow the ANSI 8-bit standard, which is referenced
-//! [here](https://en.wikipedia.org/wiki/ANSI_escape_code#8-bit).
+//! Colors follow either the ANSI 8-bit standard, which is referenced
+//! [here](https://en.wikipedia.org/wiki/ANSI_escape_code#8-bit), or the 24-bit
+//! standard, which is referenced
+//! [here](https://en.wikipedia.org/wiki/ANSI_escape_code#24-bit).
+//!
+//! 24-bit colors are only emitted if the terminal advertises support by setting the
+//! `COLORTERM` environment variable to either `truecolor` or `24bit`. Otherwise, such
+//! colors are downsampled to the nearest color in the 8-bit palette.
+//!
+//! Text attributes, such as _bold_ and _italic_, accompany colors and are applied
+//! using the corresponding SGR control sequences.
 
-use serde::de::{self, Visitor};
+use crate::error::Error;
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
 use std::collections::HashMap;
+use std::env;
 use std::fmt::{self, Display, Formatter};
+use std::ops::BitOr;
 use std::result;
+use std::sync::OnceLock;
+
+/// A color represented as either an _index_ into the 8-bit palette or as a 24-bit
+/// _RGB_ value.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, PartialOrd, Ord)]
+pub enum ColorCode {
+    Index(u8),
+    Rgb(u8, u8, u8),
+}
 
-/// A combination of _foreground_ and _background_ color.
+/// A set of text attributes, such as _bold_ and _italic_.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub struct Attrs(u8);
+
+/// A combination of _foreground_ and _background_ color, along with the text
+/// attributes to apply.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct Color {
-    pub fg: u8,
-    pub bg: u8,
+    pub fg: ColorCode,
+    pub bg: ColorCode,
+    pub attrs: Attrs,
+}
+
+/// A single color along with text attributes, which is the form of entries in the
+/// theme.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Shade {
+    pub color: ColorCode,
+    pub attrs: Attrs,
+}
+
+/// A style associated with syntax tokens, where the absence of a _foreground_ or
+/// _background_ color implies that the color is inherited from the theme.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Style {
+    pub fg: Option<ColorCode>,
+    pub bg: Option<ColorCode>,
+    pub attrs: Attrs,
 }
 
 /// A collection of color mappings.
 pub struct Colors {
-    color_map: HashMap<String, u8>,
+    color_map: HashMap<String, ColorCode>,
 }
 
 /// A color value representing either a _number_ in the range of `0` to `255`, or as a
-/// _string_ that refers to a named color.
+/// _string_ that refers to a named color or an RGB value of the form `#rrggbb`.
 pub enum ColorValue {
     Number(u8),
     Name(String),
 }
 
+/// A style value representing either a single color, or a table of the form
+/// `{ fg, bg, bold, italic, underline, reverse }` where all fields are optional.
+pub enum StyleValue {
+    Color(ColorValue),
+    Table(StyleTable),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StyleTable {
+    pub fg: Option<ColorValue>,
+    pub bg: Option<ColorValue>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underline: Option<bool>,
+    pub reverse: Option<bool>,
+}
+
 /// A deserialization visitor that accepts either color values as numbers in the range
 /// of `0` to `255`, or as strings representing either named colors or values that can
 /// be parsed as numbers.
 pub struct ColorVisitor;
 
+/// A deserialization visitor that accepts either color values understood by
+/// [`ColorVisitor`] or style tables.
+pub struct StyleVisitor;
+
+/// Returns `true` if the terminal advertises support for 24-bit colors.
+pub fn is_truecolor() -> bool {
+    static TRUECOLOR: OnceLock<bool> = OnceLock::new();
+    *TRUECOLOR.get_or_init(|| {
+        env::var("COLORTERM")
+            .map(|term| term == "truecolor" || term == "24bit")
+            .unwrap_or(false)
+    })
+}
+
+impl ColorCode {
+    /// Intensity levels of each component in the 6x6x6 color cube of the 8-bit
+    /// palette, which occupies indexes `16` through `231`.
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    /// Parses `value` of the form `#rrggbb` as an RGB color.
+    pub fn parse(value: &str) -> Option<ColorCode> {
+        let hex = value.strip_prefix('#')?;
+        if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            let comp = |n: usize| u8::from_str_radix(&hex[n..n + 2], 16).ok();
+            Some(ColorCode::Rgb(comp(0)?, comp(2)?, comp(4)?))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the index of this color in the 8-bit palette, which for RGB values is
+    /// the nearest color in either the color cube or the grayscale ramp.
+    ///
+    /// Note that the first `16` colors are never chosen when downsampling, since their
+    /// actual appearance varies by terminal.
+    pub fn to_index(self) -> u8 {
+        match self {
+            ColorCode::Index(n) => n,
+            ColorCode::Rgb(r, g, b) => {
+                let (ri, gi, bi) = (
+                    Self::cube_index(r),
+                    Self::cube_index(g),
+                    Self::cube_index(b),
+                );
+                let cube = (
+                    Self::CUBE_LEVELS[ri],
+                    Self::CUBE_LEVELS[gi],
+                    Self::CUBE_LEVELS[bi],
+                );
+                let avg = (r as u32 + g as u32 + b as u32) / 3;
+                let gray_n = if avg < 8 { 0 } else { ((avg - 8) / 10).min(23) } as u8;
+                let gray = 8 + gray_n * 10;
+                if Self::distance((r, g, b), (gray, gray, gray)) < Self::distance((r, g, b), cube) {
+                    232 + gray_n
+                } else {
+                    16 + (ri * 36 + gi * 6 + bi) as u8
+                }
+            }
+        }
+    }
+
+    fn cube_index(value: u8) -> usize {
+        Self::CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, level)| (**level as i32 - value as i32).abs())
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+        let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+        d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+    }
+}
+
+impl Display for ColorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorCode::Index(n) => write!(f, "{n}"),
+            ColorCode::Rgb(r, g, b) => write!(f, "#{r:02x}{g:02x}{b:02x}"),
+        }
+    }
+}
+
+impl Attrs {
+    pub const NONE: Attrs = Attrs(0);
+    pub const BOLD: Attrs = Attrs(0x01);
+    pub const ITALIC: Attrs = Attrs(0x02);
+    pub const UNDERLINE: Attrs = Attrs(0x04);
+    pub const REVERSE: Attrs = Attrs(0x08);
+
+    /// Names of individual attributes, which correspond to fields in style tables.
+    pub const NAMES: [(&str, Attrs); 4] = [
+        ("bold", Attrs::BOLD),
+        ("italic", Attrs::ITALIC),
+        ("underline", Attrs::UNDERLINE),
+        ("reverse", Attrs::REVERSE),
+    ];
+
+    /// Returns `true` if all attributes in `other` are also present in `self`.
+    #[inline]
+    pub const fn contains(self, other: Attrs) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Attrs {
+    type Output = Attrs;
+
+    fn bitor(self, other: Attrs) -> Attrs {
+        Attrs(self.0 | other.0)
+    }
+}
+
 impl Color {
     /// A special color constant where `fg` and `bg` are initialized to `0`.
-    pub const ZERO: Color = Color::new(0, 0);
+    pub const ZERO: Color = Color::new(ColorCode::Index(0), ColorCode::Index(0));
 
-    pub const fn new(fg: u8, bg: u8) -> Color {
-        Color { fg, bg }
+    pub const fn new(fg: ColorCode, bg: ColorCode) -> Color {
+        Color {
+            fg,
+            bg,
+            attrs: Attrs::NONE,
+        }
+    }
+
+    /// Creates a new color from the `fg` and `bg` shades, where attributes of both
+    /// are combined.
+    pub fn from_shades(fg: Shade, bg: Shade) -> Color {
+        Color {
+            fg: fg.color,
+            bg: bg.color,
+            attrs: fg.attrs | bg.attrs,
+        }
+    }
+}
+
+impl Shade {
+    pub const fn new(color: ColorCode) -> Shade {
+        Shade {
+            color,
+            attrs: Attrs::NONE,
+        }
+    }
+}
+
+impl From<ColorCode> for Style {
+    fn from(color: ColorCode) -> Style {
+        Style {
+            fg: Some(color),
+            ..Default::default()
+        }
     }
 }
 
@@ -58,35 +273,78 @@ impl Display for ColorValue {
 }
 
 impl Colors {
-    pub fn apply(&mut self, colors: &HashMap<String, u8>) {
+    pub fn apply(&mut self, colors: &HashMap<String, ColorCode>) {
         for (name, color) in colors {
             self.color_map.insert(name.to_string(), *color);
         }
     }
 
-    pub fn lookup(&self, name: &str) -> Option<u8> {
+    pub fn lookup(&self, name: &str) -> Option<ColorCode> {
         self.color_map
             .get(name)
             .map(|color| *color)
-            .or_else(|| name.parse::<u8>().ok())
+            .or_else(|| name.parse::<u8>().ok().map(ColorCode::Index))
+            .or_else(|| ColorCode::parse(name))
     }
 
-    pub fn lookup_value(&self, value: &ColorValue) -> Option<u8> {
+    pub fn lookup_value(&self, value: &ColorValue) -> Option<ColorCode> {
         match value {
             ColorValue::Name(name) => self.lookup(name),
-            ColorValue::Number(color) => Some(*color),
+            ColorValue::Number(color) => Some(ColorCode::Index(*color)),
+        }
+    }
+
+    /// Resolves all colors in the style `value`, where a single color is presumed to
+    /// be the foreground color.
+    pub fn lookup_style(&self, value: &StyleValue) -> result::Result<Style, Error> {
+        let lookup = |value: &Option<ColorValue>| {
+            value
+                .as_ref()
+                .map(|v| {
+                    self.lookup_value(v)
+                        .ok_or_else(|| Error::invalid_color(&v.to_string()))
+                })
+                .transpose()
+        };
+
+        match value {
+            StyleValue::Color(color) => self
+                .lookup_value(color)
+                .map(Style::from)
+                .ok_or_else(|| Error::invalid_color(&color.to_string())),
+            StyleValue::Table(table) => {
+                let attrs = [
+                    (table.bold, Attrs::BOLD),
+                    (table.italic, Attrs::ITALIC),
+                    (table.underline, Attrs::UNDERLINE),
+                    (table.reverse, Attrs::REVERSE),
+                ]
+                .into_iter()
+                .fold(Attrs::NONE, |attrs, (on, attr)| {
+                    if on.unwrap_or(false) {
+                        attrs | attr
+                    } else {
+                        attrs
+                    }
+                });
+                Ok(Style {
+                    fg: lookup(&table.fg)?,
+                    bg: lookup(&table.bg)?,
+                    attrs,
+                })
+            }
         }
     }
 
-    pub fn colors(&self) -> &HashMap<String, u8> {
+    pub fn colors(&self) -> &HashMap<String, ColorCode> {
         &self.color_map
     }
 
     /// Returns a mapping of standard color names to color values.
-    fn init_color_map() -> HashMap<String, u8> {
+    fn init_color_map() -> HashMap<String, ColorCode> {
         let mut color_map = HashMap::new();
         for (name, color) in Self::COLOR_MAPPINGS {
-            color_map.insert(name.to_string(), color);
+            color_map.insert(name.to_string(), ColorCode::Index(color));
         }
         color_map
     }
@@ -146,8 +404,15 @@ impl<'a> Deserialize<'a> for ColorValue {
     }
 }
 
+impl<'a> Deserialize<'a> for StyleValue {
+    fn deserialize<T: Deserializer<'a>>(deser: T) -> result::Result<StyleValue, T::Error> {
+        deser.deserialize_any(StyleVisitor)
+    }
+}
+
 impl ColorVisitor {
-    const EXPECT_MSG: &str = "a number in the range of [0, 255] or a string";
+    const EXPECT_MSG: &str =
+        "a number in the range of [0, 255] or a string, such as a color name or #rrggbb";
     const ERROR_MSG: &str = "color values must be in the range of [0, 255]";
 }
 
@@ -182,3 +447,112 @@ impl<'a> Visitor<'a> for ColorVisitor {
         Ok(ColorValue::Name(value))
     }
 }
+
+impl<'a> Visitor<'a> for StyleVisitor {
+    type Value = StyleValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} or a style table", ColorVisitor::EXPECT_MSG)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<StyleValue, E> {
+        ColorVisitor.visit_i64(value).map(StyleValue::Color)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<StyleValue, E> {
+        ColorVisitor.visit_u64(value).map(StyleValue::Color)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<StyleValue, E> {
+        ColorVisitor.visit_str(value).map(StyleValue::Color)
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<StyleValue, E> {
+        ColorVisitor.visit_string(value).map(StyleValue::Color)
+    }
+
+    fn visit_map<M: MapAccess<'a>>(self, map: M) -> Result<StyleValue, M::Error> {
+        StyleTable::deserialize(MapAccessDeserializer::new(map)).map(StyleValue::Table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rgb() {
+        assert_eq!(
+            ColorCode::parse("#1e6fA8"),
+            Some(ColorCode::Rgb(0x1e, 0x6f, 0xa8))
+        );
+        assert_eq!(ColorCode::parse("1e6fa8"), None);
+        assert_eq!(ColorCode::parse("#1e6fa"), None);
+        assert_eq!(ColorCode::parse("#1e6fa8f"), None);
+        assert_eq!(ColorCode::parse("#1e6fg8"), None);
+        assert_eq!(ColorCode::parse("#+1+2+3"), None);
+    }
+
+    #[test]
+    fn display_rgb() {
+        assert_eq!(ColorCode::Index(42).to_string(), "42");
+        assert_eq!(ColorCode::Rgb(0x1e, 0x6f, 0xa8).to_string(), "#1e6fa8");
+    }
+
+    #[test]
+    fn lookup_colors() {
+        let colors = Colors::default();
+        assert_eq!(colors.lookup("red"), Some(ColorCode::Index(1)));
+        assert_eq!(colors.lookup("42"), Some(ColorCode::Index(42)));
+        assert_eq!(colors.lookup("#000000"), Some(ColorCode::Rgb(0, 0, 0)));
+        assert_eq!(colors.lookup("no-such-color"), None);
+    }
+
+    #[test]
+    fn downsample() {
+        assert_eq!(ColorCode::Index(7).to_index(), 7);
+        assert_eq!(ColorCode::Rgb(0, 0, 0).to_index(), 16);
+        assert_eq!(ColorCode::Rgb(255, 255, 255).to_index(), 231);
+        assert_eq!(ColorCode::Rgb(255, 0, 0).to_index(), 196);
+        assert_eq!(ColorCode::Rgb(0x87, 0xaf, 0xd7).to_index(), 110);
+        assert_eq!(ColorCode::Rgb(128, 128, 128).to_index(), 244);
+        assert_eq!(ColorCode::Rgb(0x1c, 0x1c, 0x1c).to_index(), 234);
+    }
+
+    #[test]
+    fn lookup_styles() {
+        #[derive(Deserialize)]
+        struct Styles {
+            plain: StyleValue,
+            table: StyleValue,
+            bad: StyleValue,
+        }
+
+        let styles = toml::from_str::<Styles>(
+            r##"
+            plain = "red"
+            table = { fg = 42, bg = "#1e6fa8", bold = true, underline = true }
+            bad = { fg = "no-such-color" }
+            "##,
+        )
+        .unwrap();
+
+        let colors = Colors::default();
+        let style = colors.lookup_style(&styles.plain).unwrap();
+        assert_eq!(style, Style::from(ColorCode::Index(1)));
+
+        let style = colors.lookup_style(&styles.table).unwrap();
+        assert_eq!(style.fg, Some(ColorCode::Index(42)));
+        assert_eq!(style.bg, Some(ColorCode::Rgb(0x1e, 0x6f, 0xa8)));
+        assert_eq!(style.attrs, Attrs::BOLD | Attrs::UNDERLINE);
+        assert!(!style.attrs.contains(Attrs::ITALIC));
+
+        assert!(colors.lookup_style(&styles.bad).is_err());
+    }
+
+    #[test]
+    fn unknown_style_field() {
+        let style = toml::from_str::<HashMap<String, StyleValue>>(r#"foo = { blink = true }"#);
+        assert!(style.is_err());
+    }
+}
@@ -18,15 +18,37 @@
 //! * `$HOME/.pedrc`
 //! * `$HOME/.ped/pedrc`
 //! * `$HOME/.config/ped/pedrc`
+//!
+//! Settings that apply to individual editors can be overridden for files whose path
+//! matches a glob pattern using `[[overrides]]` tables, and for files of a particular
+//! syntax using the `[settings]` table of its syntax configuration. The effective
+//! settings of an editor are resolved via [`Configuration::editor_settings()`],
+//! which also applies properties from [EditorConfig](https://editorconfig.org) files
+//! unless disabled using the `editorconfig` setting.
+//!
+//! Named themes are loaded separately via [`Themes::load()`], which tries to locate
+//! theme files in one of the following directories in order of precedence:
+//!
+//! * `$HOME/.ped/themes`
+//! * `$HOME/.config/ped/themes`
 
 use crate::bind::Bindings;
-use crate::color::{ColorValue, Colors};
+use crate::color::{ColorCode, ColorValue, Colors, Shade, StyleValue};
+use crate::editorconfig;
 use crate::error::{Error, Result};
+use crate::format::BannerFormat;
+use crate::glob;
+use crate::io::{Format, LineEnding};
+use crate::op::Step;
 use crate::opt::Options;
-use crate::syntax::Registry;
+use crate::syntax::{Registry, Syntax};
 use crate::sys::{self, AsString};
-use serde::Deserialize;
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use regex_lite::Regex;
+use serde::{de, Deserialize, Deserializer};
+use std::cell::Cell;
+use std::cmp;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::Path;
 use std::rc::Rc;
@@ -40,14 +62,26 @@ pub struct Configuration {
     /// A map of color names to color values.
     pub colors: Colors,
 
-    /// A collection of configurable colors.
-    pub theme: Theme,
+    /// A collection of configurable colors, which can be replaced at runtime by
+    /// selecting one of the named [`themes`](Self::themes).
+    theme: Cell<Theme>,
+
+    /// A registry of named themes.
+    pub themes: Themes,
 
-    /// A map of key sequences to editing operations.
+    /// A map of key sequences to editing operations, including user-defined commands.
     pub bindings: Bindings,
 
     /// A registry of syntax configurations.
     pub registry: Registry,
+
+    /// A list of editor settings that apply to files whose path matches a glob
+    /// pattern, in order of appearance.
+    overrides: Vec<PathOverride>,
+
+    /// Editor settings given as command line options, which take precedence over all
+    /// other settings.
+    opt_settings: SettingsOverride,
 }
 
 pub type ConfigurationRef = Rc<Configuration>;
@@ -55,34 +89,163 @@ pub type ConfigurationRef = Rc<Configuration>;
 pub struct Settings {
     pub spotlight: bool,
     pub lines: bool,
+    pub wrap: bool,
+    pub eol: bool,
+    pub tab_glyph: bool,
+    pub tab_hard: bool,
+    pub tab_size: u32,
+    pub trim_trailing_whitespace: bool,
+    pub final_newline: bool,
+    pub whitespace: Whitespace,
+    pub rulers: Vec<u32>,
+    pub ruler_warning: bool,
+    pub theme: Option<String>,
+    pub banner_format: Option<BannerFormat>,
+    pub editorconfig: bool,
+}
+
+/// The effective settings of an individual editor, which are resolved from
+/// [`Settings`] and any applicable overrides.
+#[derive(Clone)]
+pub struct EditorSettings {
+    pub spotlight: bool,
+    pub lines: bool,
+    pub wrap: bool,
     pub eol: bool,
+    pub tab_glyph: bool,
     pub tab_hard: bool,
     pub tab_size: u32,
+
+    /// Indicates whether trailing whitespace is removed from all lines before the
+    /// buffer is saved.
+    pub trim_trailing_whitespace: bool,
+
+    /// Indicates whether a `\n` is appended to a nonempty buffer that does not
+    /// already end with one before the buffer is saved.
+    pub final_newline: bool,
+
+    /// Determines which spaces and tabs are made visible.
+    pub whitespace: Whitespace,
+
+    /// Columns painted as vertical guides, where each value is the number of columns
+    /// preceding the guide.
+    pub rulers: Vec<u32>,
+
+    /// Conventions applied when writing the buffer to a file.
+    pub format: Format,
+
+    /// Descriptions of the overrides that were applied, in order of application.
+    pub overrides: Vec<String>,
+}
+
+/// The modes of making whitespace visible.
+#[derive(Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Whitespace {
+    /// Spaces and tabs are displayed normally.
+    None,
+
+    /// Trailing spaces and tabs are shown using special characters and highlighted.
+    Trailing,
+
+    /// All spaces and tabs are shown using special characters, and trailing spaces
+    /// and tabs are also highlighted.
+    All,
+}
+
+/// A partial collection of editor settings, where each setting that is `None` defers
+/// to the corresponding setting of lower precedence.
+#[derive(Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SettingsOverride {
+    spotlight: Option<bool>,
+    lines: Option<bool>,
+    wrap: Option<bool>,
+    eol: Option<bool>,
+
+    #[serde(rename = "tab-glyph")]
+    tab_glyph: Option<bool>,
+
+    #[serde(rename = "tab-hard")]
+    tab_hard: Option<bool>,
+
+    #[serde(rename = "tab-size")]
+    tab_size: Option<u32>,
+
+    #[serde(rename = "trim-trailing-whitespace")]
+    trim_trailing_whitespace: Option<bool>,
+
+    #[serde(rename = "final-newline")]
+    final_newline: Option<bool>,
+
+    whitespace: Option<Whitespace>,
+    rulers: Option<Vec<u32>>,
+
+    #[serde(skip)]
+    end_of_line: Option<LineEnding>,
+
+    #[serde(skip)]
+    bom: Option<bool>,
 }
 
+/// Editor settings that apply to files whose path matches a glob pattern.
+struct PathOverride {
+    glob: String,
+    re: Regex,
+    settings: SettingsOverride,
+}
+
+#[derive(Copy, Clone)]
 pub struct Theme {
-    pub text_fg: u8,
-    pub text_bg: u8,
-    pub select_bg: u8,
-    pub spotlight_bg: u8,
-    pub whitespace_fg: u8,
-    pub accent_fg: u8,
-    pub echo_fg: u8,
-    pub prompt_fg: u8,
-    pub banner_fg: u8,
-    pub active_bg: u8,
-    pub inactive_bg: u8,
-    pub margin_fg: u8,
-    pub margin_bg: u8,
+    pub text_fg: Shade,
+    pub text_bg: Shade,
+    pub select_bg: Shade,
+    pub spotlight_bg: Shade,
+    pub bracket_bg: Shade,
+    pub whitespace_fg: Shade,
+    pub trailing_bg: Shade,
+    pub ruler_bg: Shade,
+    pub accent_fg: Shade,
+    pub echo_fg: Shade,
+    pub prompt_fg: Shade,
+    pub banner_fg: Shade,
+    pub active_bg: Shade,
+    pub inactive_bg: Shade,
+    pub margin_fg: Shade,
+    pub margin_bg: Shade,
+}
+
+/// A registry of themes identified by name.
+#[derive(Default)]
+pub struct Themes {
+    theme_map: BTreeMap<String, Theme>,
 }
 
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
 struct ExternalConfiguration {
     settings: Option<ExternalSettings>,
-    colors: Option<HashMap<String, u8>>,
+    colors: Option<HashMap<String, ColorValue>>,
     theme: Option<ExternalTheme>,
+    commands: Option<IndexMap<String, Vec<ExternalStep>>>,
     bindings: Option<HashMap<String, String>>,
+    overrides: Option<Vec<ExternalOverride>>,
+}
+
+/// An entry of the form `{ path = "<glob>", ... }`, where the remaining fields are
+/// editor settings.
+struct ExternalOverride {
+    path: String,
+    settings: SettingsOverride,
+}
+
+/// A step in a user-defined command, which is either the name of an editing operation
+/// or a table of the form `{ text = "..." }` containing literal text.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExternalStep {
+    Op(String),
+    Text { text: String },
 }
 
 #[derive(Deserialize)]
@@ -90,77 +253,331 @@ struct ExternalConfiguration {
 struct ExternalSettings {
     spotlight: Option<bool>,
     lines: Option<bool>,
+    wrap: Option<bool>,
     eol: Option<bool>,
 
+    #[serde(rename = "tab-glyph")]
+    tab_glyph: Option<bool>,
+
     #[serde(rename = "tab-hard")]
     tab_hard: Option<bool>,
 
     #[serde(rename = "tab-size")]
     tab_size: Option<u32>,
+
+    #[serde(rename = "trim-trailing-whitespace")]
+    trim_trailing_whitespace: Option<bool>,
+
+    #[serde(rename = "final-newline")]
+    final_newline: Option<bool>,
+
+    whitespace: Option<Whitespace>,
+    rulers: Option<Vec<u32>>,
+
+    #[serde(rename = "ruler-warning")]
+    ruler_warning: Option<bool>,
+
+    theme: Option<String>,
+
+    #[serde(rename = "banner-format")]
+    banner_format: Option<String>,
+
+    editorconfig: Option<bool>,
 }
 
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
 struct ExternalTheme {
     #[serde(rename = "text-fg")]
-    text_fg: Option<ColorValue>,
+    text_fg: Option<StyleValue>,
 
     #[serde(rename = "text-bg")]
-    text_bg: Option<ColorValue>,
+    text_bg: Option<StyleValue>,
 
     #[serde(rename = "select-bg")]
-    select_bg: Option<ColorValue>,
+    select_bg: Option<StyleValue>,
 
     #[serde(rename = "spotlight-bg")]
-    spotlight_bg: Option<ColorValue>,
+    spotlight_bg: Option<StyleValue>,
+
+    #[serde(rename = "bracket-bg")]
+    bracket_bg: Option<StyleValue>,
 
     #[serde(rename = "whitespace-fg")]
-    whitespace_fg: Option<ColorValue>,
+    whitespace_fg: Option<StyleValue>,
+
+    #[serde(rename = "trailing-bg")]
+    trailing_bg: Option<StyleValue>,
+
+    #[serde(rename = "ruler-bg")]
+    ruler_bg: Option<StyleValue>,
 
     #[serde(rename = "accent-fg")]
-    accent_fg: Option<ColorValue>,
+    accent_fg: Option<StyleValue>,
 
     #[serde(rename = "echo-fg")]
-    echo_fg: Option<ColorValue>,
+    echo_fg: Option<StyleValue>,
 
     #[serde(rename = "prompt-fg")]
-    prompt_fg: Option<ColorValue>,
+    prompt_fg: Option<StyleValue>,
 
     #[serde(rename = "banner-fg")]
-    banner_fg: Option<ColorValue>,
+    banner_fg: Option<StyleValue>,
 
     #[serde(rename = "active-bg")]
-    active_bg: Option<ColorValue>,
+    active_bg: Option<StyleValue>,
 
     #[serde(rename = "inactive-bg")]
-    inactive_bg: Option<ColorValue>,
+    inactive_bg: Option<StyleValue>,
 
     #[serde(rename = "margin-fg")]
-    margin_fg: Option<ColorValue>,
+    margin_fg: Option<StyleValue>,
 
     #[serde(rename = "margin-bg")]
-    margin_bg: Option<ColorValue>,
+    margin_bg: Option<StyleValue>,
 }
 
 impl Settings {
     /// Applies the external settings `ext` on top of `self`.
-    fn apply(&mut self, ext: Option<ExternalSettings>) {
+    ///
+    /// An error is returned if the banner format is not valid.
+    fn apply(&mut self, ext: Option<ExternalSettings>) -> Result<()> {
         if let Some(ext) = ext {
             self.spotlight = ext.spotlight.unwrap_or(self.spotlight);
             self.lines = ext.lines.unwrap_or(self.lines);
+            self.wrap = ext.wrap.unwrap_or(self.wrap);
             self.eol = ext.eol.unwrap_or(self.eol);
+            self.tab_glyph = ext.tab_glyph.unwrap_or(self.tab_glyph);
             self.tab_hard = ext.tab_hard.unwrap_or(self.tab_hard);
             self.tab_size = ext.tab_size.unwrap_or(self.tab_size);
+            self.trim_trailing_whitespace = ext
+                .trim_trailing_whitespace
+                .unwrap_or(self.trim_trailing_whitespace);
+            self.final_newline = ext.final_newline.unwrap_or(self.final_newline);
+            self.whitespace = ext.whitespace.unwrap_or(self.whitespace);
+            self.rulers = ext.rulers.unwrap_or(self.rulers.clone());
+            self.ruler_warning = ext.ruler_warning.unwrap_or(self.ruler_warning);
+            self.theme = ext.theme.or(self.theme.take());
+            if let Some(format) = ext.banner_format {
+                self.banner_format = Some(BannerFormat::parse(&format)?);
+            }
+            self.editorconfig = ext.editorconfig.unwrap_or(self.editorconfig);
         }
+        Ok(())
     }
 
     /// Applies the relevant settings from `opts` on top of `self`.
     pub fn apply_opts(&mut self, opts: &Options) {
         self.spotlight = opts.spotlight.unwrap_or(self.spotlight);
         self.lines = opts.lines.unwrap_or(self.lines);
+        self.wrap = opts.wrap.unwrap_or(self.wrap);
         self.eol = opts.eol.unwrap_or(self.eol);
+        self.tab_glyph = opts.tab_glyph.unwrap_or(self.tab_glyph);
         self.tab_hard = opts.tab_hard.unwrap_or(self.tab_hard);
         self.tab_size = opts.tab_size.unwrap_or(self.tab_size);
+        self.theme = opts.theme_name.clone().or(self.theme.take());
+    }
+}
+
+impl EditorSettings {
+    /// Applies the settings in `ext` on top of `self`, noting the override using
+    /// `desc` if any setting is present.
+    fn apply(&mut self, ext: &SettingsOverride, desc: Option<String>) {
+        self.spotlight = ext.spotlight.unwrap_or(self.spotlight);
+        self.lines = ext.lines.unwrap_or(self.lines);
+        self.wrap = ext.wrap.unwrap_or(self.wrap);
+        self.eol = ext.eol.unwrap_or(self.eol);
+        self.tab_glyph = ext.tab_glyph.unwrap_or(self.tab_glyph);
+        self.tab_hard = ext.tab_hard.unwrap_or(self.tab_hard);
+        self.tab_size = ext.tab_size.unwrap_or(self.tab_size);
+        self.trim_trailing_whitespace = ext
+            .trim_trailing_whitespace
+            .unwrap_or(self.trim_trailing_whitespace);
+        self.final_newline = ext.final_newline.unwrap_or(self.final_newline);
+        self.whitespace = ext.whitespace.unwrap_or(self.whitespace);
+        if let Some(ref rulers) = ext.rulers {
+            self.rulers = rulers.clone();
+        }
+        self.format.eol = ext.end_of_line.or(self.format.eol);
+        self.format.bom = ext.bom.or(self.format.bom);
+        if let Some(desc) = desc {
+            if !ext.is_empty() {
+                self.overrides.push(desc);
+            }
+        }
+    }
+}
+
+impl From<&Settings> for EditorSettings {
+    fn from(settings: &Settings) -> EditorSettings {
+        EditorSettings {
+            spotlight: settings.spotlight,
+            lines: settings.lines,
+            wrap: settings.wrap,
+            eol: settings.eol,
+            tab_glyph: settings.tab_glyph,
+            tab_hard: settings.tab_hard,
+            tab_size: settings.tab_size,
+            trim_trailing_whitespace: settings.trim_trailing_whitespace,
+            final_newline: settings.final_newline,
+            whitespace: settings.whitespace,
+            rulers: settings.rulers.clone(),
+            format: Format::default(),
+            overrides: Vec::new(),
+        }
+    }
+}
+
+impl Whitespace {
+    /// Returns the mode that follows `self` when cycling through all modes.
+    pub fn next(self) -> Whitespace {
+        match self {
+            Whitespace::None => Whitespace::Trailing,
+            Whitespace::Trailing => Whitespace::All,
+            Whitespace::All => Whitespace::None,
+        }
+    }
+
+    /// Returns the name of the mode as it appears in the configuration.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Whitespace::None => "none",
+            Whitespace::Trailing => "trailing",
+            Whitespace::All => "all",
+        }
+    }
+}
+
+impl SettingsOverride {
+    /// Returns `true` if none of the settings are present.
+    pub fn is_empty(&self) -> bool {
+        self.spotlight.is_none()
+            && self.lines.is_none()
+            && self.wrap.is_none()
+            && self.eol.is_none()
+            && self.tab_glyph.is_none()
+            && self.tab_hard.is_none()
+            && self.tab_size.is_none()
+            && self.trim_trailing_whitespace.is_none()
+            && self.final_newline.is_none()
+            && self.whitespace.is_none()
+            && self.rulers.is_none()
+            && self.end_of_line.is_none()
+            && self.bom.is_none()
+    }
+
+    /// Returns the settings corresponding to the EditorConfig properties in `props`,
+    /// ignoring properties and values that are not recognized.
+    fn from_editorconfig(props: &HashMap<String, String>) -> SettingsOverride {
+        let get = |name: &str| props.get(name).map(|value| value.as_str());
+        let number = |name: &str| get(name).and_then(|value| value.parse::<u32>().ok());
+        let boolean = |name: &str| match get(name) {
+            Some("true") => Some(true),
+            Some("false") => Some(false),
+            _ => None,
+        };
+
+        // Width of tab stops is the width of tabs when indenting with tabs, otherwise
+        // the number of spaces per indentation level, where each defaults to the
+        // other as prescribed by the specification. An indentation size of `tab`
+        // explicitly refers to the width of tabs.
+        let tab_hard = match get("indent_style") {
+            Some("tab") => Some(true),
+            Some("space") => Some(false),
+            _ => None,
+        };
+        let tab_width = number("tab_width");
+        let indent_size = match get("indent_size") {
+            Some("tab") => tab_width,
+            _ => number("indent_size"),
+        };
+        let tab_size = if tab_hard == Some(true) {
+            tab_width.or(indent_size)
+        } else {
+            indent_size.or(tab_width)
+        };
+
+        SettingsOverride {
+            tab_hard,
+            tab_size,
+            trim_trailing_whitespace: boolean("trim_trailing_whitespace"),
+            final_newline: boolean("insert_final_newline"),
+            end_of_line: match get("end_of_line") {
+                Some("lf") => Some(LineEnding::Lf),
+                Some("crlf") => Some(LineEnding::Crlf),
+                Some("cr") => Some(LineEnding::Cr),
+                _ => None,
+            },
+            bom: match get("charset") {
+                Some("utf-8") => Some(false),
+                Some("utf-8-bom") => Some(true),
+                _ => None,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&Options> for SettingsOverride {
+    fn from(opts: &Options) -> SettingsOverride {
+        SettingsOverride {
+            spotlight: opts.spotlight,
+            lines: opts.lines,
+            wrap: opts.wrap,
+            eol: opts.eol,
+            tab_glyph: opts.tab_glyph,
+            tab_hard: opts.tab_hard,
+            tab_size: opts.tab_size,
+            ..Default::default()
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ExternalOverride {
+    /// Deserializes `path` separately so that the remaining fields are subject to the
+    /// same validation as all other sources of [`SettingsOverride`].
+    fn deserialize<D>(deserializer: D) -> std::result::Result<ExternalOverride, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut table = toml::Table::deserialize(deserializer)?;
+        let path = match table.remove("path") {
+            Some(toml::Value::String(path)) => path,
+            Some(value) => {
+                return Err(de::Error::invalid_type(
+                    de::Unexpected::Other(value.type_str()),
+                    &"a string",
+                ))
+            }
+            None => return Err(de::Error::missing_field("path")),
+        };
+        let settings =
+            SettingsOverride::deserialize(toml::Value::Table(table)).map_err(de::Error::custom)?;
+        Ok(ExternalOverride { path, settings })
+    }
+}
+
+impl PathOverride {
+    /// Returns an override for files whose path matches `glob`, where a pattern not
+    /// starting with `/` or `~/` may match any trailing sequence of path components.
+    ///
+    /// An error is returned if `glob` cannot be translated into a valid regular
+    /// expression.
+    fn new(glob: &str, settings: SettingsOverride) -> Result<PathOverride> {
+        let pattern = if let Some(rest) = glob.strip_prefix("~/") {
+            let home = glob::to_pattern(&format!("{}/", sys::home_dir().as_string()));
+            format!("^{home}{}$", glob::to_pattern(rest))
+        } else if glob.starts_with('/') {
+            format!("^{}$", glob::to_pattern(glob))
+        } else {
+            format!("(?:^|/){}$", glob::to_pattern(glob))
+        };
+        let re = Regex::new(&pattern).map_err(|e| Error::invalid_regex(glob, &e))?;
+        Ok(PathOverride {
+            glob: glob.to_string(),
+            re,
+            settings,
+        })
     }
 }
 
@@ -169,56 +586,86 @@ impl Default for Settings {
         Settings {
             spotlight: true,
             lines: true,
+            wrap: true,
             eol: false,
+            tab_glyph: true,
             tab_hard: false,
             tab_size: 4,
+            trim_trailing_whitespace: false,
+            final_newline: false,
+            whitespace: Whitespace::None,
+            rulers: Vec::new(),
+            ruler_warning: false,
+            theme: None,
+            banner_format: None,
+            editorconfig: true,
         }
     }
 }
 
 impl Theme {
-    const TEXT_FG: u8 = 252;
-    const TEXT_BG: u8 = 233;
-    const SELECT_BG: u8 = 88;
-    const SPOTLIGHT_BG: u8 = 234;
-    const WHITSPACE_FG: u8 = 243;
-    const ACCENT_FG: u8 = 180;
-    const ECHO_FG: u8 = 208;
-    const PROMPT_FG: u8 = 102;
-    const BANNER_FG: u8 = 254;
-    const ACTIVE_BG: u8 = 60;
-    const INACTIVE_BG: u8 = 237;
-    const MARGIN_FG: u8 = 61;
-    const MARGIN_BG: u8 = 234;
+    const TEXT_FG: Shade = Shade::new(ColorCode::Index(252));
+    const TEXT_BG: Shade = Shade::new(ColorCode::Index(233));
+    const SELECT_BG: Shade = Shade::new(ColorCode::Index(88));
+    const SPOTLIGHT_BG: Shade = Shade::new(ColorCode::Index(234));
+    const BRACKET_BG: Shade = Shade::new(ColorCode::Index(239));
+    const WHITSPACE_FG: Shade = Shade::new(ColorCode::Index(243));
+    const TRAILING_BG: Shade = Shade::new(ColorCode::Index(52));
+    const RULER_BG: Shade = Shade::new(ColorCode::Index(236));
+    const ACCENT_FG: Shade = Shade::new(ColorCode::Index(180));
+    const ECHO_FG: Shade = Shade::new(ColorCode::Index(208));
+    const PROMPT_FG: Shade = Shade::new(ColorCode::Index(102));
+    const BANNER_FG: Shade = Shade::new(ColorCode::Index(254));
+    const ACTIVE_BG: Shade = Shade::new(ColorCode::Index(60));
+    const INACTIVE_BG: Shade = Shade::new(ColorCode::Index(237));
+    const MARGIN_FG: Shade = Shade::new(ColorCode::Index(61));
+    const MARGIN_BG: Shade = Shade::new(ColorCode::Index(234));
 
     /// Applies the external theme `ext` on top of `self`.
     fn apply(&mut self, ext: Option<ExternalTheme>, colors: &Colors) -> Result<()> {
-        fn resolve(color: u8, try_color: &Option<ColorValue>, colors: &Colors) -> Result<u8> {
-            if let Some(try_color) = try_color {
-                if let Some(color) = colors.lookup_value(&try_color) {
-                    Ok(color)
-                } else {
-                    Err(Error::invalid_color(&try_color.to_string()))
+        // A single color applies to the foreground or background depending on the
+        // nature of the theme entry, whereas a style table may also include text
+        // attributes, though only the color relevant to the entry is used.
+        fn resolve(
+            shade: Shade,
+            try_style: &Option<StyleValue>,
+            fg: bool,
+            colors: &Colors,
+        ) -> Result<Shade> {
+            match try_style {
+                Some(StyleValue::Color(try_color)) => colors
+                    .lookup_value(try_color)
+                    .map(Shade::new)
+                    .ok_or_else(|| Error::invalid_color(&try_color.to_string())),
+                Some(try_style) => {
+                    let style = colors.lookup_style(try_style)?;
+                    let color = if fg { style.fg } else { style.bg };
+                    Ok(Shade {
+                        color: color.unwrap_or(shade.color),
+                        attrs: style.attrs,
+                    })
                 }
-            } else {
-                Ok(color)
+                None => Ok(shade),
             }
         }
 
         if let Some(ext) = ext {
-            self.text_fg = resolve(self.text_fg, &ext.text_fg, colors)?;
-            self.text_bg = resolve(self.text_bg, &ext.text_bg, colors)?;
-            self.select_bg = resolve(self.select_bg, &ext.select_bg, colors)?;
-            self.spotlight_bg = resolve(self.spotlight_bg, &ext.spotlight_bg, colors)?;
-            self.whitespace_fg = resolve(self.whitespace_fg, &ext.whitespace_fg, colors)?;
-            self.accent_fg = resolve(self.accent_fg, &ext.accent_fg, colors)?;
-            self.echo_fg = resolve(self.echo_fg, &ext.echo_fg, colors)?;
-            self.prompt_fg = resolve(self.prompt_fg, &ext.prompt_fg, colors)?;
-            self.banner_fg = resolve(self.banner_fg, &ext.banner_fg, colors)?;
-            self.active_bg = resolve(self.active_bg, &ext.active_bg, colors)?;
-            self.inactive_bg = resolve(self.inactive_bg, &ext.inactive_bg, colors)?;
-            self.margin_fg = resolve(self.margin_fg, &ext.margin_fg, colors)?;
-            self.margin_bg = resolve(self.margin_bg, &ext.margin_bg, colors)?;
+            self.text_fg = resolve(self.text_fg, &ext.text_fg, true, colors)?;
+            self.text_bg = resolve(self.text_bg, &ext.text_bg, false, colors)?;
+            self.select_bg = resolve(self.select_bg, &ext.select_bg, false, colors)?;
+            self.spotlight_bg = resolve(self.spotlight_bg, &ext.spotlight_bg, false, colors)?;
+            self.bracket_bg = resolve(self.bracket_bg, &ext.bracket_bg, false, colors)?;
+            self.whitespace_fg = resolve(self.whitespace_fg, &ext.whitespace_fg, true, colors)?;
+            self.trailing_bg = resolve(self.trailing_bg, &ext.trailing_bg, false, colors)?;
+            self.ruler_bg = resolve(self.ruler_bg, &ext.ruler_bg, false, colors)?;
+            self.accent_fg = resolve(self.accent_fg, &ext.accent_fg, true, colors)?;
+            self.echo_fg = resolve(self.echo_fg, &ext.echo_fg, true, colors)?;
+            self.prompt_fg = resolve(self.prompt_fg, &ext.prompt_fg, true, colors)?;
+            self.banner_fg = resolve(self.banner_fg, &ext.banner_fg, true, colors)?;
+            self.active_bg = resolve(self.active_bg, &ext.active_bg, false, colors)?;
+            self.inactive_bg = resolve(self.inactive_bg, &ext.inactive_bg, false, colors)?;
+            self.margin_fg = resolve(self.margin_fg, &ext.margin_fg, true, colors)?;
+            self.margin_bg = resolve(self.margin_bg, &ext.margin_bg, false, colors)?;
         }
         Ok(())
     }
@@ -231,7 +678,10 @@ impl Default for Theme {
             text_bg: Self::TEXT_BG,
             select_bg: Self::SELECT_BG,
             spotlight_bg: Self::SPOTLIGHT_BG,
+            bracket_bg: Self::BRACKET_BG,
             whitespace_fg: Self::WHITSPACE_FG,
+            trailing_bg: Self::TRAILING_BG,
+            ruler_bg: Self::RULER_BG,
             accent_fg: Self::ACCENT_FG,
             echo_fg: Self::ECHO_FG,
             prompt_fg: Self::PROMPT_FG,
@@ -280,23 +730,113 @@ impl Configuration {
     /// Applies the relevant settings from `opts` on top of `self`.
     pub fn apply_opts(&mut self, opts: &Options) {
         self.settings.apply_opts(opts);
+        self.opt_settings = SettingsOverride::from(opts);
+    }
+
+    /// Returns the effective settings of an editor using `syntax`, where `path` is
+    /// the path of its file, if any.
+    ///
+    /// The order of precedence, from lowest to highest, is the global settings, the
+    /// settings of `syntax`, properties from EditorConfig files, overrides whose glob
+    /// pattern matches `path` in order of appearance, and finally command line
+    /// options.
+    pub fn editor_settings(&self, path: Option<&str>, syntax: &Syntax) -> EditorSettings {
+        let mut settings = EditorSettings::from(&self.settings);
+        settings.apply(syntax.settings(), Some(format!("syntax {}", syntax.name)));
+        if let Some(path) = path {
+            if self.settings.editorconfig {
+                let props = editorconfig::properties(path);
+                settings.apply(
+                    &SettingsOverride::from_editorconfig(&props),
+                    Some("editorconfig".to_string()),
+                );
+            }
+            for o in self.overrides.iter().filter(|o| o.re.is_match(path)) {
+                settings.apply(&o.settings, Some(format!("path {}", o.glob)));
+            }
+        }
+        settings.apply(&self.opt_settings, None);
+        settings.tab_size = cmp::max(settings.tab_size, 1);
+        settings
+    }
+
+    /// Replaces the registry of named themes with `themes`, and then selects the
+    /// theme named in the settings, if specified.
+    ///
+    /// The theme formed by the configuration itself is added to the registry using
+    /// the name [`Themes::DEFAULT_NAME`].
+    ///
+    /// An error is returned if the theme named in the settings does not exist.
+    pub fn apply_themes(&mut self, themes: Themes) -> Result<()> {
+        self.themes = themes;
+        self.themes.add(Themes::DEFAULT_NAME, self.theme());
+        if let Some(ref name) = self.settings.theme {
+            if let Some(theme) = self.themes.find(name) {
+                self.set_theme(theme);
+            } else {
+                return Err(Error::invalid_theme(name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the current theme.
+    pub fn theme(&self) -> Theme {
+        self.theme.get()
+    }
+
+    /// Replaces the current theme with `theme`.
+    ///
+    /// Note that components which derive colors from the theme must be redrawn in
+    /// order to reflect the change.
+    pub fn set_theme(&self, theme: Theme) {
+        self.theme.set(theme);
     }
 
     /// Applies the external configuration `ext` on top of `self`.
     fn apply(&mut self, ext: ExternalConfiguration) -> Result<()> {
-        self.settings.apply(ext.settings);
+        self.settings.apply(ext.settings)?;
         if let Some(colors) = ext.colors {
-            self.colors.apply(&colors);
+            let mut color_map = HashMap::new();
+            for (name, value) in colors {
+                if let Some(color) = self.colors.lookup_value(&value) {
+                    color_map.insert(name, color);
+                } else {
+                    return Err(Error::invalid_color(&value.to_string()));
+                }
+            }
+            self.colors.apply(&color_map);
+        }
+        self.theme.get_mut().apply(ext.theme, &self.colors)?;
+        if let Some(commands) = ext.commands {
+            for (name, steps) in commands {
+                self.define_command(&name, steps)?;
+            }
         }
-        self.theme.apply(ext.theme, &self.colors)?;
         if let Some(bindings) = ext.bindings {
             for (key_seq, op) in bindings {
                 self.bindings.bind(&key_seq, &op)?;
             }
         }
+        for o in ext.overrides.unwrap_or_default() {
+            self.overrides.push(PathOverride::new(&o.path, o.settings)?);
+        }
         Ok(())
     }
 
+    /// Defines the user command `name` whose `steps` must refer to either predefined
+    /// operations or commands defined earlier in the configuration.
+    fn define_command(&mut self, name: &str, steps: Vec<ExternalStep>) -> Result<()> {
+        let mut cmd_steps = Vec::new();
+        for step in steps {
+            match step {
+                ExternalStep::Op(op) => cmd_steps.extend(self.bindings.resolve(&op)?),
+                ExternalStep::Text { text } => cmd_steps.push(Step::Text(text)),
+            }
+        }
+        self.bindings.define(name, &cmd_steps)
+    }
+
     fn read_file(path: &Path) -> Result<ExternalConfiguration> {
         let content = fs::read_to_string(path).map_err(|e| Error::io(&path.as_string(), e))?;
         toml::from_str::<ExternalConfiguration>(&content)
@@ -311,7 +851,7 @@ impl Configuration {
         Bindings::new(&bindings).unwrap_or_else(|e| panic!("{e}: default bindings failed"))
     }
 
-    const DEFAULT_BINDINGS: [(&'static str, &'static str); 92] = [
+    const DEFAULT_BINDINGS: [(&'static str, &'static str); 115] = [
         // --- exit and cancellation ---
         ("C-q", "quit"),
         // --- help ---
@@ -366,9 +906,16 @@ impl Configuration {
         ("C-l", "scroll-center"),
         ("C-@", "set-mark"),
         ("C-_", "goto-line"),
+        ("M-m", "match-bracket"),
         // --- insertion and removal ---
         ("ret", "insert-line"),
         ("tab", "insert-tab"),
+        ("M-i", "indent-region"),
+        ("M-u", "outdent-region"),
+        ("S-tab", "outdent-region"),
+        ("M-;", "toggle-comment"),
+        ("M-k:w", "trim-whitespace"),
+        ("M-k:n", "final-newline"),
         ("C-d", "remove-after"),
         ("del", "remove-before"),
         ("C-j", "remove-start"),
@@ -391,6 +938,8 @@ impl Configuration {
         ("M-o:e", "open-file-bottom"),
         ("M-o:p", "open-file-above"),
         ("M-o:n", "open-file-below"),
+        ("M-o:b", "open-file-left"),
+        ("M-o:f", "open-file-right"),
         ("C-s", "save-file"),
         ("M-s", "save-file-as"),
         // --- editor handling ---
@@ -405,26 +954,377 @@ impl Configuration {
         ("C-w", "kill-window"),
         ("M-w:0", "close-window"),
         ("M-w:1", "close-other-windows"),
+        ("M-w:2", "split-window-below"),
+        ("M-w:3", "split-window-right"),
+        ("M-w:+", "grow-window"),
+        ("M-w:-", "shrink-window"),
+        ("M-w:=", "balance-windows"),
+        ("M-w:m", "maximize-window"),
         ("M-w:a", "top-window"),
         ("M-w:e", "bottom-window"),
         ("M-w:p", "prev-window"),
         ("M-<", "prev-window"),
         ("M-w:n", "next-window"),
         ("M->", "next-window"),
+        ("M-w:u", "up-window"),
+        ("M-w:d", "down-window"),
+        ("M-w:b", "left-window"),
+        ("M-w:f", "right-window"),
         // --- behaviors ---
         ("C-t", "describe-editor"),
         ("M-t:t", "tab-mode"),
+        ("M-t:w", "wrap-mode"),
+        ("M-t:v", "whitespace-mode"),
+        ("M-t:c", "select-theme"),
+        ("M-t:s", "set-syntax"),
     ];
 }
 
+impl Themes {
+    /// Name of the theme formed by the configuration.
+    pub const DEFAULT_NAME: &str = "default";
+
+    /// A collection of directories to try loading theme files in order of precedence.
+    const TRY_DIRS: [&str; 2] = [".ped/themes", ".config/ped/themes"];
+
+    /// File extension of theme files.
+    const FILE_EXT: &str = "toml";
+
+    /// Returns a theme registry that is initialized using theme files from well-known
+    /// directories, where colors are resolved using `colors`.
+    ///
+    /// An empty registry is returned if none of the well-known directories exist or
+    /// contain any theme files.
+    ///
+    /// An error is returned if any theme file is malformed in any way.
+    pub fn load(colors: &Colors) -> Result<Themes> {
+        let root_path = sys::home_dir();
+        Self::TRY_DIRS
+            .iter()
+            .map(|dir| root_path.join(dir))
+            .find(|dir| sys::is_dir(dir))
+            .map(|dir| Self::load_dir(dir, colors))
+            .unwrap_or_else(|| Ok(Themes::default()))
+    }
+
+    /// Returns a theme registry that is initialized using theme files in `dir`, where
+    /// the name of each theme is the name of the file without its extension.
+    ///
+    /// Each theme is formed by applying the contents of its file on top of the
+    /// default theme.
+    ///
+    /// An error is returned if any theme file is malformed in any way.
+    pub fn load_dir<P: AsRef<Path>>(dir: P, colors: &Colors) -> Result<Themes> {
+        let mut theme_map = BTreeMap::new();
+        for path in sys::list_files(dir.as_ref()) {
+            if path.extension().map(|ext| ext == Self::FILE_EXT) == Some(true) {
+                if let Some(name) = path.file_stem() {
+                    let ext = Self::read_file(&path)?;
+                    let mut theme = Theme::default();
+                    theme.apply(Some(ext), colors)?;
+                    theme_map.insert(name.to_string_lossy().to_string(), theme);
+                }
+            }
+        }
+        Ok(Themes { theme_map })
+    }
+
+    /// Adds `theme` as `name`, replacing any existing theme of the same name.
+    pub fn add(&mut self, name: &str, theme: Theme) {
+        self.theme_map.insert(name.to_string(), theme);
+    }
+
+    /// Returns the theme associated with `name`, otherwise `None`.
+    pub fn find(&self, name: &str) -> Option<Theme> {
+        self.theme_map.get(name).copied()
+    }
+
+    /// Returns an iterator over the theme names in lexicographical order.
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.theme_map.keys()
+    }
+
+    fn read_file(path: &Path) -> Result<ExternalTheme> {
+        let content = fs::read_to_string(path).map_err(|e| Error::io(&path.as_string(), e))?;
+        toml::from_str::<ExternalTheme>(&content).map_err(|e| Error::theme(&path.as_string(), &e))
+    }
+}
+
 impl Default for Configuration {
     fn default() -> Configuration {
         Configuration {
             settings: Settings::default(),
             colors: Colors::default(),
-            theme: Theme::default(),
+            theme: Cell::new(Theme::default()),
+            themes: Themes::default(),
             bindings: Self::init_bindings(),
             registry: Registry::default(),
+            overrides: Vec::new(),
+            opt_settings: SettingsOverride::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn from_props(props: &[(&str, &str)]) -> SettingsOverride {
+        let props = props
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        SettingsOverride::from_editorconfig(&props)
+    }
+
+    #[test]
+    fn editorconfig_tab_size() {
+        // Indentation with spaces prefers indent_size, otherwise tabs prefer
+        // tab_width, where each defaults to the other.
+        let both = [("indent_size", "2"), ("tab_width", "8")];
+        let o = from_props(&both);
+        assert_eq!((o.tab_hard, o.tab_size), (None, Some(2)));
+        let o = from_props(&[("indent_style", "space"), both[0], both[1]]);
+        assert_eq!((o.tab_hard, o.tab_size), (Some(false), Some(2)));
+        let o = from_props(&[("indent_style", "tab"), both[0], both[1]]);
+        assert_eq!((o.tab_hard, o.tab_size), (Some(true), Some(8)));
+        let o = from_props(&[("indent_style", "tab"), ("indent_size", "2")]);
+        assert_eq!((o.tab_hard, o.tab_size), (Some(true), Some(2)));
+        let o = from_props(&[("indent_style", "space"), ("tab_width", "8")]);
+        assert_eq!((o.tab_hard, o.tab_size), (Some(false), Some(8)));
+
+        // Indentation size of tab refers to tab_width regardless of indentation style.
+        let tab = [("indent_size", "tab"), ("tab_width", "8")];
+        let o = from_props(&[("indent_style", "tab"), tab[0], tab[1]]);
+        assert_eq!((o.tab_hard, o.tab_size), (Some(true), Some(8)));
+        let o = from_props(&[("indent_style", "space"), tab[0], tab[1]]);
+        assert_eq!((o.tab_hard, o.tab_size), (Some(false), Some(8)));
+        let o = from_props(&tab);
+        assert_eq!((o.tab_hard, o.tab_size), (None, Some(8)));
+        let o = from_props(&[("indent_style", "tab"), ("indent_size", "tab")]);
+        assert_eq!((o.tab_hard, o.tab_size), (Some(true), None));
+
+        // Values that are not recognized are ignored.
+        let o = from_props(&[("indent_style", "unset"), ("indent_size", "tab")]);
+        assert_eq!((o.tab_hard, o.tab_size), (None, None));
+    }
+
+    #[test]
+    fn editorconfig_format() {
+        let o = from_props(&[
+            ("end_of_line", "crlf"),
+            ("charset", "utf-8-bom"),
+            ("trim_trailing_whitespace", "true"),
+            ("insert_final_newline", "false"),
+        ]);
+        assert!(o.end_of_line == Some(LineEnding::Crlf));
+        assert_eq!(o.bom, Some(true));
+        assert_eq!(o.trim_trailing_whitespace, Some(true));
+        assert_eq!(o.final_newline, Some(false));
+
+        let o = from_props(&[("end_of_line", "native"), ("charset", "latin1")]);
+        assert!(o.end_of_line.is_none());
+        assert_eq!(o.bom, None);
+    }
+
+    fn apply_toml(config: &mut Configuration, content: &str) -> Result<()> {
+        config.apply(toml::from_str::<ExternalConfiguration>(content).unwrap())
+    }
+
+    /// Returns the default configuration with `content` applied on top.
+    pub fn build_config(content: &str) -> Configuration {
+        let mut config = Configuration::default();
+        apply_toml(&mut config, content).unwrap();
+        config
+    }
+
+    /// Returns the steps of command `name`, where operations are shown as `None`.
+    fn command_steps<'a>(config: &'a Configuration, name: &str) -> Vec<Option<&'a str>> {
+        config.bindings.commands()[name]
+            .iter()
+            .map(|step| match step {
+                Step::Op(_) => None,
+                Step::Text(text) => Some(text.as_str()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn define_commands() {
+        let mut config = Configuration::default();
+        let content = r#"
+[commands]
+greet = ["insert-line", { text = "hello" }]
+greet-twice = ["greet", { text = ", " }, "greet"]
+
+[bindings]
+"M-k:g" = "greet-twice"
+"#;
+        apply_toml(&mut config, content).unwrap();
+        assert_eq!(
+            config.bindings.commands().keys().collect::<Vec<_>>(),
+            vec!["greet", "greet-twice"]
+        );
+        assert_eq!(command_steps(&config, "greet"), vec![None, Some("hello")]);
+
+        // Commands referenced by other commands are expanded in place.
+        assert_eq!(
+            command_steps(&config, "greet-twice"),
+            vec![None, Some("hello"), Some(", "), None, Some("hello")]
+        );
+    }
+
+    #[test]
+    fn define_invalid_commands() {
+        // Operations must exist and commands must be defined before being referenced.
+        for (content, op) in [
+            ("[commands]\nfoo = [\"no-such-op\"]\n", "no-such-op"),
+            ("[commands]\nfoo = [\"bar\"]\nbar = [\"quit\"]\n", "bar"),
+        ] {
+            let mut config = Configuration::default();
+            match apply_toml(&mut config, content) {
+                Err(Error::InvalidOp { op: name }) => assert_eq!(name, op),
+                _ => panic!("expecting invalid operation: {op}"),
+            }
+        }
+
+        // Predefined operations cannot be redefined.
+        let mut config = Configuration::default();
+        match apply_toml(&mut config, "[commands]\nquit = [\"undo\"]\n") {
+            Err(Error::RedefinedOp { op }) => assert_eq!(op, "quit"),
+            _ => panic!("expecting redefined operation"),
+        }
+    }
+
+    /// Returns a new directory named `name` in the temporary directory containing
+    /// `files`, each of which is a tuple of file name and content.
+    fn build_theme_dir(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("ped-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (file, content) in files {
+            fs::write(dir.join(file), content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn load_theme_dir() {
+        let dir = build_theme_dir(
+            "load-themes",
+            &[
+                ("dark.toml", "text-fg = 231\ntext-bg = 16\n"),
+                ("light.toml", "text-bg = 231\n"),
+                ("notes.txt", "not a theme"),
+            ],
+        );
+        let colors = Colors::default();
+        let themes = Themes::load_dir(&dir, &colors).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Only theme files are loaded, each applied on top of the default theme.
+        assert_eq!(themes.names().collect::<Vec<_>>(), vec!["dark", "light"]);
+        let theme = themes.find("dark").unwrap();
+        assert_eq!(theme.text_fg.color, ColorCode::Index(231));
+        assert_eq!(theme.text_bg.color, ColorCode::Index(16));
+        assert_eq!(theme.select_bg, Theme::default().select_bg);
+        assert!(themes.find("notes").is_none());
+
+        // Theme formed by configuration is added implicitly.
+        let mut config = build_config("[theme]\ntext-bg = 17\n");
+        config.apply_themes(themes).unwrap();
+        assert_eq!(
+            config.themes.names().collect::<Vec<_>>(),
+            vec!["dark", Themes::DEFAULT_NAME, "light"]
+        );
+        let theme = config.themes.find(Themes::DEFAULT_NAME).unwrap();
+        assert_eq!(theme.text_bg.color, ColorCode::Index(17));
+        assert_eq!(config.theme().text_bg.color, ColorCode::Index(17));
+    }
+
+    #[test]
+    fn select_named_theme() {
+        let dir = build_theme_dir("select-theme", &[("dark.toml", "text-bg = 16\n")]);
+        let colors = Colors::default();
+
+        // Theme named in settings replaces theme formed by configuration, which
+        // remains available by its default name.
+        let mut config = build_config("[settings]\ntheme = \"dark\"\n[theme]\ntext-bg = 17\n");
+        config
+            .apply_themes(Themes::load_dir(&dir, &colors).unwrap())
+            .unwrap();
+        assert_eq!(config.theme().text_bg.color, ColorCode::Index(16));
+        let theme = config.themes.find(Themes::DEFAULT_NAME).unwrap();
+        assert_eq!(theme.text_bg.color, ColorCode::Index(17));
+
+        // Theme named in settings must exist.
+        let mut config = build_config("[settings]\ntheme = \"light\"\n");
+        match config.apply_themes(Themes::load_dir(&dir, &colors).unwrap()) {
+            Err(Error::InvalidTheme { name }) => assert_eq!(name, "light"),
+            _ => panic!("expecting invalid theme"),
+        }
+        assert_eq!(config.theme().text_bg, Theme::default().text_bg);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_malformed_theme() {
+        let colors = Colors::default();
+        for (name, content) in [
+            ("syntax", "text-bg = \n"),
+            ("unknown-field", "text-bg = 16\nfoo-bg = 16\n"),
+            ("wrong-type", "text-bg = true\n"),
+        ] {
+            let dir = build_theme_dir(
+                name,
+                &[("dark.toml", "text-bg = 16\n"), ("bad.toml", content)],
+            );
+            let result = Themes::load_dir(&dir, &colors);
+            fs::remove_dir_all(&dir).unwrap();
+            match result {
+                Err(Error::Theme { path, .. }) => assert!(path.ends_with("bad.toml"), "{name}"),
+                _ => panic!("expecting malformed theme: {name}"),
+            }
+        }
+
+        // Colors must also be known.
+        let dir = build_theme_dir(
+            "unknown-color",
+            &[("bad.toml", "text-bg = \"no-such-color\"\n")],
+        );
+        let result = Themes::load_dir(&dir, &colors);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn path_overrides() {
+        let mut config = Configuration::default();
+        let content = r#"
+[[overrides]]
+path = "{Makefile,*.mk}"
+tab-hard = true
+tab-size = 8
+"#;
+        apply_toml(&mut config, content).unwrap();
+        let syntax = Syntax::default();
+        let settings = config.editor_settings(Some("/no/such/dir/Makefile"), &syntax);
+        assert!(settings.tab_hard);
+        assert_eq!(settings.tab_size, 8);
+        assert_eq!(settings.overrides, vec!["path {Makefile,*.mk}"]);
+
+        let settings = config.editor_settings(Some("/no/such/dir/main.rs"), &syntax);
+        assert!(!settings.tab_hard);
+        assert!(settings.overrides.is_empty());
+
+        // Overrides require a path and accept only known settings.
+        for content in [
+            "[[overrides]]\ntab-hard = true\n",
+            "[[overrides]]\npath = 1\n",
+            "[[overrides]]\npath = \"*.mk\"\ntab-hard = 1\n",
+            "[[overrides]]\npath = \"*.mk\"\nfoo = true\n",
+        ] {
+            assert!(toml::from_str::<ExternalConfiguration>(content).is_err());
         }
     }
 }
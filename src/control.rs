@@ -118,6 +118,7 @@ impl Controller {
                 if let Step::Quit = self.process_key(key) {
                     break;
                 } else {
+                    self.env.sync_editors();
                     self.show_cursor();
                 }
             }
@@ -127,6 +128,8 @@ impl Controller {
     fn show_cursor(&mut self) {
         if self.question.is_none() {
             self.env.get_active_editor().borrow_mut().show_cursor();
+        } else {
+            self.input.show_cursor();
         }
     }
 
@@ -144,7 +147,7 @@ impl Controller {
             // circuits detection and bypasses normal indirection of key binding.
             match op::insert_char(&mut self.env, c) {
                 Some(Action::Echo(text)) => self.set_echo(text.as_str()),
-                _ => self.clear_echo(),
+                _ => self.clear_echo_or_warn(),
             }
         } else if key == CTRL_G {
             self.clear_echo();
@@ -168,8 +171,8 @@ impl Controller {
             // Absorb since this event serve no purpose at this time.
         } else {
             self.key_seq.push(key.clone());
-            if let Some(op_fn) = self.config.bindings.find(&self.key_seq) {
-                match op_fn(&mut self.env) {
+            if let Some(op) = self.config.bindings.find(&self.key_seq) {
+                match op.call(&mut self.env) {
                     Some(Action::Quit) => return Step::Quit,
                     Some(Action::Echo(text)) => {
                         self.set_echo(text.as_str());
@@ -179,7 +182,7 @@ impl Controller {
                         self.set_question(inquirer);
                     }
                     None => {
-                        self.clear_echo();
+                        self.clear_echo_or_warn();
                     }
                 }
                 self.clear_keys();
@@ -259,10 +262,11 @@ impl Controller {
                 Some(time)
             }
         } else {
-            let mut editor = self.env.get_active_editor().borrow_mut();
-            if editor.tokenize() {
-                editor.render();
-                editor.show_cursor();
+            let tokenized = self.env.get_active_editor().borrow_mut().tokenize();
+            if tokenized {
+                self.env.get_active_editor().borrow_mut().render();
+                self.env.sync_editors();
+                self.show_cursor();
             }
             None
         };
@@ -319,6 +323,29 @@ impl Controller {
         }
     }
 
+    /// Echoes a warning if enabled and the cursor line of the active editor extends
+    /// beyond the first ruler, otherwise clears the echo.
+    fn clear_echo_or_warn(&mut self) {
+        let warning = if self.config.settings.ruler_warning {
+            let editor = self.env.get_active_editor().borrow();
+            editor.settings().rulers.first().and_then(|&limit| {
+                let cols = editor.line_cols();
+                if cols > limit {
+                    Some(format!("line is {cols} columns, exceeding {limit}"))
+                } else {
+                    None
+                }
+            })
+        } else {
+            None
+        };
+        if let Some(text) = warning {
+            self.set_echo(&text);
+        } else {
+            self.clear_echo();
+        }
+    }
+
     fn resize_echo(&mut self) {
         if let Some(_) = self.last_echo {
             self.echo.resize();
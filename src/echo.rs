@@ -1,23 +1,19 @@
 //! Echo messages to the shared region of a workspace.
 
 use crate::color::Color;
+use crate::width;
 use crate::workspace::WorkspaceRef;
 use crate::writer::Writer;
 
 pub struct Echo {
     workspace: WorkspaceRef,
-    echo_color: Color,
     text: Option<String>,
 }
 
 impl Echo {
     pub fn new(workspace: WorkspaceRef) -> Echo {
-        let config = workspace.borrow().config().clone();
-        let echo_color = Color::new(config.theme.echo_fg, config.theme.text_bg);
-
         Echo {
             workspace,
-            echo_color,
             text: None,
         }
     }
@@ -36,13 +32,25 @@ impl Echo {
         if let Some(ref text) = self.text {
             let (origin, size) = self.workspace.borrow().shared_region();
 
-            // Possibly clip text to fit size constraint of viewable region.
-            let chars = text.chars().take(size.cols as usize).collect::<Vec<_>>();
-            let blank_cols = size.cols - chars.len() as u32;
+            // Color is derived on each draw since the theme may change at runtime.
+            let theme = self.workspace.borrow().config().theme();
+            let echo_color = Color::from_shades(theme.echo_fg, theme.text_bg);
+
+            // Possibly clip text to fit size constraint of viewable region, noting
+            // that characters may occupy more or less than one column.
+            let mut text_cols = 0;
+            let text = text
+                .chars()
+                .take_while(|c| {
+                    text_cols += width::char_width(*c);
+                    text_cols <= size.cols
+                })
+                .collect::<String>();
+            let blank_cols = size.cols - width::str_width(&text);
 
             Writer::new_at(origin)
-                .set_color(self.echo_color)
-                .write_str(chars.into_iter().collect::<String>().as_str())
+                .set_color(echo_color)
+                .write_str(text.as_str())
                 .write_str(" ".repeat(blank_cols as usize).as_str())
                 .send();
         } else {
@@ -6,19 +6,20 @@
 
 use crate::buffer::{Buffer, BufferRef};
 use crate::canvas::{Canvas, CanvasRef};
-use crate::color::Color;
-use crate::config::ConfigurationRef;
+use crate::color::{Color, Shade};
+use crate::config::{ConfigurationRef, EditorSettings, Theme, Whitespace};
 use crate::grid::Cell;
 use crate::search::Pattern;
 use crate::size::{Point, Size};
 use crate::source::Source;
 use crate::syntax::Syntax;
-use crate::token::{Cursor, Tokenizer, TokenizerRef};
+use crate::token::{Cursor, Job, Progress, Tokenizer, TokenizerRef};
+use crate::width;
 use crate::window::{Banner, BannerRef, Window, WindowRef};
-use std::cell::{Ref, RefCell, RefMut};
+use std::cell::{self, Ref, RefCell, RefMut};
 use std::cmp;
 use std::ops::Range;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use std::time::Instant;
 
 /// An editing session containing a [`kernel`](EditorKernel) that carries out most
@@ -74,7 +75,8 @@ pub trait ImmutableEditor {
     ///
     /// The _line_ and _column_ values are `0`-based. Note that neither of these values
     /// are bounded by the size of the display, which is the case with
-    /// [`cursor`](Self::cursor).
+    /// [`cursor`](Self::cursor). The _column_ is a display column, which accounts for
+    /// tabs and wide characters as well as rows wrapped from the start of the line.
     fn location(&self) -> Point;
 
     /// Returns the number of rows available on the editor canvas.
@@ -92,6 +94,36 @@ pub trait ImmutableEditor {
     /// Sets the tab mode based on the value of `hard`.
     fn set_tab(&mut self, hard: bool);
 
+    /// Returns `true` if long lines wrap onto subsequent rows.
+    fn get_wrap(&self) -> bool;
+
+    /// Sets the wrap mode based on the value of `wrap`, where `false` indicates that
+    /// lines are clipped and the display scrolls horizontally.
+    fn set_wrap(&mut self, wrap: bool);
+
+    /// Returns the mode that determines which spaces and tabs are made visible.
+    fn get_whitespace(&self) -> Whitespace;
+
+    /// Sets the mode that determines which spaces and tabs are made visible.
+    fn set_whitespace(&mut self, whitespace: Whitespace);
+
+    /// Returns the effective settings of this editor, which reflect any changes to the
+    /// tab, wrap, and whitespace modes.
+    fn settings(&self) -> &EditorSettings;
+
+    /// Returns the settings that would be in effect if the source of this editor were
+    /// the file at `path`, which reflect any changes to the tab, wrap, and whitespace
+    /// modes.
+    fn settings_for(&self, path: &str) -> EditorSettings;
+
+    /// Returns the number of columns occupied by the line containing the cursor,
+    /// excluding the line terminator, as if the line were not wrapped.
+    fn line_cols(&self) -> u32;
+
+    /// Replaces the syntax configuration with `syntax` and tokenizes the buffer
+    /// again.
+    fn set_syntax(&mut self, syntax: Syntax);
+
     /// Sets the cursor location and corresponding buffer position to `cursor`, though
     /// the final cursor location is constrained by end-of-line and end-of-buffer
     /// boundaries.
@@ -120,10 +152,16 @@ pub trait ImmutableEditor {
 
     /// Tries to move the cursor _backward_ from the current buffer position by `len`
     /// characters.
+    ///
+    /// A character in this context is a grapheme cluster, such as a letter followed
+    /// by combining marks, which ensures that the cursor never lands inside of a
+    /// combined character.
     fn move_backward(&mut self, len: usize);
 
     /// Tries to move the cursor _forward_ from the current buffer position by `len`
     /// characters.
+    ///
+    /// See [`move_backward`](Self::move_backward) for the meaning of _character_.
     fn move_forward(&mut self, len: usize);
 
     /// Tries to move the cursor _backward_ by one word from the current buffer
@@ -134,6 +172,14 @@ pub trait ImmutableEditor {
     /// position.
     fn move_forward_word(&mut self);
 
+    /// Tries to move the cursor to the bracket matching the one at the current buffer
+    /// position, or the closing bracket immediately preceding it, returning `true` if
+    /// a matching bracket was found.
+    ///
+    /// Brackets contained inside multi-character tokens, such as strings and
+    /// comments, are ignored.
+    fn move_bracket(&mut self) -> bool;
+
     /// Tries to move the cursor _up_ by the specified number of `try_rows`.
     ///
     /// If `pin` is `true`, then the cursor will remain on the current row if the
@@ -258,8 +304,12 @@ pub trait ImmutableEditor {
     /// guarantee that said state will be restored precisely as it was.
     fn restore(&mut self, capture: &Capture);
 
-    /// Tokenizes the buffer if changes occurred since the last tokenization, returning
-    /// `true` if tokenization occurred and `false` otherwise.
+    /// Tokenizes the entire buffer if changes occurred since the last tokenization or
+    /// the last tokenization was incremental, returning `true` if tokenization
+    /// occurred and `false` otherwise.
+    ///
+    /// Large buffers are tokenized on a background thread, in which case `true` is
+    /// returned once a subsequent call applies the result.
     fn tokenize(&mut self) -> bool;
 
     /// Renders the contents of the editor.
@@ -282,14 +332,57 @@ pub trait ImmutableEditor {
 /// interleaved with mutable operations.
 pub trait MutableEditor: ImmutableEditor {
     /// Inserts the character `c` at the current buffer position.
+    ///
+    /// If the syntax configuration defines a _dedent_ rule that matches the text of
+    /// the current line preceding the cursor, then the line is dedented by one level.
     fn insert_char(&mut self, c: char);
 
+    /// Inserts a new line at the current buffer position, which is indented using the
+    /// leading whitespace of the current line.
+    ///
+    /// If the syntax configuration defines an _indent_ rule that matches the text
+    /// preceding the cursor, then the new line is indented one additional level.
+    /// Likewise, a _dedent_ rule that matches the text following the cursor removes
+    /// one level of indentation.
+    fn insert_line(&mut self);
+
     /// Inserts the string slice `str` at the current buffer position.
     fn insert_str(&mut self, text: &str);
 
     /// Inserts the `TAB` character.
     fn insert_tab(&mut self);
 
+    /// Indents the lines covered by the mark, or the current line if no mark is set,
+    /// by one level.
+    ///
+    /// Empty lines are not indented. The mark is preserved and all changes are
+    /// recorded as a single change in the undo stack.
+    fn indent_region(&mut self);
+
+    /// Outdents the lines covered by the mark, or the current line if no mark is set,
+    /// by one level.
+    ///
+    /// The mark is preserved and all changes are recorded as a single change in the
+    /// undo stack.
+    fn outdent_region(&mut self);
+
+    /// Comments or uncomments the lines covered by the mark, or the current line if no
+    /// mark is set, using the comment markers defined by the syntax configuration.
+    ///
+    /// Returns `false` if the syntax configuration does not define comment markers.
+    /// The mark is preserved and all changes are recorded as a single change in the
+    /// undo stack.
+    fn toggle_comment(&mut self) -> bool;
+
+    /// Removes trailing spaces and tabs from every line if `trim` is `true`, and
+    /// appends a `\n` to a nonempty buffer that does not already end with one if
+    /// `final_newline` is `true`.
+    ///
+    /// Returns `true` if the buffer changed. The cursor remains on the same row of the
+    /// display, the mark is preserved, and all changes are recorded as a single change
+    /// in the undo stack.
+    fn tidy(&mut self, trim: bool, final_newline: bool) -> bool;
+
     /// Inserts the array of `text` at the current buffer position.
     fn insert(&mut self, text: &[char]);
 
@@ -341,31 +434,27 @@ struct EditorKernel {
     /// Buffer containing the contents of this editor.
     buffer: BufferRef,
 
-    /// A logical clock that increments with each change to the buffer.
-    clock: u64,
+    /// State shared with all editors viewing the same buffer.
+    shared: SharedRef,
 
-    /// A stack containing changes to the buffer that can be _undone_.
-    undo: Vec<Change>,
+    /// The value of [`Shared::revision`] when this editor was last synchronized.
+    revision: u64,
 
-    /// A stack containing changes to the buffer that can be _redone_.
-    redo: Vec<Change>,
+    /// The value of [`Shared::syntax_revision`] when settings of this editor were
+    /// last resolved.
+    syntax_revision: u64,
+
+    /// The value of [`Shared::clock`] when this editor was last synchronized, which
+    /// is also observed by [`Shared::sync_clocks`].
+    sync_clock: Rc<cell::Cell<u64>>,
 
     /// Tokenizes the buffer for syntax coloring.
     tokenizer: TokenizerRef,
 
-    /// The number of milliseconds spent performing the last tokenization.
-    tokenize_cost: u128,
-
-    /// The value of [`clock`](Self::clock) at the time of the last tokenization.
-    tokenize_clock: u64,
-
     /// A tokenization cursor that is always pointing to the top-left position on the
     /// display.
     syntax_cursor: Cursor,
 
-    /// An indication that unsaved changes have been made to the buffer.
-    dirty: bool,
-
     /// Buffer position corresponding to the cursor.
     cur_pos: usize,
 
@@ -399,16 +488,91 @@ struct EditorKernel {
     /// Number of columns allocated to the margin for displaying line numbers.
     margin_cols: u32,
 
-    /// Indicates whether _hard_ or _soft_ tabs are inserted.
-    tab_hard: bool,
+    /// Effective settings of this editor, including whether _hard_ or _soft_ tabs are
+    /// inserted, the width of tab stops, and whether long lines wrap onto subsequent
+    /// rows or are clipped, in which case the display scrolls horizontally.
+    settings: EditorSettings,
+
+    /// Modes toggled by the user, which are preserved when settings are resolved
+    /// again.
+    toggles: Toggles,
+
+    /// The left-most visible column when lines are clipped, which is always `0` if
+    /// lines wrap.
+    left_col: u32,
 
-    /// The width of tab stops in number of columns.
-    tab_cols: u32,
+    /// Rows of the buffer line most recently scanned for finding a row.
+    row_cache: RefCell<RowCache>,
 
     /// An optional last match from a prior search.
     last_match: Option<(usize, Box<dyn Pattern>)>,
 }
 
+/// State shared among all editors viewing the same [`Buffer`], each of which has its
+/// own cursor, mark, and position on the display.
+struct Shared {
+    /// The source of the buffer.
+    source: Source,
+
+    /// A logical clock that increments with each change to the buffer.
+    clock: u64,
+
+    /// A counter that increments with each change to shared state, which allows
+    /// editors to detect the need for synchronization.
+    revision: u64,
+
+    /// A counter that increments with each change to the syntax of the tokenizer,
+    /// which allows editors to detect the need for resolving settings again.
+    syntax_revision: u64,
+
+    /// A stack containing changes to the buffer that can be _undone_.
+    undo: Vec<Change>,
+
+    /// A stack containing changes to the buffer that can be _redone_.
+    redo: Vec<Change>,
+
+    /// The number of milliseconds spent performing the last tokenization, which
+    /// determines whether incremental tokenization is deferred.
+    tokenize_cost: u128,
+
+    /// The value of [`clock`](Self::clock) at the time of the last tokenization.
+    tokenize_clock: u64,
+
+    /// A pending background tokenization of the buffer, if any, along with the
+    /// value of [`clock`](Self::clock) at the time the job was started.
+    tokenize_job: Option<(u64, Job)>,
+
+    /// An indication that unsaved changes have been made to the buffer.
+    dirty: bool,
+
+    /// A log of recent changes to the buffer, which is only recorded when the buffer
+    /// is viewed by more than one editor or background tokenization is pending.
+    shifts: Vec<Shift>,
+
+    /// The synchronization clocks of all editors viewing the buffer, which determine
+    /// the shifts that every editor has observed and may therefore be discarded.
+    sync_clocks: Vec<Weak<cell::Cell<u64>>>,
+}
+
+type SharedRef = Rc<RefCell<Shared>>;
+
+/// A record of text inserted into or removed from the buffer, which editors use to
+/// adjust positions affected by changes made in other editors.
+#[derive(Copy, Clone)]
+struct Shift {
+    /// The value of [`Shared::clock`] following the change.
+    clock: u64,
+
+    /// Buffer position of the change.
+    pos: usize,
+
+    /// Number of characters inserted or removed.
+    len: usize,
+
+    /// Indicates whether text was inserted or removed.
+    inserted: bool,
+}
+
 /// The distinct types of changes to a buffer recorded in the _undo_ and _redo_ stacks.
 enum Change {
     /// Represents the insertion of text, where values are defined as:
@@ -439,6 +603,19 @@ enum Change {
     /// - buffer position prior to removal
     /// - text removed
     RemoveSelectionAfter(usize, Vec<char>, bool),
+
+    /// Represents a sequence of changes, in order of application, that are undone and
+    /// redone as a single change.
+    Group(Vec<Change>),
+}
+
+/// An edit to the buffer that is applied as part of a group of changes.
+enum Edit {
+    /// Inserts text at the given buffer position.
+    Insert(usize, Vec<char>),
+
+    /// Removes the given number of characters starting at the buffer position.
+    Remove(usize, usize),
 }
 
 /// Indicates how a [`Change`] should be logged.
@@ -465,6 +642,10 @@ struct Line {
     /// Length of the display line, including the `\n` if one exists.
     row_len: usize,
 
+    /// Number of columns occupied by the display line, which may be larger than
+    /// `row_len` since certain characters, such as `\t`, span multiple columns.
+    row_cols: u32,
+
     /// Buffer position corresponding to the first character of the buffer line,
     /// which is always less than or equal to `row_pos`.
     line_pos: usize,
@@ -512,10 +693,36 @@ pub struct Capture {
     pub mark: Option<Mark>,
 }
 
+/// Modes explicitly set by the user, which take precedence over settings whenever
+/// they are resolved again.
+#[derive(Clone, Default)]
+struct Toggles {
+    tab_hard: Option<bool>,
+    wrap: Option<bool>,
+    whitespace: Option<Whitespace>,
+}
+
+/// Rows of a single buffer line that have been scanned thus far, which spares
+/// rescanning a wrapped line from its start each time a row is sought.
+#[derive(Default)]
+struct RowCache {
+    /// The value of [`Shared::clock`], position of the buffer line, number of columns
+    /// at which rows wrap, and tab size at the time rows were scanned, or `None` if
+    /// invalidated.
+    key: Option<(u64, usize, u32, u32)>,
+
+    /// Starting buffer position and column relative to the start of the buffer line
+    /// of each row scanned, in order.
+    rows: Vec<(usize, u32)>,
+}
+
 /// A drawing context provided to rendering functions.
-struct Draw {
-    /// Configuration that dictates colors and behaviors.
-    config: ConfigurationRef,
+struct Draw<'a> {
+    /// Settings that dictate behaviors.
+    settings: &'a EditorSettings,
+
+    /// Theme in effect at the time of drawing.
+    theme: Theme,
 
     /// Color of margin.
     margin_color: Color,
@@ -529,6 +736,9 @@ struct Draw {
     /// Range in the buffer containing selected text, if applicable, otherwise this
     /// span is assumed to be `0`..`0`.
     select_span: Range<usize>,
+
+    /// Position of the bracket matching the one at the cursor, if applicable.
+    bracket_pos: Option<usize>,
 }
 
 /// A rendering context that captures state information for rendering functions.
@@ -538,8 +748,86 @@ struct Render {
     col: u32,
     line: u32,
     line_wrapped: bool,
+
+    /// Column relative to the start of the buffer line at which the current row
+    /// starts, which is nonzero only if the row continues a wrapped line.
+    row_col: u32,
+
     tokenizer: TokenizerRef,
     syntax_cursor: Cursor,
+
+    /// Range in the buffer containing trailing whitespace of the current line, which
+    /// is only determined if whitespace is made visible, otherwise this span is
+    /// assumed to be `0`..`0`.
+    trailing_span: Range<usize>,
+}
+
+impl Shared {
+    /// Number of shifts in the log beyond which shifts already observed by all
+    /// editors are discarded.
+    const SHIFTS_LIMIT: usize = 1024;
+
+    fn new(source: Source, sync_clock: &Rc<cell::Cell<u64>>) -> Shared {
+        Shared {
+            source,
+            clock: 0,
+            revision: 0,
+            syntax_revision: 0,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            tokenize_cost: 0,
+            tokenize_clock: 0,
+            tokenize_job: None,
+            dirty: false,
+            shifts: Vec::new(),
+            sync_clocks: vec![Rc::downgrade(sync_clock)],
+        }
+    }
+
+    fn to_ref(self) -> SharedRef {
+        Rc::new(RefCell::new(self))
+    }
+
+    /// Discards shifts from the log that have been observed by all editors viewing
+    /// the buffer as well as pending background tokenization, if any.
+    ///
+    /// Shifts not yet observed are always retained, which means the log may exceed
+    /// [`SHIFTS_LIMIT`](Self::SHIFTS_LIMIT) when a single operation makes many
+    /// changes before other editors have a chance to synchronize.
+    fn trim_shifts(&mut self) {
+        self.sync_clocks.retain(|clock| clock.strong_count() > 0);
+        let clock = self
+            .sync_clocks
+            .iter()
+            .filter_map(|clock| clock.upgrade())
+            .map(|clock| clock.get())
+            .chain(self.tokenize_job.as_ref().map(|(clock, _)| *clock))
+            .min()
+            .unwrap_or(self.clock);
+        self.shifts.retain(|shift| shift.clock > clock);
+    }
+}
+
+impl Shift {
+    fn new(pos: usize, len: usize, inserted: bool) -> Shift {
+        Shift {
+            clock: 0,
+            pos,
+            len,
+            inserted,
+        }
+    }
+
+    /// Returns the position corresponding to `p` after the change.
+    fn apply(&self, p: usize) -> usize {
+        if p <= self.pos {
+            p
+        } else if self.inserted {
+            p + self.len
+        } else {
+            p - cmp::min(p - self.pos, self.len)
+        }
+    }
 }
 
 impl Change {
@@ -591,7 +879,7 @@ impl Line {
     /// Returns `true` if the row of this line points to the bottom of the buffer,
     /// where `cols` is the width of the display.
     fn is_bottom(&self, cols: u32) -> bool {
-        self.line_bottom && self.row_len < cols as usize
+        self.line_bottom && !self.does_wrap() && self.row_cols < cols
     }
 
     /// Returns `true` if the row of this line wraps at least to the next row,
@@ -608,62 +896,6 @@ impl Line {
         self.row_pos > self.line_pos
     }
 
-    /// Returns a possibly smaller value of `col` if it extends beyond the end of
-    /// the row, where `cols` is the width of the display.
-    ///
-    /// In most cases, the right-most column aligns to the last character of the row,
-    /// which is usually `\n` but may also be any other character if the row wraps.
-    /// However, if this is the bottom-most row in the buffer, there is no terminating
-    /// `\n`, and thus the right-most column is right of the last character.
-    #[inline]
-    fn snap_col(&self, col: u32, cols: u32) -> u32 {
-        if self.row_len == 0 {
-            0
-        } else if self.is_bottom(cols) {
-            cmp::min(col, self.row_len as u32)
-        } else {
-            cmp::min(col, self.row_len as u32 - 1)
-        }
-    }
-
-    /// Returns the buffer position of `col` relative to the starting position of
-    /// the row.
-    #[inline]
-    fn pos_of(&self, col: u32) -> usize {
-        self.row_pos + col as usize
-    }
-
-    /// Returns the column number of `pos` relative to the starting position of the
-    /// row, though be advised that the resulting column may extend beyond the end
-    /// of the row.
-    #[inline]
-    fn col_of(&self, pos: usize) -> u32 {
-        (pos - self.row_pos) as u32
-    }
-
-    /// Returns the right-most column number of this row, where `cols` is the width of
-    /// the display.
-    ///
-    /// See [`snap_col`](Line::snap_col) for further details on calculating the
-    /// right-most column.
-    #[inline]
-    fn end_col(&self, cols: u32) -> u32 {
-        if self.row_len == 0 {
-            0
-        } else if self.is_bottom(cols) {
-            self.row_len as u32
-        } else {
-            (self.row_len - 1) as u32
-        }
-    }
-
-    /// Returns the column number of `col` relative to the buffer line, which may be
-    /// larger than the width of the display.
-    #[inline]
-    fn line_col(&self, col: u32) -> u32 {
-        (self.row_pos - self.line_pos) as u32 + col
-    }
-
     /// Returns the buffer position at the end of the row.
     #[inline]
     fn end_pos(&self) -> usize {
@@ -680,6 +912,7 @@ impl Default for Line {
         Line {
             row_pos: 0,
             row_len: 0,
+            row_cols: 0,
             line_pos: 0,
             line_len: 0,
             line: 0,
@@ -688,20 +921,29 @@ impl Default for Line {
     }
 }
 
-impl Draw {
+impl Draw<'_> {
     // Special character shown for \n (newline) when visible.
     const EOL_CHAR: char = '\u{21b2}';
 
     // Special character shown for \t (tab).
     const TAB_CHAR: char = '\u{2192}';
 
+    // Special character shown for spaces when whitespace is visible.
+    const SPACE_CHAR: char = '\u{00b7}';
+
+    // Special characters shown in margin when text is clipped on the left, right, or
+    // both sides of the display.
+    const CLIP_LEFT_CHAR: char = '\u{2039}';
+    const CLIP_RIGHT_CHAR: char = '\u{203a}';
+    const CLIP_BOTH_CHAR: char = '\u{2194}';
+
     // Special character shown for all other ASCII control characters.
     const CTRL_CHAR: char = '\u{00bf}';
 
-    fn new(editor: &EditorKernel) -> Draw {
-        let config = editor.config.clone();
-        let margin_color = Color::new(config.theme.margin_fg, config.theme.margin_bg);
-        let text_color = Color::new(config.theme.text_fg, config.theme.text_bg);
+    fn new(editor: &EditorKernel) -> Draw<'_> {
+        let theme = editor.config.theme();
+        let margin_color = Color::from_shades(theme.margin_fg, theme.margin_bg);
+        let text_color = Color::from_shades(theme.text_fg, theme.text_bg);
 
         let select_span = editor
             .mark
@@ -714,12 +956,28 @@ impl Draw {
             })
             .unwrap_or(0..0);
 
+        // Search for matching bracket is confined to visible region of buffer, which
+        // extends to the end of the last visible line if lines are clipped.
+        let bracket_pos = {
+            let start_pos = editor.top_line.row_pos;
+            let end_pos = if editor.settings.wrap {
+                start_pos + (editor.rows * editor.cols) as usize
+            } else {
+                editor
+                    .buffer()
+                    .find_line(editor.top_line.line + editor.rows)
+            };
+            editor.find_bracket(start_pos..end_pos)
+        };
+
         Draw {
-            config,
+            settings: &editor.settings,
+            theme,
             margin_color,
             text_color,
             cursor: editor.cursor(),
             select_span,
+            bracket_pos,
         }
     }
 
@@ -736,42 +994,115 @@ impl Draw {
     }
 
     /// Formats `c` using a color depending on the current rendering context.
+    ///
+    /// Colors and attributes from the theme are combined with the style of the syntax
+    /// token, if any, where a background color of the token is superseded by the
+    /// selection, matching bracket, and trailing whitespace, and in turn supersedes
+    /// rulers.
     fn as_text(&self, c: char, render: &Render) -> Cell {
-        let fg = if (c == '\n' && self.config.settings.eol) || c.is_ascii_control() {
-            self.config.theme.whitespace_fg
-        } else if let Some(fg) = render.syntax_cursor.color() {
-            fg
+        let ruler = self.is_ruler(render);
+        self.as_cell(c, self.convert_char(c, render), ruler, render)
+    }
+
+    /// Formats ` ` (space) as a blank cell using a color depending on the current
+    /// rendering context, which is used for filling space that does not correspond
+    /// to a character in the buffer.
+    fn as_fill(&self, render: &Render) -> Cell {
+        self.as_cell(' ', ' ', false, render)
+    }
+
+    /// Formats ` ` (space) as a blank cell in the same manner as
+    /// [`as_fill`](Self::as_fill), but which coincides with a ruler.
+    fn as_ruler(&self, render: &Render) -> Cell {
+        self.as_cell(' ', ' ', true, render)
+    }
+
+    /// Formats `c`, which is the display character of `orig_c`, using a color
+    /// depending on the current rendering context and whether the cell coincides
+    /// with a `ruler`.
+    fn as_cell(&self, orig_c: char, c: char, ruler: bool, render: &Render) -> Cell {
+        let theme = &self.theme;
+        let style = render.syntax_cursor.style().unwrap_or_default();
+
+        let fg = if (orig_c == '\n' && self.settings.eol)
+            || orig_c.is_ascii_control()
+            || (orig_c == ' ' && c == Self::SPACE_CHAR)
+        {
+            theme.whitespace_fg
+        } else if let Some(color) = style.fg {
+            Shade {
+                color,
+                attrs: theme.text_fg.attrs,
+            }
         } else {
-            self.config.theme.text_fg
+            theme.text_fg
         };
 
         let bg = if self.select_span.contains(&render.pos) {
-            self.config.theme.select_bg
-        } else if self.config.settings.spotlight && render.row == self.cursor.row {
-            self.config.theme.spotlight_bg
+            theme.select_bg
+        } else if self.bracket_pos == Some(render.pos) {
+            theme.bracket_bg
+        } else if render.trailing_span.contains(&render.pos) {
+            theme.trailing_bg
+        } else if let Some(color) = style.bg {
+            Shade::new(color)
+        } else if ruler {
+            theme.ruler_bg
+        } else if self.settings.spotlight && render.row == self.cursor.row {
+            theme.spotlight_bg
         } else {
-            self.config.theme.text_bg
+            theme.text_bg
         };
 
-        Cell::new(self.convert_char(c), Color::new(fg, bg))
+        let mut color = Color::from_shades(fg, bg);
+        color.attrs = color.attrs | style.attrs;
+        Cell::new(c, color)
     }
 
     /// Possibly converts `c` to an alternate display character.
     #[inline]
-    fn convert_char(&self, c: char) -> char {
+    fn convert_char(&self, c: char, render: &Render) -> char {
         match c {
+            ' ' if self.is_whitespace_visible(render) => Self::SPACE_CHAR,
             '\n' => {
-                if self.config.settings.eol {
+                if self.settings.eol {
                     Self::EOL_CHAR
                 } else {
                     ' '
                 }
             }
-            '\t' => Self::TAB_CHAR,
+            '\t' => {
+                if self.settings.tab_glyph || self.is_whitespace_visible(render) {
+                    Self::TAB_CHAR
+                } else {
+                    ' '
+                }
+            }
             c if c.is_ascii_control() => Self::CTRL_CHAR,
             c => c,
         }
     }
+
+    /// Returns `true` if a ruler is painted at the column of `render`, which is
+    /// relative to the start of the buffer line even if the row continues a wrapped
+    /// line.
+    #[inline]
+    fn is_ruler(&self, render: &Render) -> bool {
+        self.settings
+            .rulers
+            .contains(&(render.row_col + render.col))
+    }
+
+    /// Returns `true` if spaces and tabs at the position of `render` are shown using
+    /// special characters.
+    #[inline]
+    fn is_whitespace_visible(&self, render: &Render) -> bool {
+        match self.settings.whitespace {
+            Whitespace::None => false,
+            Whitespace::Trailing => render.trailing_span.contains(&render.pos),
+            Whitespace::All => true,
+        }
+    }
 }
 
 impl Render {
@@ -783,34 +1114,51 @@ impl Render {
             col: 0,
             line: editor.top_line.line + 1,
             line_wrapped: false,
+            row_col: editor.row_col(&editor.top_line),
             tokenizer: editor.tokenizer.clone(),
             syntax_cursor: editor.syntax_cursor,
+            trailing_span: editor.find_trailing_span(editor.top_line.row_pos),
         }
     }
 
-    /// Returns a new rendering context representing a transition to the next column.
-    fn next_col(self) -> Render {
+    /// Returns a new rendering context representing a transition to the column that
+    /// is `cols` columns to the right.
+    fn next_col(self, cols: u32) -> Render {
         Render {
             pos: self.pos + 1,
-            col: self.col + 1,
+            col: self.col + cols,
             syntax_cursor: self.syntax_forward(1),
             ..self
         }
     }
 
-    /// Returns a new rendering context representing a transition to the next row,
-    /// indicating that the current line wraps.
-    fn next_row(self) -> Render {
+    /// Returns a new rendering context representing a transition to the next row
+    /// following a character that is `cols` columns wide, indicating that the current
+    /// line wraps.
+    fn next_row(self, cols: u32) -> Render {
         Render {
             pos: self.pos + 1,
             row: self.row + 1,
             col: 0,
             line_wrapped: true,
+            row_col: self.row_col + self.col + cols,
             syntax_cursor: self.syntax_forward(1),
             ..self
         }
     }
 
+    /// Returns a new rendering context representing a transition to the next row
+    /// without advancing the buffer position, indicating that the current line wraps.
+    fn wrap_row(self) -> Render {
+        Render {
+            row: self.row + 1,
+            col: 0,
+            line_wrapped: true,
+            row_col: self.row_col + self.col,
+            ..self
+        }
+    }
+
     /// Returns a new rendering context representing a transition to the next line,
     /// which is also the next row.
     fn next_line(self) -> Render {
@@ -820,6 +1168,7 @@ impl Render {
             col: 0,
             line: self.line + 1,
             line_wrapped: false,
+            row_col: 0,
             syntax_cursor: self.syntax_forward(1),
             ..self
         }
@@ -872,6 +1221,38 @@ impl Editor {
             Some(&mut self.kernel)
         }
     }
+
+    /// Returns a new editor that views the same buffer as this editor.
+    ///
+    /// Both editors share the buffer, its syntax tokenization, the undo and redo
+    /// stacks, and the dirty state, whereas the cursor, mark, and position on the
+    /// display are independent.
+    pub fn share(&self) -> Editor {
+        Editor {
+            kernel: self.kernel.share_kernel(),
+            readonly: self.readonly,
+        }
+    }
+
+    /// Returns `true` if the buffer of this editor is viewed by at least one other
+    /// editor.
+    pub fn is_shared(&self) -> bool {
+        Rc::strong_count(&self.kernel.shared) > 1
+    }
+
+    /// Returns `true` if this editor and `other` view the same buffer.
+    pub fn is_shared_with(&self, other: &Editor) -> bool {
+        Rc::ptr_eq(&self.kernel.shared, &other.kernel.shared)
+    }
+
+    /// Synchronizes this editor with changes made to the shared buffer by other
+    /// editors, returning `true` if changes were detected.
+    ///
+    /// Positions of the cursor and mark are adjusted to account for such changes,
+    /// and if attached to a window, the editor is rendered.
+    pub fn sync(&mut self) -> bool {
+        self.kernel.sync()
+    }
 }
 
 impl ImmutableEditor for Editor {
@@ -940,6 +1321,46 @@ impl ImmutableEditor for Editor {
         self.kernel.set_tab(hard);
     }
 
+    #[inline]
+    fn get_wrap(&self) -> bool {
+        self.kernel.get_wrap()
+    }
+
+    #[inline]
+    fn set_wrap(&mut self, wrap: bool) {
+        self.kernel.set_wrap(wrap);
+    }
+
+    #[inline]
+    fn get_whitespace(&self) -> Whitespace {
+        self.kernel.get_whitespace()
+    }
+
+    #[inline]
+    fn set_whitespace(&mut self, whitespace: Whitespace) {
+        self.kernel.set_whitespace(whitespace);
+    }
+
+    #[inline]
+    fn settings(&self) -> &EditorSettings {
+        self.kernel.settings()
+    }
+
+    #[inline]
+    fn settings_for(&self, path: &str) -> EditorSettings {
+        self.kernel.settings_for(path)
+    }
+
+    #[inline]
+    fn line_cols(&self) -> u32 {
+        self.kernel.line_cols()
+    }
+
+    #[inline]
+    fn set_syntax(&mut self, syntax: Syntax) {
+        self.kernel.set_syntax(syntax);
+    }
+
     #[inline]
     fn set_focus(&mut self, cursor: Point) {
         self.kernel.set_focus(cursor);
@@ -990,6 +1411,11 @@ impl ImmutableEditor for Editor {
         self.kernel.move_forward_word();
     }
 
+    #[inline]
+    fn move_bracket(&mut self) -> bool {
+        self.kernel.move_bracket()
+    }
+
     #[inline]
     fn move_up(&mut self, try_rows: u32, pin: bool) {
         self.kernel.move_up(try_rows, pin);
@@ -1134,7 +1560,15 @@ impl ImmutableEditor for EditorKernel {
     }
 
     fn assume(&mut self, source: Source) {
+        // Settings depend on the path of the file, so they are resolved again when
+        // the path changes.
+        let moved = self.source.file_path() != source.file_path();
+        self.shared_mut().source = source.clone();
         self.source = source;
+        if moved {
+            self.resolve_settings();
+        }
+        self.touch();
     }
 
     #[inline]
@@ -1143,11 +1577,12 @@ impl ImmutableEditor for EditorKernel {
     }
 
     fn is_dirty(&self) -> bool {
-        self.dirty
+        self.shared().dirty
     }
 
     fn clear_dirty(&mut self) {
-        self.dirty = false;
+        self.shared_mut().dirty = false;
+        self.touch();
         self.show_banner();
     }
 
@@ -1158,7 +1593,8 @@ impl ImmutableEditor for EditorKernel {
 
     #[inline]
     fn location(&self) -> Point {
-        Point::new(self.cur_line.line, self.cur_line.line_col(self.cursor.col))
+        let col = self.row_col(&self.cur_line) + self.col_of(&self.cur_line, self.cur_pos);
+        Point::new(self.cur_line.line, col)
     }
 
     fn rows(&self) -> u32 {
@@ -1174,11 +1610,80 @@ impl ImmutableEditor for EditorKernel {
     }
 
     fn get_tab(&self) -> bool {
-        self.tab_hard
+        self.settings.tab_hard
     }
 
     fn set_tab(&mut self, hard: bool) {
-        self.tab_hard = hard;
+        self.toggles.tab_hard = Some(hard);
+        self.settings.tab_hard = hard;
+        self.banner.borrow_mut().set_tab(hard).draw();
+    }
+
+    fn get_wrap(&self) -> bool {
+        self.settings.wrap
+    }
+
+    fn set_wrap(&mut self, wrap: bool) {
+        self.toggles.wrap = Some(wrap);
+        self.change_wrap(wrap);
+    }
+
+    fn get_whitespace(&self) -> Whitespace {
+        self.settings.whitespace
+    }
+
+    fn set_whitespace(&mut self, whitespace: Whitespace) {
+        self.toggles.whitespace = Some(whitespace);
+        self.settings.whitespace = whitespace;
+    }
+
+    fn settings(&self) -> &EditorSettings {
+        &self.settings
+    }
+
+    fn settings_for(&self, path: &str) -> EditorSettings {
+        self.resolved_settings(Some(path))
+    }
+
+    fn line_cols(&self) -> u32 {
+        let line_pos = self.cur_line.line_pos;
+        let end_pos = self.find_end_line(line_pos);
+        let tab_size = self.settings.tab_size;
+        self.buffer()
+            .forward(line_pos)
+            .take(end_pos - line_pos)
+            .fold(0, |col, c| {
+                if c == '\t' {
+                    col + tab_size - col % tab_size
+                } else {
+                    col + width::char_width(c)
+                }
+            })
+    }
+
+    fn set_syntax(&mut self, syntax: Syntax) {
+        // Any pending background tokenization applies to the prior syntax, and the
+        // tokenizer is replaced in place since it is shared among editors viewing
+        // the same buffer.
+        self.shared_mut().tokenize_job = None;
+        self.syntax_cursor = {
+            let mut tokenizer = self.tokenizer_mut();
+            *tokenizer = Tokenizer::new(syntax);
+            let cursor = tokenizer.reset(&self.buffer());
+            cursor
+        };
+
+        // Settings are resolved again since they depend on syntax, which other editors
+        // viewing the same buffer also detect.
+        self.syntax_revision = {
+            let mut shared = self.shared_mut();
+            shared.syntax_revision += 1;
+            shared.syntax_revision
+        };
+        self.resolve_settings();
+        self.tokenize();
+        self.touch();
+        self.show_banner();
     }
 
     fn set_focus(&mut self, cursor: Point) {
@@ -1188,16 +1693,16 @@ impl ImmutableEditor for EditorKernel {
         let try_col = if cursor.col < self.margin_cols {
             0
         } else {
-            cmp::min(cursor.col - self.margin_cols, self.cols)
+            cmp::min(cursor.col - self.margin_cols, self.cols) + self.left_col
         };
 
         // Find effective cursor location and buffer position by moving down from
         // top line of display.
         self.cur_line = self.top_line.clone();
         let row = self.down_cur_line(try_row);
-        let col = self.cur_line.snap_col(try_col, self.cols);
+        let (col, pos) = self.find_col(&self.cur_line, try_col);
         self.snap_col = Some(col);
-        self.cur_pos = self.cur_line.pos_of(col);
+        self.cur_pos = pos;
         self.cursor = Point::new(row, col);
     }
 
@@ -1206,14 +1711,8 @@ impl ImmutableEditor for EditorKernel {
         self.canvas = window.borrow().canvas().clone();
         self.banner = window.borrow().banner().clone();
 
-        // Allocate leftmost columns of window to line numbers, but only if enabled and
-        // total width of window is large enough to reasonably accommodate.
         let Size { rows, cols } = self.canvas.borrow().size();
-        self.margin_cols = if self.config.settings.lines && cols >= Self::MARGIN_COLS * 2 {
-            Self::MARGIN_COLS
-        } else {
-            0
-        };
+        self.margin_cols = self.margin_cols_of(cols);
         self.rows = rows;
         self.cols = cols - self.margin_cols;
 
@@ -1242,7 +1741,7 @@ impl ImmutableEditor for EditorKernel {
         // of row to top of buffer.
         self.cur_line = self.find_line(self.cur_pos);
         let row = self.set_top_line(try_row);
-        let col = self.cur_line.col_of(self.cur_pos);
+        let col = self.col_of(&self.cur_line, self.cur_pos);
         self.snap_col = None;
         self.align_syntax();
         self.cursor = Point::new(row, col);
@@ -1255,23 +1754,25 @@ impl ImmutableEditor for EditorKernel {
     }
 
     fn show_cursor(&mut self) {
+        let col = self.cursor.col.saturating_sub(self.left_col);
+        let cursor = Point::new(self.cursor.row, cmp::min(col, self.cols.saturating_sub(1)));
         let cursor = if self.margin_cols > 0 {
-            self.cursor + Size::cols(self.margin_cols)
+            cursor + Size::cols(self.margin_cols)
         } else {
-            self.cursor
+            cursor
         };
         self.canvas.borrow_mut().set_cursor(cursor);
     }
 
     fn move_backward(&mut self, len: usize) {
-        let pos = self.cur_pos - cmp::min(len, self.cur_pos);
+        let pos = (0..len).fold(self.cur_pos, |pos, _| self.find_cluster_before(pos));
         if pos < self.cur_pos {
             self.move_to(pos, Align::Auto);
         }
     }
 
     fn move_forward(&mut self, len: usize) {
-        let pos = cmp::min(self.cur_pos + len, self.buffer().size());
+        let pos = (0..len).fold(self.cur_pos, |pos, _| self.find_cluster_after(pos));
         if pos > self.cur_pos {
             self.move_to(pos, Align::Auto);
         }
@@ -1291,6 +1792,16 @@ impl ImmutableEditor for EditorKernel {
         }
     }
 
+    fn move_bracket(&mut self) -> bool {
+        let end_pos = self.buffer().size();
+        if let Some(pos) = self.find_bracket(0..end_pos) {
+            self.move_to(pos, Align::Auto);
+            true
+        } else {
+            false
+        }
+    }
+
     fn move_up(&mut self, try_rows: u32, pin: bool) {
         let rows = self.up_cur_line(try_rows);
         if rows > 0 {
@@ -1315,8 +1826,8 @@ impl ImmutableEditor for EditorKernel {
             };
             let try_col = self.snap_col.take().unwrap_or(self.cursor.col);
             self.snap_col = Some(try_col);
-            let col = self.cur_line.snap_col(try_col, self.cols);
-            self.cur_pos = self.cur_line.pos_of(col);
+            let (col, pos) = self.find_col(&self.cur_line, try_col);
+            self.cur_pos = pos;
             self.align_syntax();
             self.cursor = Point::new(row, col);
         }
@@ -1341,8 +1852,8 @@ impl ImmutableEditor for EditorKernel {
             };
             let try_col = self.snap_col.take().unwrap_or(self.cursor.col);
             self.snap_col = Some(try_col);
-            let col = self.cur_line.snap_col(try_col, self.cols);
-            self.cur_pos = self.cur_line.pos_of(col);
+            let (col, pos) = self.find_col(&self.cur_line, try_col);
+            self.cur_pos = pos;
             self.align_syntax();
             self.cursor = Point::new(row, col);
         }
@@ -1357,9 +1868,9 @@ impl ImmutableEditor for EditorKernel {
     }
 
     fn move_end(&mut self) {
-        let end_col = self.cur_line.end_col(self.cols);
+        let (end_col, end_pos) = self.end_col(&self.cur_line);
         if self.cursor.col < end_col {
-            self.cur_pos = self.cur_line.pos_of(end_col);
+            self.cur_pos = end_pos;
             self.cursor.col = end_col;
         }
         self.snap_col = None;
@@ -1428,7 +1939,7 @@ impl ImmutableEditor for EditorKernel {
             self.set_top_line(row)
         };
         self.cur_pos = pos;
-        let col = self.cur_line.col_of(self.cur_pos);
+        let col = self.col_of(&self.cur_line, self.cur_pos);
         self.snap_col = None;
         self.align_syntax();
         self.cursor = Point::new(row, col);
@@ -1443,8 +1954,8 @@ impl ImmutableEditor for EditorKernel {
                 self.cur_line = self.top_line.clone();
                 let try_col = self.snap_col.take().unwrap_or(self.cursor.col);
                 self.snap_col = Some(try_col);
-                let col = self.cur_line.snap_col(try_col, self.cols);
-                self.cur_pos = self.cur_line.pos_of(col);
+                let (col, pos) = self.find_col(&self.cur_line, try_col);
+                self.cur_pos = pos;
                 (0, col)
             } else {
                 // Cursor still visible on display.
@@ -1468,8 +1979,8 @@ impl ImmutableEditor for EditorKernel {
                 self.up_cur_line(row - self.rows + 1);
                 let try_col = self.snap_col.take().unwrap_or(self.cursor.col);
                 self.snap_col = Some(try_col);
-                let col = self.cur_line.snap_col(try_col, self.cols);
-                self.cur_pos = self.cur_line.pos_of(col);
+                let (col, pos) = self.find_col(&self.cur_line, try_col);
+                self.cur_pos = pos;
                 (self.rows - 1 as u32, col)
             };
             self.align_syntax();
@@ -1537,9 +2048,10 @@ impl ImmutableEditor for EditorKernel {
     }
 
     fn undo(&mut self) -> bool {
-        if let Some(change) = self.undo.pop() {
+        let change = self.shared_mut().undo.pop();
+        if let Some(change) = change {
             self.undo_change(&change);
-            self.redo.push(change);
+            self.shared_mut().redo.push(change);
             true
         } else {
             false
@@ -1547,9 +2059,10 @@ impl ImmutableEditor for EditorKernel {
     }
 
     fn redo(&mut self) -> bool {
-        if let Some(change) = self.redo.pop() {
+        let change = self.shared_mut().redo.pop();
+        if let Some(change) = change {
             self.redo_change(&change);
-            self.undo.push(change);
+            self.shared_mut().undo.push(change);
             true
         } else {
             false
@@ -1575,33 +2088,51 @@ impl ImmutableEditor for EditorKernel {
     }
 
     fn tokenize(&mut self) -> bool {
-        if self.tokenize_clock < self.clock {
-            self.possibly_tokenize(true);
-            true
+        if self.shared().tokenize_job.is_some() {
+            return self.poll_tokenize();
+        }
+
+        let stale = {
+            let shared = self.shared();
+            shared.tokenize_clock < shared.clock
+        };
+        if stale || self.tokenizer().is_approximate() {
+            if self.buffer().size() < Self::TOKENIZE_SYNC_LIMIT {
+                // Cost of tokenizing the entire buffer bounds the cost of subsequent
+                // incremental tokenization, which is deferred if too costly.
+                self.syntax_cursor = {
+                    let timer = Instant::now();
+                    let cursor = self.tokenizer_mut().tokenize_all(&self.buffer());
+                    let mut shared = self.shared_mut();
+                    shared.tokenize_cost = timer.elapsed().as_millis();
+                    shared.tokenize_clock = shared.clock;
+                    cursor
+                };
+                self.touch();
+                self.align_syntax();
+                true
+            } else {
+                self.spawn_tokenize();
+                false
+            }
         } else {
             false
         }
     }
 
     fn render(&mut self) {
-        // Renders visible buffer content.
-        let draw = Draw::new(&self);
-        let render = Render::new(&self);
-        let rest = self
-            .buffer
-            .borrow()
-            .forward(render.pos)
-            .try_fold(render, |render, c| self.render_cell(&draw, render, c));
-        if let Some(render) = rest {
-            self.render_rest(&draw, render);
-        }
+        // Renders visible buffer content, though horizontal scrolling may be necessary
+        // to ensure visibility of cursor when lines are clipped.
+        self.align_left_col();
+        self.render_buffer();
         self.canvas.borrow_mut().draw();
 
         // Renders additional information.
         self.banner
             .borrow_mut()
-            .set_dirty(self.dirty)
+            .set_dirty(self.is_dirty())
             .set_location(self.location())
+            .set_position(self.cur_pos, self.buffer().size())
             .draw();
     }
 
@@ -1616,7 +2147,26 @@ impl ImmutableEditor for EditorKernel {
 
 impl MutableEditor for EditorKernel {
     fn insert_char(&mut self, c: char) {
-        self.insert_normal(&[c])
+        self.insert_normal(&[c]);
+        self.possibly_dedent();
+    }
+
+    fn insert_line(&mut self) {
+        let (before, after) = self.line_text();
+        let mut indent = Self::leading_space(&before).to_string();
+        let (indent_more, indent_less) = {
+            let tokenizer = self.tokenizer();
+            let syntax = tokenizer.syntax();
+            (syntax.is_indent(&before), syntax.is_dedent(&after))
+        };
+        if indent_more {
+            indent.push_str(&self.indent_unit());
+        }
+        if indent_less {
+            let n = self.dedent_len(&indent);
+            indent.truncate(indent.len() - n);
+        }
+        self.insert_str(&format!("\n{indent}"));
     }
 
     fn insert_str(&mut self, text: &str) {
@@ -1624,14 +2174,69 @@ impl MutableEditor for EditorKernel {
     }
 
     fn insert_tab(&mut self) {
-        if self.tab_hard {
-            self.insert_char('\t');
+        if self.settings.tab_hard {
+            self.insert_normal(&['\t']);
         } else {
-            let n = self.tab_cols - (self.location().col % self.tab_cols);
+            let n = self.settings.tab_size - (self.location().col % self.settings.tab_size);
             self.insert_str(&" ".repeat(n as usize));
         }
     }
 
+    fn indent_region(&mut self) {
+        self.shift_region(false);
+    }
+
+    fn outdent_region(&mut self) {
+        self.shift_region(true);
+    }
+
+    fn toggle_comment(&mut self) -> bool {
+        self.comment_region()
+    }
+
+    fn tidy(&mut self, trim: bool, final_newline: bool) -> bool {
+        let mut edits = Vec::new();
+        {
+            let buffer = self.buffer();
+            let size = buffer.size();
+            if final_newline && size > 0 && buffer.get_char(size - 1) != Some('\n') {
+                edits.push(Edit::Insert(size, vec!['\n']));
+            }
+            if trim {
+                // Trailing whitespace precedes the end of each line, excluding the \r of
+                // a \r\n line break.
+                let mut end_pos = size;
+                loop {
+                    let line_pos = buffer.find_start_line(end_pos);
+                    let mut pos = end_pos;
+                    if pos > line_pos && buffer.get_char(pos - 1) == Some('\r') {
+                        pos -= 1;
+                    }
+                    let n = buffer
+                        .backward(pos)
+                        .take(pos - line_pos)
+                        .take_while(|c| *c == ' ' || *c == '\t')
+                        .count();
+                    if n > 0 {
+                        edits.push(Edit::Remove(pos - n, n));
+                    }
+                    if line_pos == 0 {
+                        break;
+                    }
+                    end_pos = line_pos - 1;
+                }
+            }
+        }
+
+        let changed = !edits.is_empty();
+        if changed {
+            let row = self.cursor.row;
+            self.apply_edits(edits);
+            self.move_to(self.cur_pos, Align::Row(row));
+        }
+        changed
+    }
+
     fn insert(&mut self, text: &[char]) {
         self.insert_normal(text);
     }
@@ -1691,18 +2296,23 @@ impl EditorKernel {
     /// buffer in real-time, otherwise the operation is deferred.
     const TOKENIZE_COST_LIMIT: u128 = 50;
 
+    /// Exclusive upper bound on the number of characters in the buffer for which
+    /// tokenization of the entire buffer is performed synchronously, otherwise the
+    /// operation is performed on a background thread.
+    const TOKENIZE_SYNC_LIMIT: usize = 1_048_576;
+
     /// Creates a new editor using `source` and an optional `buffer`, which if `None`
     /// automatically creates an empty buffer.
     fn new(config: ConfigurationRef, source: Source, buffer: Option<Buffer>) -> EditorKernel {
         let buffer = buffer.unwrap_or_else(|| Buffer::new()).to_ref();
         let cur_pos = buffer.borrow().get_pos();
 
-        // Constructs syntax configuration based on type of buffer and file extension,
-        // if applicable.
+        // Constructs syntax configuration based on type of buffer and either its
+        // contents or file extension, if applicable.
         let syntax = if let Source::File(path, _) = &source {
             config
                 .registry
-                .find(path)
+                .detect(path, &buffer.borrow())
                 .map(|syntax| syntax.clone())
                 .unwrap_or_else(|| Syntax::default())
         } else if let Source::Ephemeral(_) = &source {
@@ -1715,28 +2325,39 @@ impl EditorKernel {
             Syntax::default()
         };
 
-        // Tokenize buffer.
+        // Tokenize buffer, though large buffers are tokenized in the background and
+        // remain uncolored until finished.
         let mut tokenizer = Tokenizer::new(syntax);
-        let timer = Instant::now();
-        let syntax_cursor = tokenizer.tokenize(&buffer.borrow());
-        let tokenize_cost = timer.elapsed().as_millis();
+        let (syntax_cursor, tokenize_job) = if buffer.borrow().size() < Self::TOKENIZE_SYNC_LIMIT {
+            (tokenizer.tokenize_all(&buffer.borrow()), None)
+        } else {
+            let job = tokenizer.spawn(&buffer.borrow());
+            (tokenizer.reset(&buffer.borrow()), Some((0, job)))
+        };
 
-        // Additional settings.
-        let tab_hard = config.settings.tab_hard;
-        let tab_cols = config.settings.tab_size as u32;
+        // Resolve settings based on syntax and path of file, if applicable.
+        let path = if let Source::File(path, _) = &source {
+            Some(path.as_str())
+        } else {
+            None
+        };
+        let settings = config.editor_settings(path, tokenizer.syntax());
+        let sync_clock = Rc::new(cell::Cell::new(0));
 
         EditorKernel {
             config,
-            source,
+            source: source.clone(),
             buffer,
-            clock: 0,
-            undo: Vec::new(),
-            redo: Vec::new(),
+            shared: Shared {
+                tokenize_job,
+                ..Shared::new(source, &sync_clock)
+            }
+            .to_ref(),
+            revision: 0,
+            syntax_revision: 0,
+            sync_clock,
             tokenizer: tokenizer.to_ref(),
-            tokenize_cost,
-            tokenize_clock: 0,
             syntax_cursor,
-            dirty: false,
             cur_pos,
             top_line: Line::default(),
             cur_line: Line::default(),
@@ -1748,8 +2369,10 @@ impl EditorKernel {
             rows: 0,
             cols: 0,
             margin_cols: 0,
-            tab_hard,
-            tab_cols,
+            settings,
+            toggles: Toggles::default(),
+            left_col: 0,
+            row_cache: RefCell::new(RowCache::default()),
             last_match: None,
         }
     }
@@ -1763,11 +2386,200 @@ impl EditorKernel {
         editor
     }
 
+    /// Returns a kernel that shares the buffer and associated state of this kernel,
+    /// but whose cursor and display attributes are independent.
+    fn share_kernel(&self) -> EditorKernel {
+        let sync_clock = Rc::new(cell::Cell::new(self.sync_clock.get()));
+        self.shared_mut()
+            .sync_clocks
+            .push(Rc::downgrade(&sync_clock));
+        EditorKernel {
+            config: self.config.clone(),
+            source: self.source.clone(),
+            buffer: self.buffer.clone(),
+            shared: self.shared.clone(),
+            revision: self.revision,
+            syntax_revision: self.syntax_revision,
+            sync_clock,
+            tokenizer: self.tokenizer.clone(),
+            syntax_cursor: self.syntax_cursor,
+            cur_pos: self.cur_pos,
+            top_line: Line::default(),
+            cur_line: Line::default(),
+            snap_col: None,
+            cursor: self.cursor,
+            mark: None,
+            canvas: Canvas::zero().to_ref(),
+            banner: Banner::none().to_ref(),
+            rows: 0,
+            cols: 0,
+            margin_cols: 0,
+            settings: self.settings.clone(),
+            toggles: self.toggles.clone(),
+            left_col: 0,
+            row_cache: RefCell::new(RowCache::default()),
+            last_match: None,
+        }
+    }
+
+    /// Resolves settings again based on the current syntax and source.
+    fn resolve_settings(&mut self) {
+        let settings = self.resolved_settings(self.source.file_path());
+        let wrap = settings.wrap;
+        self.settings = EditorSettings {
+            wrap: self.settings.wrap,
+            ..settings
+        };
+        self.change_wrap(wrap);
+    }
+
+    /// Returns the settings resolved from the current syntax and the optional file
+    /// `path`, though modes toggled by the user are preserved, as is the line numbers
+    /// setting since the margin is only allocated when a window is attached.
+    fn resolved_settings(&self, path: Option<&str>) -> EditorSettings {
+        let settings = self.config.editor_settings(path, self.tokenizer().syntax());
+        EditorSettings {
+            lines: self.settings.lines,
+            tab_hard: self.toggles.tab_hard.unwrap_or(settings.tab_hard),
+            wrap: self.toggles.wrap.unwrap_or(settings.wrap),
+            whitespace: self.toggles.whitespace.unwrap_or(settings.whitespace),
+            ..settings
+        }
+    }
+
+    /// Changes the wrap mode to `wrap`, realigning the cursor if the mode changes.
+    fn change_wrap(&mut self, wrap: bool) {
+        if wrap != self.settings.wrap {
+            self.settings.wrap = wrap;
+            self.left_col = 0;
+            if self.rows > 0 {
+                // Margin may need to change since clip markers are only relevant when
+                // lines do not wrap.
+                let cols = self.margin_cols + self.cols;
+                self.margin_cols = self.margin_cols_of(cols);
+                self.cols = cols - self.margin_cols;
+                self.align_cursor(Align::Auto);
+            }
+        }
+    }
+
+    /// Returns the number of leftmost columns of a window whose total width is `cols`
+    /// that are allocated to the margin.
+    ///
+    /// The margin holds line numbers if enabled and the window is large enough to
+    /// reasonably accommodate, otherwise a single column is reserved for clip markers
+    /// if lines do not wrap.
+    fn margin_cols_of(&self, cols: u32) -> u32 {
+        if self.settings.lines && cols >= Self::MARGIN_COLS * 2 {
+            Self::MARGIN_COLS
+        } else if !self.settings.wrap && cols > 1 {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Returns a mutable reference to the buffer, which invalidates rows previously
+    /// scanned since the buffer is presumably about to change.
     #[inline]
     fn buffer_mut(&self) -> RefMut<'_, Buffer> {
+        self.row_cache.borrow_mut().key = None;
         self.buffer.borrow_mut()
     }
 
+    #[inline]
+    fn shared(&self) -> Ref<'_, Shared> {
+        self.shared.borrow()
+    }
+
+    #[inline]
+    fn shared_mut(&self) -> RefMut<'_, Shared> {
+        self.shared.borrow_mut()
+    }
+
+    /// Records a change to shared state other than the buffer itself, such that
+    /// other editors are able to detect the need for synchronization.
+    fn touch(&mut self) {
+        let mut shared = self.shared.borrow_mut();
+        shared.revision += 1;
+        self.revision = shared.revision;
+    }
+
+    /// Records a change to the buffer described by `shift`, which also marks the
+    /// buffer as dirty.
+    ///
+    /// The shift is only logged if the buffer is viewed by other editors, which use
+    /// it to adjust their positions during synchronization, or if background
+    /// tokenization is pending, since its result must be rebased.
+    fn record(&mut self, shift: Shift) {
+        let mut shared = self.shared.borrow_mut();
+        let logged = Rc::strong_count(&self.shared) > 1 || shared.tokenize_job.is_some();
+        shared.clock += 1;
+        shared.revision += 1;
+        shared.dirty = true;
+        if logged {
+            let clock = shared.clock;
+            shared.shifts.push(Shift { clock, ..shift });
+        }
+        self.revision = shared.revision;
+        self.sync_clock.set(shared.clock);
+    }
+
+    /// Synchronizes with changes to shared state made by other editors, returning
+    /// `true` if changes were detected.
+    fn sync(&mut self) -> bool {
+        let resolve = {
+            let mut shared = self.shared.borrow_mut();
+            if shared.revision == self.revision {
+                return false;
+            }
+
+            // Adjust positions using changes to buffer that occurred since last
+            // synchronization, all of which remain in log until observed by every
+            // editor.
+            let sync_clock = self.sync_clock.get();
+            (self.cur_pos, self.mark) = shared
+                .shifts
+                .iter()
+                .filter(|shift| shift.clock > sync_clock)
+                .fold((self.cur_pos, self.mark), |(pos, mark), shift| {
+                    (
+                        shift.apply(pos),
+                        mark.map(|Mark(pos, soft)| Mark(shift.apply(pos), soft)),
+                    )
+                });
+            let resolve = self.source.file_path() != shared.source.file_path()
+                || self.syntax_revision != shared.syntax_revision;
+            self.source = shared.source.clone();
+            self.syntax_revision = shared.syntax_revision;
+            self.revision = shared.revision;
+            self.sync_clock.set(shared.clock);
+            if shared.shifts.len() >= Shared::SHIFTS_LIMIT {
+                shared.trim_shifts();
+            }
+            resolve
+        };
+
+        // Settings depend on the path of the file and the syntax, either of which may
+        // have been changed by another editor.
+        if resolve {
+            self.resolve_settings();
+        }
+
+        // Syntax cursor is reset since tokenizer may have changed.
+        let origin = self.tokenizer().origin();
+        self.syntax_cursor = origin;
+
+        // Keep cursor on same row of display, though only applicable if attached to
+        // window.
+        if self.rows > 0 {
+            self.align_cursor(Align::Row(self.cursor.row));
+            self.show_banner();
+            self.render();
+        }
+        true
+    }
+
     #[inline]
     fn tokenizer(&self) -> Ref<'_, Tokenizer> {
         self.tokenizer.borrow()
@@ -1790,10 +2602,12 @@ impl EditorKernel {
     fn show_banner(&mut self) {
         self.banner
             .borrow_mut()
-            .set_dirty(self.dirty)
+            .set_dirty(self.is_dirty())
             .set_source(self.source.clone())
             .set_syntax(self.tokenizer().syntax().name.clone())
             .set_location(self.location())
+            .set_position(self.cur_pos, self.buffer().size())
+            .set_tab(self.settings.tab_hard)
             .draw();
     }
 
@@ -1821,6 +2635,376 @@ impl EditorKernel {
             .unwrap_or(self.buffer().size())
     }
 
+    /// Returns the position of the bracket matching the one at the current buffer
+    /// position, or the closing bracket immediately preceding it, though the search
+    /// is confined to `range`.
+    ///
+    /// `None` is returned if neither position contains a bracket, if the bracket is
+    /// contained inside literal text, such as a string or comment, or if no matching
+    /// bracket is found.
+    fn find_bracket(&self, range: Range<usize>) -> Option<usize> {
+        const BRACKETS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+        let buffer = self.buffer();
+        let (pos, open, close, forward) = buffer
+            .get_char(self.cur_pos)
+            .and_then(|c| {
+                BRACKETS.iter().find_map(|&(open, close)| {
+                    if c == open {
+                        Some((self.cur_pos, open, close, true))
+                    } else if c == close {
+                        Some((self.cur_pos, open, close, false))
+                    } else {
+                        None
+                    }
+                })
+            })
+            .or_else(|| {
+                let pos = self.cur_pos.checked_sub(1)?;
+                let c = buffer.get_char(pos)?;
+                BRACKETS
+                    .iter()
+                    .find(|(_, close)| c == *close)
+                    .map(|&(open, close)| (pos, open, close, false))
+            })?;
+
+        let tokenizer = self.tokenizer();
+        let mut cursor = tokenizer.find(self.syntax_cursor, pos);
+        if tokenizer.is_literal(cursor) {
+            return None;
+        }
+
+        // Scan in the direction of the matching bracket, ignoring brackets inside
+        // literal text, until nesting depth returns to zero.
+        let (this, that) = if forward {
+            (open, close)
+        } else {
+            (close, open)
+        };
+        let mut depth = 0;
+        let mut match_bracket = |(p, c): (usize, char)| {
+            if c == this || c == that {
+                cursor = tokenizer.find(cursor, p);
+                if !tokenizer.is_literal(cursor) {
+                    if c == this {
+                        depth += 1;
+                    } else {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(p);
+                        }
+                    }
+                }
+            }
+            None
+        };
+        if forward {
+            buffer
+                .forward(pos)
+                .index()
+                .take_while(|(p, _)| *p < range.end)
+                .find_map(&mut match_bracket)
+        } else {
+            buffer
+                .backward(pos + 1)
+                .index()
+                .take_while(|(p, _)| *p >= range.start)
+                .find_map(&mut match_bracket)
+        }
+    }
+
+    /// Returns a tuple containing the text of the current line preceding and following
+    /// the cursor, excluding the terminating `\n`.
+    fn line_text(&self) -> (String, String) {
+        let line_pos = self.cur_line.line_pos;
+        let end_pos = line_pos + self.cur_line.line_len;
+        let buffer = self.buffer();
+        let end_pos = match buffer.get_char(end_pos.saturating_sub(1)) {
+            Some('\n') if end_pos > self.cur_pos => end_pos - 1,
+            _ => end_pos,
+        };
+        (
+            buffer.copy_as_string(line_pos, self.cur_pos),
+            buffer.copy_as_string(self.cur_pos, end_pos),
+        )
+    }
+
+    /// Returns the leading whitespace of `text`.
+    fn leading_space(text: &str) -> &str {
+        let n = text.chars().take_while(|c| *c == ' ' || *c == '\t').count();
+        &text[..n]
+    }
+
+    /// Returns the text representing a single level of indentation, which depends on
+    /// whether hard or soft tabs are in effect.
+    fn indent_unit(&self) -> String {
+        if self.settings.tab_hard {
+            "\t".to_string()
+        } else {
+            " ".repeat(self.settings.tab_size as usize)
+        }
+    }
+
+    /// Returns the number of trailing characters to remove from the whitespace
+    /// `indent` in order to dedent by a single level.
+    ///
+    /// Trailing spaces are removed up to the prior tab stop.
+    fn dedent_len(&self, indent: &str) -> usize {
+        if indent.ends_with('\t') {
+            1
+        } else {
+            let spaces = indent.chars().rev().take_while(|c| *c == ' ').count();
+            match spaces % self.settings.tab_size as usize {
+                0 => cmp::min(spaces, self.settings.tab_size as usize),
+                n => n,
+            }
+        }
+    }
+
+    /// Shifts the lines covered by the mark, or the current line if no mark is set, by
+    /// one level of indentation, either to the left if `outdent` is `true`, otherwise
+    /// to the right.
+    fn shift_region(&mut self, outdent: bool) {
+        let mut edits = Vec::new();
+        for line_pos in self.region_lines().into_iter().rev() {
+            let (indent, empty) = self.line_indent(line_pos);
+            if outdent {
+                let n = self.dedent_len(&indent);
+                if n > 0 {
+                    edits.push(Edit::Remove(line_pos + indent.len() - n, n));
+                }
+            } else if !empty {
+                edits.push(Edit::Insert(line_pos, self.indent_unit().chars().collect()));
+            }
+        }
+        self.apply_edits(edits);
+    }
+
+    /// Comments or uncomments the region of lines as described in
+    /// [`toggle_comment`](MutableEditor::toggle_comment).
+    ///
+    /// Line comments are preferred over block comments when both are defined. Lines
+    /// are uncommented only if all nonempty lines are already commented.
+    fn comment_region(&mut self) -> bool {
+        let (line_comment, block_comment) = {
+            let tokenizer = self.tokenizer();
+            let syntax = tokenizer.syntax();
+            (
+                syntax.line_comment().map(|c| c.to_string()),
+                syntax
+                    .block_comment()
+                    .map(|(open, close)| (open.to_string(), close.to_string())),
+            )
+        };
+        let line_starts = self.region_lines();
+        let edits = if let Some(comment) = line_comment {
+            // Only consider nonempty lines, each of which is paired with the position
+            // of its first non-whitespace character.
+            let lines = line_starts
+                .into_iter()
+                .filter_map(|line_pos| {
+                    let (indent, empty) = self.line_indent(line_pos);
+                    if empty {
+                        None
+                    } else {
+                        Some(line_pos + indent.len())
+                    }
+                })
+                .collect::<Vec<_>>();
+            let uncomment = !lines.is_empty()
+                && lines
+                    .iter()
+                    .all(|pos| self.comment_len(*pos, &comment).is_some());
+
+            lines
+                .into_iter()
+                .rev()
+                .map(|pos| {
+                    if uncomment {
+                        let n = self.comment_len(pos, &comment).unwrap_or(0);
+                        Edit::Remove(pos, n)
+                    } else {
+                        Edit::Insert(pos, format!("{comment} ").chars().collect())
+                    }
+                })
+                .collect()
+        } else if let Some((open, close)) = block_comment {
+            // Block comments enclose the text from the first non-whitespace character
+            // of the first line through the end of the last line.
+            let start_pos = {
+                let line_pos = line_starts[0];
+                let (indent, _) = self.line_indent(line_pos);
+                line_pos + indent.len()
+            };
+            let end_pos = {
+                let line_pos = line_starts[line_starts.len() - 1];
+                let (next_pos, bottom) = self.buffer().find_next_line(line_pos);
+                if bottom {
+                    next_pos
+                } else {
+                    next_pos - 1
+                }
+            };
+            let text = self.buffer().copy_as_string(start_pos, end_pos);
+            if text.starts_with(&open)
+                && text.ends_with(&close)
+                && text.len() >= open.len() + close.len()
+            {
+                let open_n = self.comment_len(start_pos, &open).unwrap_or(0);
+                let close_n = close.chars().count();
+                let close_n = if text[..text.len() - close.len()].ends_with(' ') {
+                    close_n + 1
+                } else {
+                    close_n
+                };
+
+                // A single space between markers, as in `/* */`, would otherwise be
+                // claimed by both, so removals are kept from overlapping.
+                let close_n = cmp::min(close_n, (end_pos - start_pos).saturating_sub(open_n));
+                vec![
+                    Edit::Remove(end_pos - close_n, close_n),
+                    Edit::Remove(start_pos, open_n),
+                ]
+            } else {
+                vec![
+                    Edit::Insert(end_pos, format!(" {close}").chars().collect()),
+                    Edit::Insert(start_pos, format!("{open} ").chars().collect()),
+                ]
+            }
+        } else {
+            return false;
+        };
+        self.apply_edits(edits);
+        true
+    }
+
+    /// Returns the number of characters occupied by the comment marker `comment` at
+    /// `pos`, including a single trailing space if present, or `None` if `comment`
+    /// does not appear at `pos`.
+    fn comment_len(&self, pos: usize, comment: &str) -> Option<usize> {
+        let buffer = self.buffer();
+        let n = comment.chars().count();
+        if buffer.forward(pos).take(n).eq(comment.chars()) {
+            if buffer.get_char(pos + n) == Some(' ') {
+                Some(n + 1)
+            } else {
+                Some(n)
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Returns the starting positions of lines covered by the mark, or the current line
+    /// if no mark is set.
+    ///
+    /// The last line covered by the mark is excluded if the mark ends at its first
+    /// character.
+    fn region_lines(&self) -> Vec<usize> {
+        let (start_pos, end_pos) = if let Some(Mark(pos, _)) = self.mark {
+            (cmp::min(pos, self.cur_pos), cmp::max(pos, self.cur_pos))
+        } else {
+            (self.cur_pos, self.cur_pos)
+        };
+
+        let buffer = self.buffer();
+        let mut line_pos = buffer.find_start_line(start_pos);
+        let mut line_starts = vec![line_pos];
+        loop {
+            match buffer.find_next_line(line_pos) {
+                (next_pos, false) if next_pos < end_pos => {
+                    line_starts.push(next_pos);
+                    line_pos = next_pos;
+                }
+                _ => break,
+            }
+        }
+        line_starts
+    }
+
+    /// Returns a tuple containing the leading whitespace of the line starting at
+    /// `line_pos` and a boolean value indicating if the line is otherwise empty.
+    fn line_indent(&self, line_pos: usize) -> (String, bool) {
+        let buffer = self.buffer();
+        let indent = buffer
+            .forward(line_pos)
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect::<String>();
+        let empty = matches!(buffer.get_char(line_pos + indent.len()), Some('\n') | None);
+        (indent, empty)
+    }
+
+    /// Applies `edits`, which must be ordered by descending buffer position, such that
+    /// all changes are recorded as a single change in the undo stack.
+    ///
+    /// The positions of the cursor and mark are adjusted to account for the edits.
+    fn apply_edits(&mut self, edits: Vec<Edit>) {
+        let mut mark = self.mark;
+        let mut cur_pos = self.cur_pos;
+        let mut changes = Vec::new();
+        for edit in edits {
+            let (pos, n, inserted) = match edit {
+                Edit::Insert(pos, text) => {
+                    let n = text.len();
+                    self.move_to(pos, Align::Auto);
+                    self.insert_internal(&text, None);
+                    changes.push(Change::Insert(pos, text));
+                    (pos, n, true)
+                }
+                Edit::Remove(pos, n) => {
+                    self.move_to(pos, Align::Auto);
+                    let text = self.remove_internal(pos + n, None);
+                    changes.push(Change::RemoveAfter(pos, text));
+                    (pos, n, false)
+                }
+            };
+            let shift = Shift::new(pos, n, inserted);
+            mark = mark.map(|Mark(p, soft)| Mark(shift.apply(p), soft));
+            cur_pos = shift.apply(cur_pos);
+        }
+
+        if !changes.is_empty() {
+            self.log(Change::Group(changes));
+            self.move_to(cur_pos, Align::Auto);
+            self.mark = mark;
+        }
+    }
+
+    /// Removes one level of indentation from the current line if the text preceding
+    /// the cursor matches the _dedent_ rule of the syntax configuration.
+    fn possibly_dedent(&mut self) {
+        // Text of line is only copied if the syntax defines a dedent rule and the line
+        // is indented, since this happens with every typed character.
+        let line_pos = self.cur_line.line_pos;
+        if !self.tokenizer().syntax().has_dedent()
+            || !matches!(self.buffer().get_char(line_pos), Some(' ' | '\t'))
+        {
+            return;
+        }
+        let before = self.buffer().copy_as_string(line_pos, self.cur_pos);
+        if self.tokenizer().syntax().is_dedent(&before) {
+            let indent = Self::leading_space(&before);
+            let n = self.dedent_len(indent);
+            if n > 0 {
+                let pos = self.cur_pos;
+                let indent_pos = line_pos + indent.len();
+                self.move_to(indent_pos, Align::Auto);
+                let text = self.remove_internal(indent_pos - n, None);
+                self.move_to(pos - n, Align::Auto);
+
+                // Removal is grouped with the insertion that triggered it so that both
+                // are reverted by a single undo.
+                let change = Change::RemoveBefore(indent_pos, text);
+                let mut shared = self.shared_mut();
+                let change = match shared.undo.pop() {
+                    Some(top) => Change::Group(vec![top, change]),
+                    None => change,
+                };
+                shared.undo.push(change);
+            }
+        }
+    }
+
     /// Inserts `text` such that the change is recorded in the undo stack.
     fn insert_normal(&mut self, text: &[char]) {
         self.insert_internal(text, Some(Log::Normal));
@@ -1834,7 +3018,8 @@ impl EditorKernel {
         if text.len() > 0 {
             // Most common use case is single-character insertions, so favor use of
             // more efficient buffer insertion in that case.
-            self.buffer_mut().set_pos(self.cur_pos);
+            let pos = self.cur_pos;
+            self.buffer_mut().set_pos(pos);
             let cur_pos = if text.len() == 1 {
                 self.buffer_mut().insert_char(text[0])
             } else {
@@ -1868,12 +3053,11 @@ impl EditorKernel {
                 self.set_top_line(self.rows - 1)
             };
             self.cur_pos = cur_pos;
-            let col = self.cur_line.col_of(self.cur_pos);
+            let col = self.col_of(&self.cur_line, self.cur_pos);
             self.snap_col = None;
             self.cursor = Point::new(row, col);
-            self.dirty = true;
-            self.clock += 1;
-            self.possibly_tokenize(false);
+            self.record(Shift::new(pos, text.len(), true));
+            self.possibly_tokenize();
         }
     }
 
@@ -1952,31 +3136,106 @@ impl EditorKernel {
             self.cur_line = self.update_line(&self.cur_line);
             self.top_line = self.update_line(&self.top_line);
             self.cur_pos = from_pos;
-            let col = self.cur_line.col_of(self.cur_pos);
+            let col = self.col_of(&self.cur_line, self.cur_pos);
             self.snap_col = None;
             self.cursor = Point::new(row, col);
-            self.dirty = true;
-            self.clock += 1;
-            self.possibly_tokenize(false);
+            self.record(Shift::new(from_pos, text.len(), false));
+            self.possibly_tokenize();
             text
         }
     }
 
-    /// Tokenizes the buffer if either the prior tokenization fell below the real-time
-    /// limit or `force` is `true`, otherwise the operation is not performed.
-    fn possibly_tokenize(&mut self, force: bool) {
-        if force || self.tokenize_cost < Self::TOKENIZE_COST_LIMIT {
+    /// Tokenizes the regions of the buffer affected by changes if the prior
+    /// tokenization fell below the real-time limit and no background tokenization is
+    /// pending, otherwise the operation is deferred.
+    fn possibly_tokenize(&mut self) {
+        let deferred = {
+            let shared = self.shared();
+            shared.tokenize_cost >= Self::TOKENIZE_COST_LIMIT || shared.tokenize_job.is_some()
+        };
+        if !deferred {
             self.syntax_cursor = {
                 let timer = Instant::now();
                 let cursor = self.tokenizer_mut().tokenize(&self.buffer());
-                self.tokenize_cost = timer.elapsed().as_millis();
+                let mut shared = self.shared_mut();
+                shared.tokenize_cost = timer.elapsed().as_millis();
+                shared.tokenize_clock = shared.clock;
                 cursor
             };
-            self.tokenize_clock = self.clock;
+            self.touch();
         }
         self.align_syntax();
     }
 
+    /// Starts tokenizing a snapshot of the buffer on a background thread.
+    fn spawn_tokenize(&mut self) {
+        let job = self.tokenizer().spawn(&self.buffer());
+        let mut shared = self.shared_mut();
+        shared.tokenize_job = Some((shared.clock, job));
+    }
+
+    /// Applies the result of background tokenization if finished, returning `true`
+    /// if applied and `false` otherwise.
+    ///
+    /// Changes to the buffer made since the job started are rebased onto the result
+    /// using the log of shifts, though if the log is incomplete, the result is
+    /// discarded and tokenization is attempted again later.
+    fn poll_tokenize(&mut self) -> bool {
+        let (clock, progress) = match &self.shared().tokenize_job {
+            Some((clock, job)) => (*clock, job.poll()),
+            None => return false,
+        };
+        match progress {
+            Progress::Pending => false,
+            Progress::Failed => {
+                // Fall back to synchronous tokenization rather than repeatedly
+                // starting jobs that fail.
+                self.shared_mut().tokenize_job = None;
+                self.syntax_cursor = {
+                    let timer = Instant::now();
+                    let cursor = self.tokenizer_mut().tokenize_all(&self.buffer());
+                    let mut shared = self.shared_mut();
+                    shared.tokenize_cost = timer.elapsed().as_millis();
+                    shared.tokenize_clock = shared.clock;
+                    cursor
+                };
+                self.touch();
+                self.align_syntax();
+                true
+            }
+            Progress::Finished(tokens) => {
+                let changes = {
+                    let mut shared = self.shared_mut();
+                    shared.tokenize_job = None;
+                    let changes = shared
+                        .shifts
+                        .iter()
+                        .filter(|shift| shift.clock > clock)
+                        .map(|shift| (shift.pos, shift.len, shift.inserted))
+                        .collect::<Vec<_>>();
+                    if changes.len() as u64 != shared.clock - clock {
+                        return false;
+                    }
+                    changes
+                };
+                self.syntax_cursor = {
+                    let timer = Instant::now();
+                    let cursor = self.tokenizer_mut().accept(tokens, &self.buffer(), changes);
+                    let mut shared = self.shared_mut();
+                    shared.tokenize_cost = timer.elapsed().as_millis();
+                    shared.tokenize_clock = shared.clock;
+                    if Rc::strong_count(&self.shared) == 1 {
+                        shared.shifts.clear();
+                    }
+                    cursor
+                };
+                self.touch();
+                self.align_syntax();
+                true
+            }
+        }
+    }
+
     fn get_mark_range(&self, mark: Mark) -> Range<usize> {
         let Mark(pos, _) = mark;
         if pos < self.cur_pos {
@@ -2024,6 +3283,11 @@ impl EditorKernel {
                 }
                 self.move_to(*pos, Align::Auto);
             }
+            Change::Group(changes) => {
+                for change in changes.iter().rev() {
+                    self.undo_change(change);
+                }
+            }
         }
     }
 
@@ -2055,6 +3319,11 @@ impl EditorKernel {
                 self.move_to(*pos, Align::Auto);
                 self.remove_internal(pos + text.len(), None);
             }
+            Change::Group(changes) => {
+                for change in changes {
+                    self.redo_change(change);
+                }
+            }
         }
     }
 
@@ -2064,23 +3333,24 @@ impl EditorKernel {
         const UNDO_SOFT_LIMIT: usize = 1024;
         const UNDO_HARD_LIMIT: usize = 1280;
 
-        if let Some(top) = self.undo.pop() {
+        let mut shared = self.shared_mut();
+        if let Some(top) = shared.undo.pop() {
             if let Some(combined) = change.possibly_combine(&top) {
-                self.undo.push(combined);
+                shared.undo.push(combined);
             } else {
-                self.undo.push(top);
-                self.undo.push(change);
+                shared.undo.push(top);
+                shared.undo.push(change);
             }
         } else {
-            self.undo.push(change);
+            shared.undo.push(change);
         }
-        self.redo.clear();
+        shared.redo.clear();
 
         // Trim undo stack to soft limit once size exceeds hard limit, as this avoids
         // repeatedly trimming with every change.
-        if self.undo.len() > UNDO_HARD_LIMIT {
-            let n = self.undo.len() - UNDO_SOFT_LIMIT;
-            self.undo.drain(0..n);
+        if shared.undo.len() > UNDO_HARD_LIMIT {
+            let n = shared.undo.len() - UNDO_SOFT_LIMIT;
+            shared.undo.drain(0..n);
         }
     }
 
@@ -2111,6 +3381,42 @@ impl EditorKernel {
         try_rows
     }
 
+    /// Returns the position of the grapheme cluster preceding `pos`, or `0` if `pos`
+    /// is at the top of the buffer.
+    fn find_cluster_before(&self, pos: usize) -> usize {
+        let buffer = self.buffer();
+        let mut chars = buffer.backward(pos).peekable();
+        let mut pos = pos;
+        while let Some(c) = chars.next() {
+            pos -= 1;
+            match chars.peek() {
+                Some(prev) if width::is_joined(*prev, c) => (),
+                _ => break,
+            }
+        }
+        pos
+    }
+
+    /// Returns the position of the grapheme cluster following `pos`, or the end of
+    /// the buffer if `pos` is already at the last cluster.
+    fn find_cluster_after(&self, pos: usize) -> usize {
+        let buffer = self.buffer();
+        let mut chars = buffer.forward(pos);
+        let mut pos = pos;
+        if let Some(mut prev) = chars.next() {
+            pos += 1;
+            for c in chars {
+                if width::is_joined(prev, c) {
+                    pos += 1;
+                    prev = c;
+                } else {
+                    break;
+                }
+            }
+        }
+        pos
+    }
+
     fn find_up_cur_line(&mut self, pos: usize) -> u32 {
         let mut rows = 0;
         while pos < self.cur_line.row_pos {
@@ -2144,7 +3450,7 @@ impl EditorKernel {
 
     fn find_down_cur_line(&mut self, pos: usize) -> u32 {
         let mut rows = 0;
-        while pos >= self.cur_line.end_pos() && !self.cur_line.is_bottom(self.cols) {
+        while pos >= self.cur_line.end_pos() && !self.cur_line.is_bottom(self.wrap_cols()) {
             self.cur_line = self.next_line_unchecked(&self.cur_line);
             rows += 1;
         }
@@ -2154,16 +3460,205 @@ impl EditorKernel {
     /// Finds and returns the display line corresponding to `pos`.
     fn find_line(&self, pos: usize) -> Line {
         let (line_pos, next_pos, line_bottom) = self.find_line_bounds(pos);
-        let line_len = next_pos - line_pos;
-        let row_pos = pos - ((pos - line_pos) % self.cols as usize);
-        let row_len = cmp::min(line_len - (row_pos - line_pos), self.cols as usize);
-        Line {
-            row_pos,
-            row_len,
+        let line = Line {
+            row_pos: line_pos,
+            row_len: 0,
+            row_cols: 0,
             line_pos,
-            line_len,
+            line_len: next_pos - line_pos,
             line: self.buffer().line_of(line_pos),
             line_bottom,
+        };
+        self.find_row(line, pos)
+    }
+
+    /// Returns the display line corresponding to `pos`, which is assumed to be on the
+    /// buffer line of `line`.
+    fn find_row(&self, line: Line, pos: usize) -> Line {
+        self.find_row_col(line, pos).0
+    }
+
+    /// Returns the column relative to the start of the buffer line at which the row
+    /// in `line` starts.
+    fn row_col(&self, line: &Line) -> u32 {
+        if line.has_wrapped() {
+            self.find_row_col(line.clone(), line.row_pos).1
+        } else {
+            0
+        }
+    }
+
+    /// Returns a tuple containing the display line corresponding to `pos`, which is
+    /// assumed to be on the buffer line of `line`, and the column relative to the
+    /// start of the buffer line at which its row starts.
+    ///
+    /// Since the width of rows may vary, rows can only be found by scanning forward
+    /// from the start of the buffer line. Rows scanned are retained such that
+    /// subsequent calls for the same buffer line only scan rows not already seen,
+    /// and scanning stops at the row containing `pos`.
+    fn find_row_col(&self, line: Line, pos: usize) -> (Line, u32) {
+        let key = (
+            self.shared().clock,
+            line.line_pos,
+            self.wrap_cols(),
+            self.settings.tab_size,
+        );
+        let mut cache = self.row_cache.borrow_mut();
+        if cache.key != Some(key) {
+            cache.key = Some(key);
+            cache.rows.clear();
+            cache.rows.push((line.line_pos, 0));
+        }
+
+        // Start from the last row already scanned that precedes `pos`, and only scan
+        // forward if that row is also the last row scanned.
+        let n = cache.rows.partition_point(|&(row_pos, _)| row_pos <= pos);
+        let (row_pos, mut col) = cache.rows[n.saturating_sub(1)];
+        let mut line = self.scan_line(Line { row_pos, ..line });
+        if n >= cache.rows.len() {
+            let next_pos = line.line_pos + line.line_len;
+            while pos >= line.end_pos()
+                && line.row_len > 0
+                && (line.end_pos() < next_pos
+                    || (line.line_bottom && line.row_cols >= self.wrap_cols()))
+            {
+                col += line.row_cols;
+                line = self.scan_line(Line {
+                    row_pos: line.end_pos(),
+                    ..line
+                });
+                cache.rows.push((line.row_pos, col));
+            }
+        }
+        (line, col)
+    }
+
+    /// Returns `line` with the length and number of columns of its row calculated
+    /// from the starting position of that row.
+    fn scan_line(&self, line: Line) -> Line {
+        let (row_len, row_cols) = self.scan_row(line.row_pos);
+        Line {
+            row_len,
+            row_cols,
+            ..line
+        }
+    }
+
+    /// Returns a tuple containing the number of characters and the number of columns
+    /// of the row starting at `row_pos`.
+    ///
+    /// A row ends after `\n`, at the end of the buffer, when the width of the display
+    /// has been reached, or when the next character would not fit on the remainder of
+    /// the row.
+    fn scan_row(&self, row_pos: usize) -> (usize, u32) {
+        let mut row_len = 0;
+        let mut row_cols = 0;
+        for c in self.buffer().forward(row_pos) {
+            let cols = self.char_cols(c, row_cols);
+            if row_cols >= self.wrap_cols() || (row_len > 0 && row_cols + cols > self.wrap_cols()) {
+                break;
+            }
+            row_len += 1;
+            row_cols += cols;
+            if c == '\n' {
+                break;
+            }
+        }
+        (row_len, row_cols)
+    }
+
+    /// Possibly scrolls the display horizontally to ensure that the cursor is visible
+    /// when lines are clipped.
+    fn align_left_col(&mut self) {
+        if self.settings.wrap {
+            self.left_col = 0;
+        } else {
+            // Wide character under cursor must also be entirely visible.
+            let cols = match self.buffer().get_char(self.cur_pos) {
+                Some(c) if c != '\t' => cmp::max(width::char_width(c), 1),
+                _ => 1,
+            };
+            if self.cursor.col < self.left_col {
+                self.left_col = self.cursor.col;
+            } else if self.cursor.col + cols > self.left_col + self.cols {
+                self.left_col = (self.cursor.col + cols).saturating_sub(self.cols);
+            }
+        }
+    }
+
+    /// Returns the number of columns at which rows wrap, which is effectively unbounded
+    /// if wrapping is disabled.
+    #[inline]
+    fn wrap_cols(&self) -> u32 {
+        if self.settings.wrap {
+            self.cols
+        } else {
+            u32::MAX
+        }
+    }
+
+    /// Returns the number of columns occupied by `c` when placed at `col`.
+    ///
+    /// A `\t` extends to the next tab stop, though never beyond the width of the
+    /// display. Zero-width characters occupy no columns unless placed at the start
+    /// of a row, in which case there is no preceding character to which they might
+    /// attach.
+    #[inline]
+    fn char_cols(&self, c: char, col: u32) -> u32 {
+        if c == '\t' {
+            let cols = cmp::min(
+                self.settings.tab_size - col % self.settings.tab_size,
+                self.wrap_cols().saturating_sub(col),
+            );
+            cmp::max(cols, 1)
+        } else if col == 0 {
+            cmp::max(width::char_width(c), 1)
+        } else {
+            width::char_width(c)
+        }
+    }
+
+    /// Returns the column number of `pos` relative to the starting position of the
+    /// row in `line`.
+    fn col_of(&self, line: &Line, pos: usize) -> u32 {
+        self.buffer()
+            .forward(line.row_pos)
+            .take(pos - line.row_pos)
+            .fold(0, |col, c| col + self.char_cols(c, col))
+    }
+
+    /// Returns a tuple containing the starting column and buffer position of the
+    /// character occupying `col` on the row in `line`, or the right-most column of the
+    /// row if `col` extends beyond its end.
+    fn find_col(&self, line: &Line, col: u32) -> (u32, usize) {
+        let mut c_col = 0;
+        let buffer = self.buffer();
+        let chars = buffer.forward(line.row_pos).take(line.row_len);
+        for (pos, c) in (line.row_pos..).zip(chars) {
+            let n = self.char_cols(c, c_col);
+            if col < c_col + n {
+                return (c_col, pos);
+            }
+            c_col += n;
+        }
+        self.end_col(line)
+    }
+
+    /// Returns a tuple containing the right-most column and corresponding buffer
+    /// position of the row in `line`.
+    ///
+    /// In most cases, the right-most column aligns to the last character of the row,
+    /// which is usually `\n` but may also be any other character if the row wraps.
+    /// However, if this is the bottom-most row in the buffer, there is no terminating
+    /// `\n`, and thus the right-most column is right of the last character.
+    fn end_col(&self, line: &Line) -> (u32, usize) {
+        if line.row_len == 0 {
+            (0, line.row_pos)
+        } else if line.is_bottom(self.wrap_cols()) {
+            (line.row_cols, line.end_pos())
+        } else {
+            let pos = line.end_pos() - 1;
+            (self.col_of(line, pos), pos)
         }
     }
 
@@ -2178,17 +3673,11 @@ impl EditorKernel {
     /// alter the values noted above.
     fn update_line(&self, line: &Line) -> Line {
         let (next_pos, line_bottom) = self.buffer().find_next_line(line.line_pos);
-        let line_len = next_pos - line.line_pos;
-        let row_len = cmp::min(
-            line_len - (line.row_pos - line.line_pos),
-            self.cols as usize,
-        );
-        Line {
-            row_len,
-            line_len,
+        self.scan_line(Line {
+            line_len: next_pos - line.line_pos,
             line_bottom,
             ..*line
-        }
+        })
     }
 
     /// Returns the line preceding `line`, or `None` if `line` is already at the
@@ -2198,26 +3687,23 @@ impl EditorKernel {
             None
         } else if line.has_wrapped() {
             let l = Line {
-                row_pos: line.row_pos - self.cols as usize,
-                row_len: self.cols as usize,
+                row_pos: line.line_pos,
                 ..*line
             };
-            Some(l)
+            Some(self.find_row(l, line.row_pos - 1))
         } else {
             let pos = line.line_pos - 1;
             let (line_pos, next_pos, line_bottom) = self.find_line_bounds(pos);
-            let line_len = next_pos - line_pos;
-            let row_pos = pos - ((pos - line_pos) % self.cols as usize);
-            let row_len = cmp::min(line_len - (row_pos - line_pos), self.cols as usize);
             let l = Line {
-                row_pos,
-                row_len,
+                row_pos: line_pos,
+                row_len: 0,
+                row_cols: 0,
                 line_pos,
-                line_len,
+                line_len: next_pos - line_pos,
                 line: line.line - 1,
                 line_bottom,
             };
-            Some(l)
+            Some(self.find_row(l, pos))
         }
     }
 
@@ -2231,34 +3717,29 @@ impl EditorKernel {
     /// Returns the line following `line`, or `None` if `line` is already at the
     /// bottom of the buffer.
     fn next_line(&self, line: &Line) -> Option<Line> {
-        if line.is_bottom(self.cols) {
+        if line.is_bottom(self.wrap_cols()) {
             None
-        } else if line.does_wrap() {
-            let row_pos = line.row_pos + line.row_len;
-            let row_len = cmp::min(
-                line.line_len - (row_pos - line.line_pos),
-                self.cols as usize,
-            );
+        } else if line.does_wrap() || line.line_bottom {
+            // Bottom-most row in buffer that occupies the entire width of the display
+            // continues to an empty row.
             let l = Line {
-                row_pos,
-                row_len,
+                row_pos: line.end_pos(),
                 ..*line
             };
-            Some(l)
+            Some(self.scan_line(l))
         } else {
             let line_pos = line.line_pos + line.line_len;
             let (next_pos, line_bottom) = self.buffer().find_next_line(line_pos);
-            let line_len = next_pos - line_pos;
-            let row_len = cmp::min(line_len, self.cols as usize);
             let l = Line {
                 row_pos: line_pos,
-                row_len,
+                row_len: 0,
+                row_cols: 0,
                 line_pos,
-                line_len,
+                line_len: next_pos - line_pos,
                 line: line.line + 1,
                 line_bottom,
             };
-            Some(l)
+            Some(self.scan_line(l))
         }
     }
 
@@ -2280,22 +3761,109 @@ impl EditorKernel {
         (line_pos, next_pos, line_bottom)
     }
 
+    /// Returns the position of the line terminator, either `\n` or `\r\n`, of the
+    /// line corresponding to `pos`, or the end of buffer if the line has no
+    /// terminator.
+    fn find_end_line(&self, pos: usize) -> usize {
+        let buffer = self.buffer.borrow();
+        let (mut end_pos, _) = buffer.find_next_line(pos);
+        if end_pos > 0 && buffer.get_char(end_pos - 1) == Some('\n') {
+            end_pos -= 1;
+            if end_pos > 0 && buffer.get_char(end_pos - 1) == Some('\r') {
+                end_pos -= 1;
+            }
+        }
+        end_pos
+    }
+
+    /// Returns the range in the buffer containing trailing spaces and tabs of the line
+    /// corresponding to `pos`, excluding the line terminator, or `0`..`0` if whitespace
+    /// is not made visible.
+    fn find_trailing_span(&self, pos: usize) -> Range<usize> {
+        if self.settings.whitespace == Whitespace::None {
+            return 0..0;
+        }
+        let end_pos = self.find_end_line(pos);
+        let buffer = self.buffer.borrow();
+        let start_pos = end_pos
+            - buffer
+                .backward(end_pos)
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .count();
+        start_pos..end_pos
+    }
+
+    /// Renders visible buffer content onto the canvas without drawing it.
+    fn render_buffer(&self) {
+        let draw = Draw::new(self);
+        let render = Render::new(self);
+        let rest = self
+            .buffer
+            .borrow()
+            .forward(render.pos)
+            .try_fold(render, |render, c| self.render_cell(&draw, render, c));
+        if let Some(render) = rest {
+            self.render_rest(&draw, render);
+        }
+    }
+
     /// Renders an individual cell for the character `c`, returning the next rendering
     /// context or `None` if rendering has finished.
     fn render_cell(&self, draw: &Draw, render: Render, c: char) -> Option<Render> {
+        // Wide characters that do not fit on the remainder of the row wrap to the next
+        // row, which is consistent with the calculation of rows.
+        let cols = self.char_cols(c, render.col);
+        let render = if render.col > 0 && render.col + cols > self.wrap_cols() {
+            self.render_wrap(draw, render)?
+        } else {
+            render
+        };
+
         self.render_margin(draw, &render);
         let mut canvas = self.canvas.borrow_mut();
-        let (row, col) = (render.row, render.col + self.margin_cols);
+        let (row, col) = (render.row, self.screen_col(render.col));
         let render = if c == '\n' {
-            canvas.set_cell(row, col, draw.as_text(c, &render));
-            canvas.fill_cell_from(row, col + 1, draw.as_text(' ', &render));
-            render.next_line()
+            if self.is_visible(render.col) {
+                canvas.set_cell(row, col, draw.as_text(c, &render));
+                self.fill_text_from(&mut canvas, draw, &render, col + 1);
+            } else {
+                self.fill_text_from(&mut canvas, draw, &render, col);
+            }
+            self.render_clip(&mut canvas, draw, &render);
+            let render = render.next_line();
+            Render {
+                trailing_span: self.find_trailing_span(render.pos),
+                ..render
+            }
+        } else if cols == 0 {
+            // Zero-width characters attach to the preceding cell.
+            if render.col > self.left_col && self.is_visible(render.col - 1) {
+                canvas.add_mark(row, col - 1, c);
+            }
+            render.next_col(0)
         } else {
-            canvas.set_cell(row, col, draw.as_text(c, &render));
-            if render.col + 1 < self.cols {
-                render.next_col()
+            let end_col = self.screen_col(render.col + cols);
+            if self.is_visible(render.col) && self.is_visible(render.col + cols - 1) {
+                if width::is_zero_width(c) {
+                    // Zero-width character at start of row has nothing to which it can
+                    // attach, so blank cell is used instead.
+                    self.fill_text(&mut canvas, draw, &render, col..(col + 1));
+                    canvas.add_mark(row, col, c);
+                } else {
+                    canvas.set_cell(row, col, draw.as_text(c, &render));
+                }
+                if c == '\t' && cols > 1 {
+                    // Tabs are padded with blank cells up to next tab stop.
+                    self.fill_text(&mut canvas, draw, &render, (col + 1)..end_col);
+                }
+            } else {
+                // Characters clipped on either side are shown as blank cells.
+                self.fill_text(&mut canvas, draw, &render, col..end_col);
+            }
+            if render.col + cols < self.wrap_cols() {
+                render.next_col(cols)
             } else {
-                render.next_row()
+                render.next_row(cols)
             }
         };
         if render.row < self.rows {
@@ -2305,6 +3873,20 @@ impl EditorKernel {
         }
     }
 
+    /// Renders the remainder of the current row as empty space and returns the next
+    /// rendering context on the following row without advancing the buffer position,
+    /// or `None` if rendering has finished.
+    fn render_wrap(&self, draw: &Draw, render: Render) -> Option<Render> {
+        let col = render.col + self.margin_cols;
+        self.fill_text_from(&mut self.canvas.borrow_mut(), draw, &render, col);
+        let render = render.wrap_row();
+        if render.row < self.rows {
+            Some(render)
+        } else {
+            None
+        }
+    }
+
     /// Renders the remainder of the displayable area which is considered empty space.
     ///
     /// This function gets invoked when the end of buffer is reached before the entire
@@ -2314,8 +3896,9 @@ impl EditorKernel {
         let mut canvas = self.canvas.borrow_mut();
 
         // Blank out rest of existing row.
-        let (row, col) = (render.row, render.col + self.margin_cols);
-        canvas.fill_cell_from(row, col, draw.as_text(' ', &render));
+        let col = self.screen_col(render.col);
+        self.fill_text_from(&mut canvas, draw, &render, col);
+        self.render_clip(&mut canvas, draw, &render);
 
         // Blank out remaining rows.
         for row in (render.row + 1)..self.rows {
@@ -2326,12 +3909,66 @@ impl EditorKernel {
         }
     }
 
-    /// Renders the margin if line numbering is enabled and the rendering context is
-    /// on the first column of any row.
+    /// Renders a marker in the right-most column of the margin, which is allocated
+    /// whenever lines are clipped, if the row of the rendering context, whose width is
+    /// presumed to be the current column, extends beyond either side of the display.
+    fn render_clip(&self, canvas: &mut Canvas, draw: &Draw, render: &Render) {
+        if !self.settings.wrap && self.margin_cols > 0 {
+            let left = self.left_col > 0 && render.col > 0;
+            let right = render.col > self.left_col + self.cols;
+            let marker = match (left, right) {
+                (true, true) => Some(Draw::CLIP_BOTH_CHAR),
+                (true, false) => Some(Draw::CLIP_LEFT_CHAR),
+                (false, true) => Some(Draw::CLIP_RIGHT_CHAR),
+                (false, false) => None,
+            };
+            if let Some(c) = marker {
+                canvas.set_cell(render.row, self.margin_cols - 1, draw.as_margin(c));
+            }
+        }
+    }
+
+    /// Fills cells on the row of the rendering context for the column range `cols` of
+    /// the canvas with blank space, painting those columns that coincide with rulers.
+    fn fill_text(&self, canvas: &mut Canvas, draw: &Draw, render: &Render, cols: Range<u32>) {
+        canvas.fill_cell(render.row, cols.clone(), draw.as_fill(render));
+        for &ruler in &draw.settings.rulers {
+            if let Some(col) = ruler.checked_sub(render.row_col) {
+                if self.is_visible(col) && cols.contains(&self.screen_col(col)) {
+                    canvas.set_cell(render.row, self.screen_col(col), draw.as_ruler(render));
+                }
+            }
+        }
+    }
+
+    /// Fills cells on the row of the rendering context starting at column `start_col`
+    /// of the canvas in the same manner as [`fill_text`](Self::fill_text).
+    fn fill_text_from(&self, canvas: &mut Canvas, draw: &Draw, render: &Render, start_col: u32) {
+        let end_col = canvas.size().cols;
+        self.fill_text(canvas, draw, render, start_col..end_col);
+    }
+
+    /// Returns `true` if `col`, which is relative to the start of the row, is visible
+    /// on the display.
+    #[inline]
+    fn is_visible(&self, col: u32) -> bool {
+        col >= self.left_col && col < self.left_col + self.cols
+    }
+
+    /// Returns the column on the canvas corresponding to `col`, which is relative to
+    /// the start of the row, though clipped to either side of the display.
+    #[inline]
+    fn screen_col(&self, col: u32) -> u32 {
+        cmp::min(col.saturating_sub(self.left_col), self.cols) + self.margin_cols
+    }
+
+    /// Renders the margin if allocated and the rendering context is on the first
+    /// column of any row, where line numbers are shown only if the margin is wide
+    /// enough.
     fn render_margin(&self, draw: &Draw, render: &Render) {
         if render.col == 0 && self.margin_cols > 0 {
             let mut canvas = self.canvas.borrow_mut();
-            if render.line_wrapped {
+            if render.line_wrapped || self.margin_cols < Self::MARGIN_COLS {
                 canvas.fill_cell(render.row, 0..self.margin_cols, draw.as_margin(' '));
             } else if render.line < Self::LINE_LIMIT {
                 let s = format!(
@@ -2344,8 +3981,859 @@ impl EditorKernel {
                 }
             } else {
                 canvas.fill_cell(render.row, 0..self.margin_cols - 1, draw.as_margin('-'));
-                canvas.set_cell(render.row, self.margin_cols, draw.as_margin(' '));
+                canvas.set_cell(render.row, self.margin_cols - 1, draw.as_margin(' '));
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::build_config;
+    use crate::config::Configuration;
+    use crate::syntax::tests::{
+        build_block_comment_syntax, build_empty_syntax, build_line_comment_syntax,
+        build_literal_syntax, build_region_syntax, build_settings_syntax,
+    };
+    use crate::syntax::Registry;
+
+    /// Returns an editor containing `text` with the cursor at the top of the buffer,
+    /// whose canvas has `rows` and `cols`, though the canvas is never drawn.
+    fn build_editor(text: &str, rows: u32, cols: u32) -> EditorKernel {
+        let mut buffer = Buffer::new();
+        buffer.insert_str(text);
+        buffer.set_pos(0);
+        let config = Configuration::default().to_ref();
+        let mut editor = EditorKernel::new(config, Source::Null, Some(buffer));
+        editor.canvas = Canvas::new(Point::ORIGIN, Size::new(rows, cols)).to_ref();
+        editor.rows = rows;
+        editor.cols = cols;
+        editor.align_cursor(Align::Top);
+        editor
+    }
+
+    fn buffer_text(editor: &EditorKernel) -> String {
+        editor.buffer().iter().collect()
+    }
+
+    /// Returns an editor sharing the buffer of `editor`, whose canvas has the same
+    /// size, though the canvas is never drawn.
+    fn share_editor(editor: &EditorKernel) -> EditorKernel {
+        let mut shared = editor.share_kernel();
+        shared.canvas = Canvas::new(Point::ORIGIN, Size::new(editor.rows, editor.cols)).to_ref();
+        shared.rows = editor.rows;
+        shared.cols = editor.cols;
+        shared.align_cursor(Align::Top);
+        shared
+    }
+
+    fn mark_pos(editor: &EditorKernel) -> Option<usize> {
+        editor.mark.map(|Mark(pos, _)| pos)
+    }
+
+    #[test]
+    fn dedent_undo() {
+        let text = "fn f() {\n    x\n    ";
+        let mut editor = build_editor(text, 10, 40);
+        let registry = Registry::builtin(&editor.config.colors).unwrap();
+        editor.set_syntax(registry.get("rust").unwrap().clone());
+        editor.move_to(text.chars().count(), Align::Auto);
+        editor.insert_char('}');
+        assert_eq!(buffer_text(&editor), "fn f() {\n    x\n}");
+        assert!(editor.undo());
+        assert_eq!(buffer_text(&editor), text);
+        assert!(editor.redo());
+        assert_eq!(buffer_text(&editor), "fn f() {\n    x\n}");
+    }
+
+    #[test]
+    fn syntax_settings() {
+        let mut editor = build_editor("a: b\n", 10, 40);
+        assert_eq!(editor.settings.tab_size, 4);
+        editor.set_tab(true);
+        editor.set_whitespace(Whitespace::All);
+
+        // Settings of syntax apply, though modes toggled by user are preserved.
+        let registry = Registry::builtin(&editor.config.colors).unwrap();
+        editor.set_syntax(registry.get("yaml").unwrap().clone());
+        assert_eq!(editor.settings.tab_size, 2);
+        assert!(editor.settings.tab_hard);
+        assert!(editor.settings.whitespace == Whitespace::All);
+
+        // Modes not toggled by user follow settings of syntax.
+        let mut editor = build_editor("a\tb\n", 10, 40);
+        editor.set_whitespace(Whitespace::Trailing);
+        let syntax = build_settings_syntax("tab-hard = true\nwrap = false\nwhitespace = \"all\"");
+        editor.set_syntax(syntax);
+        assert!(editor.settings.tab_hard);
+        assert!(!editor.settings.wrap);
+        assert!(editor.settings.whitespace == Whitespace::Trailing);
+
+        // Toggled modes persist across further changes of syntax, while other modes
+        // revert to settings of the new syntax.
+        editor.set_tab(false);
+        editor.set_tab(true);
+        editor.set_syntax(build_empty_syntax());
+        assert!(editor.settings.tab_hard);
+        assert!(editor.settings.wrap);
+
+        // Shared editors follow the change of syntax, though modes toggled by user
+        // are preserved.
+        let mut other = share_editor(&editor);
+        other.set_whitespace(Whitespace::All);
+        editor.set_syntax(registry.get("yaml").unwrap().clone());
+        assert_eq!(other.settings.tab_size, 4);
+        assert!(other.sync());
+        assert_eq!(other.settings.tab_size, 2);
+        assert!(other.settings.whitespace == Whitespace::All);
+        assert!(!other.sync());
+        assert_eq!(other.settings.tab_size, 2);
+    }
+
+    #[test]
+    fn find_bracket_literal_tokens() {
+        let text = "#[x] f(\")\", g(\"(\"))";
+        let mut editor = build_editor(text, 10, 40);
+        editor.set_syntax(build_literal_syntax());
+        let mut find = |pos| {
+            editor.move_to(pos, Align::Auto);
+            editor.find_bracket(0..text.len())
+        };
+
+        // Brackets inside tokens that are not literal are matched.
+        assert_eq!(find(1), Some(3));
+
+        // Brackets inside literal tokens are ignored.
+        assert_eq!(find(6), Some(18));
+        assert_eq!(find(13), Some(17));
+        assert_eq!(find(8), None);
+        assert_eq!(find(15), None);
+    }
+
+    #[test]
+    fn find_bracket_literal_regions() {
+        let text = "f(\"(\", \"{g(1)}\")";
+        let mut editor = build_editor(text, 10, 40);
+        editor.set_syntax(build_region_syntax());
+        let mut find = |pos| {
+            editor.move_to(pos, Align::Auto);
+            editor.find_bracket(0..text.len())
+        };
+
+        // Brackets inside literal regions are ignored, though not inside regions
+        // nested within that are not literal, such as interpolated expressions.
+        assert_eq!(find(1), Some(15));
+        assert_eq!(find(3), None);
+        assert_eq!(find(8), Some(13));
+        assert_eq!(find(10), Some(12));
+    }
+
+    #[test]
+    fn find_bracket_literal() {
+        let text = "#[derive(Debug)]\nfn f(c: char) { g(')', \"(\") } // )\n";
+        let mut editor = build_editor(text, 10, 40);
+        let registry = Registry::builtin(&editor.config.colors).unwrap();
+        editor.set_syntax(registry.get("rust").unwrap().clone());
+        let mut find = |pos| {
+            editor.move_to(pos, Align::Auto);
+            editor.find_bracket(0..text.len())
+        };
+
+        // Brackets inside styled tokens that are not literal are matched.
+        assert_eq!(find(1), Some(15));
+        assert_eq!(find(8), Some(14));
+
+        // Brackets inside strings, characters, and comments are ignored.
+        assert_eq!(find(34), Some(43));
+        assert_eq!(find(31), Some(45));
+        assert_eq!(find(36), None);
+        assert_eq!(find(41), None);
+    }
+
+    #[test]
+    fn render_rulers() {
+        // Canvas includes margin, and line is long enough to wrap once.
+        let mut editor = build_editor("abcdefghijklmn\nab\n", 4, 16);
+        editor.margin_cols = 6;
+        editor.cols = 10;
+        editor.settings.rulers = vec![4];
+        let ruler_bg = editor.config.theme().ruler_bg;
+        let is_ruler = |editor: &EditorKernel, row, col| {
+            editor.canvas.borrow().get_cell(row, col).color.bg == ruler_bg.color
+        };
+
+        // Rulers are painted on text and blank space of each line, though not on
+        // rows that continue a wrapped line beyond the ruler or follow the end of the
+        // buffer.
+        editor.set_wrap(true);
+        editor.render_buffer();
+        assert!(is_ruler(&editor, 0, 10));
+        assert!(!is_ruler(&editor, 1, 10));
+        assert!(is_ruler(&editor, 2, 10));
+        assert!(is_ruler(&editor, 3, 10));
+        assert!(!is_ruler(&editor, 0, 9));
+
+        editor.set_wrap(false);
+        editor.render_buffer();
+        for row in 0..3 {
+            assert!(is_ruler(&editor, row, 10));
+            assert!(!is_ruler(&editor, row, 11));
+        }
+        assert!(!is_ruler(&editor, 3, 10));
+    }
+
+    #[test]
+    fn render_wrapped_rulers() {
+        // Second line wraps early since wide character does not fit on first row.
+        let mut editor = build_editor("abcdefghijklmn\nabcdefghi\u{65e5}x\n", 5, 16);
+        editor.margin_cols = 6;
+        editor.cols = 10;
+        editor.settings.rulers = vec![13, 16];
+        editor.set_wrap(true);
+        let ruler_bg = editor.config.theme().ruler_bg;
+        let rulers = |editor: &EditorKernel, row| {
+            (0..16)
+                .filter(|&col| editor.canvas.borrow().get_cell(row, col).color.bg == ruler_bg.color)
+                .collect::<Vec<_>>()
+        };
+
+        // Rulers beyond width of display are painted on rows of wrapped lines that
+        // cross them, whether on text or blank space.
+        editor.render_buffer();
+        assert_eq!(rulers(&editor, 0), vec![]);
+        assert_eq!(rulers(&editor, 1), vec![9, 12]);
+        assert_eq!(rulers(&editor, 2), vec![]);
+        assert_eq!(rulers(&editor, 3), vec![10, 13]);
+        assert_eq!(rulers(&editor, 4), vec![]);
+
+        // Rendering from a row that continues a wrapped line starts at the column of
+        // that row.
+        editor.top_line = editor.find_line(10);
+        editor.align_syntax();
+        editor.render_buffer();
+        assert_eq!(rulers(&editor, 0), vec![9, 12]);
+    }
+
+    #[test]
+    fn find_trailing_spans() {
+        let mut editor = build_editor("ab  \t\ncd\n  \nx ", 4, 16);
+        assert_eq!(editor.find_trailing_span(0), 0..0);
+
+        editor.set_whitespace(Whitespace::Trailing);
+        assert_eq!(editor.find_trailing_span(0), 2..5);
+        assert_eq!(editor.find_trailing_span(4), 2..5);
+        assert_eq!(editor.find_trailing_span(6), 8..8);
+        assert_eq!(editor.find_trailing_span(9), 9..11);
+        assert_eq!(editor.find_trailing_span(12), 13..14);
+    }
+
+    #[test]
+    fn render_whitespace() {
+        let mut editor = build_editor("a b \t\nl\u{b7}l\n", 4, 16);
+        editor.settings.rulers = vec![3];
+        let theme = editor.config.theme();
+        let cell = |editor: &EditorKernel, row, col| {
+            let cell = editor.canvas.borrow().get_cell(row, col);
+            (
+                cell.value,
+                cell.color.fg == theme.whitespace_fg.color,
+                cell.color.bg == theme.trailing_bg.color,
+            )
+        };
+
+        // Whitespace is shown normally, though tabs are always colored.
+        editor.settings.tab_glyph = false;
+        editor.set_whitespace(Whitespace::None);
+        editor.render_buffer();
+        assert_eq!(cell(&editor, 0, 1), (' ', false, false));
+        assert_eq!(cell(&editor, 0, 3), (' ', false, false));
+        assert_eq!(cell(&editor, 0, 4), (' ', true, false));
+
+        // Only trailing whitespace is converted and highlighted, which supersedes
+        // rulers.
+        editor.set_whitespace(Whitespace::Trailing);
+        editor.render_buffer();
+        assert_eq!(cell(&editor, 0, 1), (' ', false, false));
+        assert_eq!(cell(&editor, 0, 3), (Draw::SPACE_CHAR, true, true));
+        assert_eq!(cell(&editor, 0, 4), (Draw::TAB_CHAR, true, true));
+
+        // All whitespace is converted, though only trailing whitespace is highlighted.
+        editor.set_whitespace(Whitespace::All);
+        editor.render_buffer();
+        assert_eq!(cell(&editor, 0, 1), (Draw::SPACE_CHAR, true, false));
+        assert_eq!(cell(&editor, 0, 3), (Draw::SPACE_CHAR, true, true));
+
+        // Literal middle dot in buffer is never colored as whitespace.
+        for whitespace in [Whitespace::None, Whitespace::Trailing, Whitespace::All] {
+            editor.set_whitespace(whitespace);
+            editor.render_buffer();
+            assert_eq!(cell(&editor, 1, 1), ('\u{b7}', false, false));
+        }
+
+        // Selection supersedes trailing whitespace.
+        editor.set_hard_mark();
+        editor.move_to(4, Align::Auto);
+        editor.render_buffer();
+        let select_bg = theme.select_bg.color;
+        assert!(editor.canvas.borrow().get_cell(0, 3).color.bg == select_bg);
+        assert_eq!(cell(&editor, 0, 4), (Draw::TAB_CHAR, true, true));
+    }
+
+    #[test]
+    fn clip_columns() {
+        let mut editor = build_editor("0123456789abcdefghij\nabcdefgh\u{65e5}x\n", 4, 10);
+        assert_eq!((editor.margin_cols, editor.cols), (0, 10));
+
+        // Single column of margin is reserved for clip markers.
+        editor.set_wrap(false);
+        assert_eq!((editor.margin_cols, editor.cols), (1, 9));
+        assert!(editor.is_visible(0) && editor.is_visible(8));
+        assert!(!editor.is_visible(9));
+        assert_eq!(editor.screen_col(0), 1);
+        assert_eq!(editor.screen_col(12), 10);
+
+        // Display scrolls right just enough to show cursor, which happens as part of
+        // rendering.
+        editor.move_to(15, Align::Auto);
+        editor.align_left_col();
+        assert_eq!(editor.left_col, 7);
+        assert!(!editor.is_visible(6) && !editor.is_visible(16));
+        assert!(editor.is_visible(7) && editor.is_visible(15));
+        assert_eq!(editor.screen_col(3), 1);
+        assert_eq!(editor.screen_col(10), 4);
+
+        // Display scrolls left just enough to show cursor.
+        editor.move_to(2, Align::Auto);
+        editor.align_left_col();
+        assert_eq!(editor.left_col, 2);
+        editor.move_to(0, Align::Auto);
+        editor.align_left_col();
+        assert_eq!(editor.left_col, 0);
+
+        // Wide character under cursor is entirely visible.
+        editor.move_to(29, Align::Auto);
+        editor.align_left_col();
+        assert_eq!(editor.cursor, Point::new(1, 8));
+        assert_eq!(editor.left_col, 1);
+
+        editor.set_wrap(true);
+        assert_eq!(editor.left_col, 0);
+        assert_eq!((editor.margin_cols, editor.cols), (0, 10));
+    }
+
+    #[test]
+    fn render_clip_markers() {
+        let mut editor = build_editor("0123456789abcdefghij\nab\n", 4, 10);
+        editor.settings.lines = false;
+        editor.set_wrap(false);
+        let markers = |editor: &EditorKernel| {
+            (0..3)
+                .map(|row| editor.canvas.borrow().get_cell(row, 0).value)
+                .collect::<String>()
+        };
+
+        editor.render_buffer();
+        assert_eq!(markers(&editor), format!("{}  ", Draw::CLIP_RIGHT_CHAR));
+
+        // Empty rows are never clipped.
+        editor.move_to(15, Align::Auto);
+        editor.align_left_col();
+        editor.render_buffer();
+        let expect = format!("{}{} ", Draw::CLIP_BOTH_CHAR, Draw::CLIP_LEFT_CHAR);
+        assert_eq!(markers(&editor), expect);
+
+        // Markers are shown in last column of line numbers if enabled.
+        let mut editor = build_editor("0123456789abcdefghij\n", 4, 16);
+        editor.margin_cols = 6;
+        editor.cols = 10;
+        editor.set_wrap(false);
+        assert_eq!((editor.margin_cols, editor.cols), (6, 10));
+        editor.render_buffer();
+        let cell = editor.canvas.borrow().get_cell(0, 5);
+        assert_eq!(cell.value, Draw::CLIP_RIGHT_CHAR);
+    }
+
+    #[test]
+    fn find_clusters() {
+        // Combining mark, emoji joined by ZWJ, and wide characters.
+        let text = "e\u{301}x\u{1f469}\u{200d}\u{1f4bb}y\u{65e5}\u{672c}\n";
+        let editor = build_editor(text, 4, 20);
+        let after = (0..=10)
+            .map(|pos| editor.find_cluster_after(pos))
+            .collect::<Vec<_>>();
+        assert_eq!(after, vec![2, 2, 3, 6, 6, 6, 7, 8, 9, 10, 10]);
+        let before = (0..=10)
+            .map(|pos| editor.find_cluster_before(pos))
+            .collect::<Vec<_>>();
+        assert_eq!(before, vec![0, 0, 0, 2, 3, 3, 3, 6, 7, 8, 9]);
+
+        // Marks attached to \n are not joined.
+        let editor = build_editor("a\n\u{301}b", 4, 20);
+        assert_eq!(editor.find_cluster_after(1), 2);
+        assert_eq!(editor.find_cluster_before(3), 2);
+    }
+
+    #[test]
+    fn move_over_clusters() {
+        let text = "e\u{301}x\u{1f469}\u{200d}\u{1f4bb}y\u{65e5}\u{672c}\n";
+        let mut editor = build_editor(text, 4, 20);
+
+        // Cursor moves over entire clusters, where wide characters occupy two columns.
+        let mut cursors = Vec::new();
+        for _ in 0..7 {
+            editor.move_forward(1);
+            cursors.push((editor.cur_pos, editor.cursor.col));
+        }
+        assert_eq!(
+            cursors,
+            vec![(2, 1), (3, 2), (6, 6), (7, 7), (8, 9), (9, 11), (10, 0)]
+        );
+
+        // Cursor moves back over same clusters from start of following line.
+        let mut cursors = Vec::new();
+        for _ in 0..7 {
+            editor.move_backward(1);
+            cursors.push((editor.cur_pos, editor.cursor.col));
+        }
+        assert_eq!(
+            cursors,
+            vec![(9, 11), (8, 9), (7, 7), (6, 6), (3, 2), (2, 1), (0, 0)]
+        );
+    }
+
+    #[test]
+    fn tab_columns() {
+        // Columns of each position in a line mixing tabs and wide characters.
+        for (tab_size, expect) in [
+            (1, [0, 1, 2, 4, 5, 6]),
+            (3, [0, 3, 4, 6, 9, 10]),
+            (4, [0, 4, 5, 7, 8, 9]),
+            (8, [0, 8, 9, 11, 16, 17]),
+        ] {
+            let mut editor = build_editor("\ta\u{65e5}\tb\n", 4, 40);
+            editor.settings.tab_size = tab_size;
+            let line = editor.find_line(0);
+            let cols = (0..6)
+                .map(|pos| editor.col_of(&line, pos))
+                .collect::<Vec<_>>();
+            assert_eq!(cols, expect, "tab size {tab_size}");
+            assert_eq!(editor.scan_row(0), (6, expect[5] + 1));
+
+            // Columns occupied by a character map back to its starting column.
+            for (pos, &col) in expect.iter().enumerate() {
+                let end_col = expect.get(pos + 1).copied().unwrap_or(col + 1);
+                for c in col..end_col {
+                    assert_eq!(editor.find_col(&line, c), (col, pos));
+                }
+            }
+            assert_eq!(editor.find_col(&line, 40), (expect[5], 5));
+        }
+    }
+
+    #[test]
+    fn tab_rows() {
+        let mut editor = build_editor("ab\tcd\tef\tg\nabcdefgh\tx\n", 4, 10);
+        editor.settings.tab_size = 4;
+
+        // Rows end when next tab no longer fits.
+        assert_eq!(editor.scan_row(0), (8, 10));
+        assert_eq!(editor.scan_row(8), (3, 6));
+        let line = editor.find_line(9);
+        assert_eq!((line.row_pos, line.row_len, line.row_cols), (8, 3, 6));
+        assert_eq!(editor.col_of(&line, 9), 4);
+
+        // Tab is truncated at end of row.
+        assert_eq!(editor.scan_row(11), (9, 10));
+        let line = editor.find_line(20);
+        assert_eq!((line.row_pos, line.row_len, line.row_cols), (20, 2, 2));
+    }
+
+    #[test]
+    fn find_wrapped_rows() {
+        // Wide characters vary the width of rows of the middle line.
+        let text = format!("a\n{}\nb", "x\u{65e5}".repeat(20));
+        let editor = build_editor(&text, 4, 5);
+        let mut lines = vec![editor.find_line(0)];
+        while let Some(line) = editor.next_line(lines.last().unwrap()) {
+            lines.push(line);
+        }
+        let rows = |line: &Line| (line.row_pos, line.row_len, line.row_cols);
+
+        // Moving up finds the same rows as moving down.
+        let mut line = lines.last().unwrap().clone();
+        for expect in lines.iter().rev().skip(1) {
+            line = editor.prev_line_unchecked(&line);
+            assert_eq!(rows(&line), rows(expect));
+            if line.row_pos == line.line_pos && line.line == 1 {
+                // Rows of the middle line were scanned only once.
+                let n = lines.iter().filter(|l| l.line == 1).count();
+                assert_eq!(editor.row_cache.borrow().rows.len(), n);
+            }
+        }
+
+        // Scanning stops at the row containing the position.
+        let line = editor.find_line(10);
+        assert_eq!(rows(&line), rows(&lines[3]));
+        assert_eq!(editor.row_cache.borrow().rows.len(), 3);
+        assert_eq!(editor.find_row_col(line.clone(), 10).1, 9);
+    }
+
+    #[test]
+    fn location_over_tabs() {
+        let mut editor = build_editor("\tx\u{65e5}y\nabcdefghij\n", 4, 6);
+        editor.settings.tab_size = 4;
+
+        // Column accounts for width of tabs and wide characters.
+        editor.move_to(2, Align::Auto);
+        assert_eq!(editor.location(), Point::new(0, 5));
+        editor.move_to(3, Align::Auto);
+        assert_eq!(editor.location(), Point::new(0, 7));
+
+        // Column accounts for rows wrapped from start of line.
+        editor.move_to(13, Align::Auto);
+        assert_eq!((editor.cursor.row, editor.cursor.col), (3, 2));
+        assert_eq!(editor.location(), Point::new(1, 8));
+    }
+
+    #[test]
+    fn insert_soft_tabs() {
+        let mut editor = build_editor("\tx\n\u{65e5}\n", 4, 20);
+        editor.settings.tab_size = 4;
+        editor.settings.tab_hard = false;
+
+        // Spaces extend to next tab stop following hard tab.
+        editor.move_to(2, Align::Auto);
+        editor.insert_tab();
+        assert_eq!(buffer_text(&editor), "\tx   \n\u{65e5}\n");
+        assert_eq!(editor.location(), Point::new(0, 8));
+
+        // Spaces extend to next tab stop following wide character.
+        editor.move_to(7, Align::Auto);
+        editor.insert_tab();
+        assert_eq!(buffer_text(&editor), "\tx   \n\u{65e5}  \n");
+        assert_eq!(editor.location(), Point::new(1, 4));
+    }
+
+    #[test]
+    fn focus_over_tabs() {
+        let mut editor = build_editor("abcdefgh\n\tx\u{65e5}\nabcdefgh\n", 4, 20);
+        editor.settings.tab_size = 4;
+
+        // Focus on any column occupied by a character lands on its starting column.
+        for (col, expect) in [
+            (0, (0, 9)),
+            (3, (0, 9)),
+            (4, (4, 10)),
+            (6, (5, 11)),
+            (9, (7, 12)),
+        ] {
+            editor.set_focus(Point::new(1, col));
+            assert_eq!((editor.cursor.col, editor.cur_pos), expect, "col {col}");
+        }
+
+        // Cursor snaps back to original column after passing over shorter line.
+        editor.set_focus(Point::new(0, 6));
+        editor.move_down(1, false);
+        assert_eq!((editor.cursor.col, editor.cur_pos), (5, 11));
+        editor.move_down(1, false);
+        assert_eq!((editor.cursor.col, editor.cur_pos), (6, 19));
+        editor.move_up(2, false);
+        assert_eq!((editor.cursor.col, editor.cur_pos), (6, 6));
+    }
+
+    #[test]
+    fn indent_region_lines() {
+        // Mark selects first three lines, though empty lines are not indented, and a
+        // line is only included if the selection extends beyond its start.
+        let text = "a\n\nb\nc\n";
+        let mut editor = build_editor(text, 10, 40);
+        editor.set_hard_mark();
+        editor.move_to(4, Align::Auto);
+        editor.indent_region();
+        assert_eq!(buffer_text(&editor), "    a\n\n    b\nc\n");
+        assert!(editor.mark.is_some());
+
+        // All lines are restored using a single undo.
+        assert!(editor.undo());
+        assert_eq!(buffer_text(&editor), text);
+
+        // Hard tabs are inserted if enabled.
+        editor.set_tab(true);
+        editor.move_to(0, Align::Auto);
+        editor.set_hard_mark();
+        editor.move_to(4, Align::Auto);
+        editor.indent_region();
+        assert_eq!(buffer_text(&editor), "\ta\n\n\tb\nc\n");
+    }
+
+    #[test]
+    fn outdent_region_lines() {
+        // Spaces are removed up to prior tab stop, and tabs are removed individually.
+        let text = "      a\n  b\n\t\tc\nd\n";
+        let mut editor = build_editor(text, 10, 40);
+        editor.set_hard_mark();
+        editor.move_to(buffer_text(&editor).rfind('d').unwrap(), Align::Auto);
+        editor.outdent_region();
+        assert_eq!(buffer_text(&editor), "    a\nb\n\tc\nd\n");
+        editor.outdent_region();
+        assert_eq!(buffer_text(&editor), "a\nb\nc\nd\n");
+
+        assert!(editor.undo());
+        assert!(editor.undo());
+        assert_eq!(buffer_text(&editor), text);
+    }
+
+    #[test]
+    fn tidy_buffer() {
+        let text = "a  \n\t\nb \t\r\n  c\t \r\nd ";
+        let mut editor = build_editor(text, 10, 40);
+
+        // Cursor inside trailing whitespace of third line stays on same row.
+        editor.move_to(8, Align::Auto);
+        assert_eq!(editor.cursor.row, 2);
+        assert!(editor.tidy(true, true));
+        assert_eq!(buffer_text(&editor), "a\n\nb\r\n  c\r\nd\n");
+        assert_eq!(editor.cursor.row, 2);
+        assert_eq!(editor.location(), Point::new(2, 1));
+
+        // All changes are undone in a single step.
+        assert!(editor.undo());
+        assert_eq!(buffer_text(&editor), text);
+        assert!(!editor.tidy(false, false));
+
+        // Each transform applies independently.
+        assert!(editor.tidy(true, false));
+        assert_eq!(buffer_text(&editor), "a\n\nb\r\n  c\r\nd");
+        assert!(!editor.tidy(true, false));
+        assert!(editor.tidy(false, true));
+        assert_eq!(buffer_text(&editor), "a\n\nb\r\n  c\r\nd\n");
+        assert!(!editor.tidy(true, true));
+
+        // Final newline is not added to an empty buffer.
+        let mut editor = build_editor("", 10, 40);
+        assert!(!editor.tidy(true, true));
+        assert_eq!(buffer_text(&editor), "");
+    }
+
+    #[test]
+    fn uncomment_block() {
+        for (text, expect) in [
+            ("/* */\nabc\n", "\nabc\n"),
+            ("/**/\nabc\n", "\nabc\n"),
+            ("/* x */\nabc\n", "x\nabc\n"),
+            ("  /*x*/", "  x"),
+        ] {
+            let mut editor = build_editor(text, 10, 40);
+            editor.set_syntax(build_block_comment_syntax());
+            assert!(editor.comment_region());
+            assert_eq!(buffer_text(&editor), expect, "uncommenting {text:?}");
+        }
+    }
+
+    #[test]
+    fn comment_lines() {
+        let comment_all = |text: &str| {
+            let mut editor = build_editor(text, 10, 40);
+            editor.set_syntax(build_line_comment_syntax());
+            editor.set_hard_mark();
+            editor.move_to(text.len(), Align::Auto);
+            assert!(editor.comment_region());
+            editor
+        };
+
+        // Markers follow indentation of each line, and blank lines are skipped.
+        let text = "fn f() {\n    a\n\n  \n\tb\n}\n";
+        let mut editor = comment_all(text);
+        assert_eq!(
+            buffer_text(&editor),
+            "// fn f() {\n    // a\n\n  \n\t// b\n// }\n"
+        );
+        assert!(editor.comment_region());
+        assert_eq!(buffer_text(&editor), text);
+
+        // All lines are commented again if only some are already commented.
+        let mut editor = comment_all("// a\n  b\n");
+        assert_eq!(buffer_text(&editor), "// // a\n  // b\n");
+        assert!(editor.undo());
+        assert_eq!(buffer_text(&editor), "// a\n  b\n");
+
+        // Markers are removed with or without a following space.
+        let editor = comment_all("//a\n\n  // b\n");
+        assert_eq!(buffer_text(&editor), "a\n\n  b\n");
+
+        // Region of only blank lines is unchanged.
+        let editor = comment_all("\n  \n");
+        assert_eq!(buffer_text(&editor), "\n  \n");
+
+        // Only current line is toggled without mark.
+        let mut editor = build_editor("a\nb\n", 10, 40);
+        editor.set_syntax(build_line_comment_syntax());
+        editor.move_to(2, Align::Auto);
+        assert!(editor.comment_region());
+        assert_eq!(buffer_text(&editor), "a\n// b\n");
+    }
+
+    #[test]
+    fn comment_block() {
+        let mut editor = build_editor("  x\nabc\n", 10, 40);
+        editor.set_syntax(build_block_comment_syntax());
+        assert!(editor.comment_region());
+        assert_eq!(buffer_text(&editor), "  /* x */\nabc\n");
+        assert!(editor.comment_region());
+        assert_eq!(buffer_text(&editor), "  x\nabc\n");
+    }
+
+    #[test]
+    fn share_buffer() {
+        let config = Configuration::default().to_ref();
+        let editor = Editor::mutable(config.clone(), Source::Null, None);
+        let other = editor.share();
+        assert!(editor.is_shared());
+        assert!(editor.is_shared_with(&other));
+        assert!(other.is_shared_with(&editor));
+
+        let unrelated = Editor::mutable(config, Source::Null, None);
+        assert!(!unrelated.is_shared());
+        assert!(!editor.is_shared_with(&unrelated));
+
+        drop(other);
+        assert!(!editor.is_shared());
+    }
+
+    #[test]
+    fn assume_path_settings() {
+        let content = "[[overrides]]\npath = \"*.mk\"\ntab-hard = true\nfinal-newline = true\n";
+        let config = build_config(content).to_ref();
+        let source = Source::as_file("/no/such/dir/a.txt", None);
+        let mut editor = EditorKernel::new(config, source, None);
+        let mut other = share_editor(&editor);
+        editor.set_whitespace(Whitespace::All);
+        assert!(!editor.settings.tab_hard);
+
+        // Settings for another path are resolved without being applied.
+        let settings = editor.settings_for("/no/such/dir/b.mk");
+        assert!(settings.tab_hard && settings.final_newline);
+        assert!(settings.whitespace == Whitespace::All);
+        assert!(!editor.settings.tab_hard);
+
+        // Settings are resolved again when path changes, though modes toggled by user
+        // are preserved.
+        editor.assume(Source::as_file("/no/such/dir/b.mk", None));
+        assert!(editor.settings.tab_hard && editor.settings.final_newline);
+        assert!(editor.settings.whitespace == Whitespace::All);
+
+        // Shared editors follow the change of path.
+        assert!(!other.settings.tab_hard);
+        other.sync();
+        assert!(other.settings.tab_hard && other.settings.final_newline);
+        assert!(other.settings.whitespace == Whitespace::None);
+    }
+
+    #[test]
+    fn sync_shared_edits() {
+        let text = "abc\ndef\nghi\n";
+        let mut editor = build_editor(text, 10, 40);
+        let mut other = share_editor(&editor);
+
+        // Other editor selects "ef" with cursor before mark.
+        other.move_to(7, Align::Auto);
+        other.set_hard_mark();
+        other.move_to(5, Align::Auto);
+        assert!(!other.sync());
+
+        // Insertion before cursor and mark shifts both.
+        editor.insert_str("xx");
+        assert!(other.sync());
+        assert_eq!(other.cur_pos, 7);
+        assert_eq!(mark_pos(&other), Some(9));
+        assert!(!other.sync());
+
+        // Insertion between cursor and mark only shifts mark.
+        editor.move_to(8, Align::Auto);
+        editor.insert_char('y');
+        assert!(other.sync());
+        assert_eq!(other.cur_pos, 7);
+        assert_eq!(mark_pos(&other), Some(10));
+
+        // Insertion after both shifts neither.
+        editor.move_to(13, Align::Auto);
+        editor.insert_char('z');
+        assert!(other.sync());
+        assert_eq!(other.cur_pos, 7);
+        assert_eq!(mark_pos(&other), Some(10));
+
+        // Removal in other editor shifts cursor of first editor, which follows it.
+        other.remove_before();
+        assert!(editor.sync());
+        assert_eq!(editor.cur_pos, 13);
+        assert_eq!(buffer_text(&editor), "xxabc\neyf\nghzi\n");
+        assert_eq!(other.buffer().get_char(other.cur_pos), Some('e'));
+
+        // Removal spanning cursor and mark collapses both to start of removal.
+        editor.move_to(5, Align::Auto);
+        editor.set_hard_mark();
+        editor.move_to(10, Align::Auto);
+        let mark = editor.clear_mark().unwrap();
+        editor.remove_mark(mark);
+        assert!(other.sync());
+        assert_eq!(other.cur_pos, 5);
+        assert_eq!(mark_pos(&other), Some(5));
+    }
+
+    #[test]
+    fn sync_many_shared_edits() {
+        // Indenting more lines than the log limit in a single operation must still
+        // adjust positions in the other editor, so the log is only trimmed once all
+        // editors have synchronized.
+        let lines = Shared::SHIFTS_LIMIT + 1;
+        let text = "x\n".repeat(lines) + "end\n";
+        let mut editor = build_editor(&text, 10, 40);
+        let mut other = share_editor(&editor);
+        other.move_to(lines * 2 + 1, Align::Auto);
+        other.set_hard_mark();
+        other.move_to(lines * 2, Align::Auto);
+
+        editor.set_hard_mark();
+        editor.move_to(lines * 2, Align::Auto);
+        editor.indent_region();
+        assert!(editor.shared().shifts.len() > Shared::SHIFTS_LIMIT);
+        assert!(other.sync());
+        assert!(editor.shared().shifts.len() < Shared::SHIFTS_LIMIT);
+        let expect = lines * 6;
+        assert_eq!(other.cur_pos, expect);
+        assert_eq!(mark_pos(&other), Some(expect + 1));
+        assert_eq!(other.buffer().get_char(other.cur_pos), Some('e'));
+
+        // Undoing in other editor restores position in first editor.
+        editor.clear_mark();
+        editor.move_to(expect - 2, Align::Auto);
+        assert!(other.undo());
+        assert!(editor.sync());
+        assert_eq!(buffer_text(&editor), text);
+        assert_eq!(editor.cur_pos, lines * 2 - 2);
+    }
+
+    #[test]
+    fn undo_across_shared_editors() {
+        let mut editor = build_editor("abc\ndef\n", 10, 40);
+        let mut other = share_editor(&editor);
+        other.move_to(5, Align::Auto);
+
+        editor.insert_str("123\n");
+        assert!(other.sync());
+        assert_eq!(other.cur_pos, 9);
+
+        // Undo stack is shared, so other editor undoes change of first editor.
+        assert!(other.undo());
+        assert_eq!(buffer_text(&other), "abc\ndef\n");
+        assert_eq!(other.cur_pos, 0);
+        assert!(editor.sync());
+        assert_eq!(editor.cur_pos, 0);
+
+        assert!(editor.redo());
+        assert!(other.sync());
+        assert_eq!(buffer_text(&other), "123\nabc\ndef\n");
+        assert_eq!(other.cur_pos, 0);
+    }
+}
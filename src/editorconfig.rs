@@ -0,0 +1,187 @@
+//! Support for [EditorConfig](https://editorconfig.org) files.
+//!
+//! The properties of a file are found by searching for files named `.editorconfig`
+//! in the directory of the file and each of its ancestors, stopping at the first
+//! file that declares `root = true`. Files closer to the root are applied first, so
+//! properties in files closer to the file being edited take precedence, as do later
+//! sections within the same file.
+//!
+//! A section name is a glob pattern, which is matched against the file name if it
+//! does not contain `/`, otherwise against the path relative to the directory of the
+//! `.editorconfig` file.
+//!
+//! Parsing is lenient in accordance with the specification, so malformed lines and
+//! unreadable files are quietly ignored.
+
+use crate::glob;
+use crate::sys::{self, AsString};
+use regex_lite::{escape, Regex};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Name of files containing properties.
+const FILE_NAME: &str = ".editorconfig";
+
+/// Value that removes the effect of a property set in a prior section or file.
+const UNSET: &str = "unset";
+
+/// A section of an `.editorconfig` file.
+struct Section {
+    /// Regular expression equivalent to the glob pattern of the section, which is
+    /// anchored to the directory of the file.
+    re: Option<Regex>,
+
+    /// Properties in order of appearance, with names and values in lowercase.
+    props: Vec<(String, String)>,
+}
+
+/// Returns the properties that apply to the file at `path`, where names and values
+/// are in lowercase.
+pub fn properties(path: &str) -> HashMap<String, String> {
+    let path = sys::canonicalize(path).as_string();
+    let mut dirs = Vec::new();
+    let mut dir = sys::base_dir(&path);
+    loop {
+        let (root, sections) = read_file(&dir.join(FILE_NAME), &dir.as_string());
+        dirs.push(sections);
+        if root {
+            break;
+        }
+        match dir.parent() {
+            Some(parent) => dir = parent.to_path_buf(),
+            None => break,
+        }
+    }
+
+    let mut props = HashMap::new();
+    for section in dirs.iter().rev().flatten() {
+        if section.re.as_ref().is_some_and(|re| re.is_match(&path)) {
+            for (name, value) in &section.props {
+                if value == UNSET {
+                    props.remove(name);
+                } else {
+                    props.insert(name.clone(), value.clone());
+                }
+            }
+        }
+    }
+    props
+}
+
+/// Reads the `.editorconfig` file at `path` located in `dir`, returning a tuple
+/// containing `true` if the file declares itself as the root along with its
+/// sections.
+///
+/// A missing or unreadable file is treated as being empty.
+fn read_file(path: &Path, dir: &str) -> (bool, Vec<Section>) {
+    fs::read_to_string(path)
+        .map(|content| parse(&content, dir))
+        .unwrap_or_default()
+}
+
+/// Parses the contents of an `.editorconfig` file located in `dir`.
+fn parse(content: &str, dir: &str) -> (bool, Vec<Section>) {
+    let mut root = false;
+    let mut sections: Vec<Section> = Vec::new();
+    for line in content.lines().map(|line| line.trim()) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            sections.push(Section {
+                re: to_regex(name, dir),
+                props: Vec::new(),
+            });
+        } else if let Some((name, value)) = line.split_once('=') {
+            let name = name.trim().to_lowercase();
+            let value = value.trim().to_lowercase();
+            if let Some(section) = sections.last_mut() {
+                section.props.push((name, value));
+            } else if name == "root" {
+                root = value == "true";
+            }
+        }
+    }
+    (root, sections)
+}
+
+/// Returns a regular expression that matches paths in accordance with the glob
+/// pattern `name` of a section in an `.editorconfig` file located in `dir`.
+fn to_regex(name: &str, dir: &str) -> Option<Regex> {
+    let pattern = if name.contains('/') {
+        glob::to_pattern(name.strip_prefix('/').unwrap_or(name))
+    } else {
+        glob::to_pattern(&format!("**/{name}"))
+    };
+    let dir = dir.strip_suffix('/').unwrap_or(dir);
+    Regex::new(&format!("^{}/{pattern}$", escape(dir))).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = r#"
+# comment
+root = true
+
+[*]
+indent_style = space
+indent_size = 4
+
+[*.{yml,yaml}]
+Indent_Size = 2
+
+; comment
+[Makefile]
+indent_style = TAB
+
+[lib/**.js]
+indent_size = unset
+"#;
+
+    fn matches(sections: &[Section], path: &str) -> Vec<(String, String)> {
+        sections
+            .iter()
+            .filter(|s| s.re.as_ref().is_some_and(|re| re.is_match(path)))
+            .flat_map(|s| s.props.clone())
+            .collect()
+    }
+
+    fn prop(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn parse_sections() {
+        let (root, sections) = parse(CONTENT, "/a");
+        assert!(root);
+        assert_eq!(sections.len(), 4);
+        assert_eq!(
+            matches(&sections, "/a/b/c.yml"),
+            vec![
+                prop("indent_style", "space"),
+                prop("indent_size", "4"),
+                prop("indent_size", "2")
+            ]
+        );
+        assert_eq!(
+            matches(&sections, "/a/Makefile")[2],
+            prop("indent_style", "tab")
+        );
+        assert_eq!(
+            matches(&sections, "/a/lib/x/y.js")[2],
+            prop("indent_size", "unset")
+        );
+        assert_eq!(matches(&sections, "/a/src/lib/y.js").len(), 2);
+        assert_eq!(matches(&sections, "/b/c.yml").len(), 0);
+    }
+
+    #[test]
+    fn parse_root() {
+        let (root, sections) = parse("[*]\nroot = true\n", "/");
+        assert!(!root);
+        assert_eq!(matches(&sections, "/x"), vec![prop("root", "true")]);
+    }
+}
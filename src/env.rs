@@ -29,11 +29,28 @@ pub struct Environment {
 pub enum Focus {
     Top,
     Bottom,
-    Above,
-    Below,
+    Prev,
+    Next,
+    Up,
+    Down,
+    Left,
+    Right,
     To(u32),
 }
 
+/// The outcome of [`Environment::toggle_maximize`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Maximize {
+    /// The active window was maximized.
+    Maximized,
+
+    /// Hidden windows were restored.
+    Restored,
+
+    /// Nothing changed because the active window is the only window.
+    Unchanged,
+}
+
 impl Environment {
     /// Collection of predefined editors, all of which are _ephemeral_ and may not
     /// be removed from the list of editors.
@@ -106,10 +123,19 @@ impl Environment {
         self.active_view_id = match focus {
             Focus::Top => self.workspace().top_view().id,
             Focus::Bottom => self.workspace().bottom_view().id,
-            Focus::Above => self.workspace().above_view(self.active_view_id).id,
-            Focus::Below => self.workspace().below_view(self.active_view_id).id,
+            Focus::Prev => self.workspace().prev_view(self.active_view_id).id,
+            Focus::Next => self.workspace().next_view(self.active_view_id).id,
+            Focus::Up => self.workspace().up_view(self.active_view_id).id,
+            Focus::Down => self.workspace().down_view(self.active_view_id).id,
+            Focus::Left => self.workspace().left_view(self.active_view_id).id,
+            Focus::Right => self.workspace().right_view(self.active_view_id).id,
             Focus::To(view_id) => {
                 if self.view_map.contains_key(&view_id) {
+                    // Restore hidden views if focus is shifting to one of them.
+                    if !self.workspace().get_view(view_id).is_visible() {
+                        self.workspace_mut().restore_views();
+                        self.reattach_views();
+                    }
                     view_id
                 } else {
                     panic!("expecting view id {view_id}")
@@ -248,8 +274,10 @@ impl Environment {
         let editor_id = self.get_view_editor_id_unchecked(view_id);
         let next_id = self.close_window_for(view_id);
         next_id.map(|next_id| {
+            // Editor may have already been removed when detached if it shares its
+            // buffer with other editors.
             if !self.is_builtin(editor_id) {
-                self.remove_editor_unchecked(editor_id);
+                self.editor_map.remove(&editor_id);
             }
             next_id
         })
@@ -268,8 +296,7 @@ impl Environment {
             if self.is_builtin(editor_id) {
                 None
             } else {
-                self.remove_editor_unchecked(editor_id);
-                Some(editor_id)
+                self.editor_map.remove(&editor_id).map(|_| editor_id)
             }
         }
     }
@@ -284,6 +311,14 @@ impl Environment {
         self.clipboard.as_ref()
     }
 
+    /// Synchronizes all editors with changes made to buffers shared with other
+    /// editors.
+    pub fn sync_editors(&mut self) {
+        for editor in self.editor_map.values() {
+            editor.borrow_mut().sync();
+        }
+    }
+
     /// Resizes the workspace, which might remove a subset of views if resizing
     /// violates the minimum size constraint for windows.
     pub fn resize(&mut self) {
@@ -298,6 +333,54 @@ impl Environment {
         }
     }
 
+    /// Recreates and redraws all views in the workspace, which is necessary after
+    /// the theme changes.
+    pub fn refresh(&mut self) {
+        self.workspace_mut().refresh();
+        self.workspace_mut().clear_shared();
+        self.reattach_views();
+        self.focus(self.active_view_id);
+    }
+
+    /// Changes the size of the active window by `delta` rows or columns, returning
+    /// `true` if the window was resized.
+    pub fn resize_window(&mut self, delta: i32) -> bool {
+        let resized = self.workspace_mut().resize_view(self.active_view_id, delta);
+        if resized {
+            self.reattach_views();
+            self.focus(self.active_view_id);
+        }
+        resized
+    }
+
+    /// Restores an equal allocation of area among all windows.
+    pub fn balance_windows(&mut self) {
+        self.workspace_mut().balance_views();
+        self.reattach_views();
+        self.focus(self.active_view_id);
+    }
+
+    /// Maximizes the active window by temporarily hiding all other windows, though
+    /// hidden windows remain attached to their editors. If the active window is
+    /// already maximized, then hidden windows are restored instead.
+    ///
+    /// Returns the outcome of the toggle, noting that the active window cannot be
+    /// maximized if it is the only window.
+    pub fn toggle_maximize(&mut self) -> Maximize {
+        let outcome = if self.workspace_mut().restore_views() {
+            Maximize::Restored
+        } else if self.workspace_mut().maximize_view(self.active_view_id) {
+            Maximize::Maximized
+        } else {
+            Maximize::Unchanged
+        };
+        if outcome != Maximize::Unchanged {
+            self.reattach_views();
+            self.focus(self.active_view_id);
+        }
+        outcome
+    }
+
     pub fn editor_map(&self) -> &EditorMap {
         &self.editor_map
     }
@@ -310,7 +393,7 @@ impl Environment {
     /// also detaches the window from its current editor if an association exists.
     fn attach_to_editor(&mut self, view_id: u32, editor_id: u32, align: Align) {
         if let Some(id) = self.view_map.get(&view_id) {
-            self.get_editor_unchecked(*id).borrow_mut().detach();
+            self.detach_editor(*id);
         }
         self.get_editor_unchecked(editor_id)
             .borrow_mut()
@@ -335,10 +418,25 @@ impl Environment {
             .view_map
             .remove(&view_id)
             .unwrap_or_else(|| panic!("expecting view id {view_id}"));
-        self.get_editor_unchecked(editor_id).borrow_mut().detach();
+        self.detach_editor(editor_id);
         editor_id
     }
 
+    /// Detaches the editor of `editor_id` from its window.
+    ///
+    /// If the editor shares its buffer with other editors, then it is also removed,
+    /// as its only purpose was to provide an independent view of that buffer.
+    fn detach_editor(&mut self, editor_id: u32) {
+        let shared = {
+            let mut editor = self.get_editor_unchecked(editor_id).borrow_mut();
+            editor.detach();
+            editor.is_shared()
+        };
+        if shared && !self.is_builtin(editor_id) {
+            self.editor_map.remove(&editor_id);
+        }
+    }
+
     fn add_editor(&mut self, editor: EditorRef) -> u32 {
         let editor_id = self.next_editor_id();
         self.editor_map.insert(editor_id, editor);
@@ -351,12 +449,6 @@ impl Environment {
             .unwrap_or_else(|| panic!("expecting editor id {editor_id}"))
     }
 
-    fn remove_editor_unchecked(&mut self, editor_id: u32) -> EditorRef {
-        self.editor_map
-            .remove(&editor_id)
-            .unwrap_or_else(|| panic!("expecting editor id {editor_id}"))
-    }
-
     fn get_view_editor_id_unchecked(&self, view_id: u32) -> u32 {
         *self
             .view_map
@@ -402,3 +494,40 @@ impl Environment {
         self.workspace.borrow_mut()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Configuration;
+    use crate::size::Size;
+
+    fn build_env() -> Environment {
+        let workspace = Workspace::with_size(Configuration::default(), Size::new(25, 80));
+        Environment::new(workspace.to_ref())
+    }
+
+    fn build_editor(env: &Environment, name: &str) -> EditorRef {
+        let config = env.workspace().config().clone();
+        Editor::mutable(config, Source::Ephemeral(name.to_string()), None).to_ref()
+    }
+
+    #[test]
+    fn detach_shared_editor() {
+        let mut env = build_env();
+        let editor_id = env.set_editor(build_editor(&env, "a"), Align::Auto);
+        let shared = env.get_active_editor().borrow().share().to_ref();
+        let place = Placement::Right(env.get_active_view_id());
+        let (view_id, shared_id) = env.open_editor(shared, place, Align::Auto).unwrap();
+        assert!(env.get_active_editor().borrow().is_shared());
+
+        // Editor sharing buffer is removed when detached from its window.
+        env.close_window_for(view_id);
+        assert!(!env.editor_map().contains_key(&shared_id));
+        assert!(env.editor_map().contains_key(&editor_id));
+        assert!(!env.get_active_editor().borrow().is_shared());
+
+        // Editor no longer sharing buffer remains when detached.
+        env.switch_editor(Environment::BUILTIN_EDITORS[0].0, Align::Auto);
+        assert!(env.editor_map().contains_key(&editor_id));
+    }
+}
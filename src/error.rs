@@ -37,6 +37,9 @@ pub enum Error {
     /// An operation `op` given in a key binding is not valid.
     InvalidOp { op: String },
 
+    /// A user-defined command `op` conflicts with the name of a predefined operation.
+    RedefinedOp { op: String },
+
     /// A `key_seq` is restricted from being rebound.
     RestrictedKey { key_seq: String },
 
@@ -46,11 +49,23 @@ pub enum Error {
     /// An error occurred while parsing a syntax file referenced by `path`.
     Syntax { path: String, cause: String },
 
+    /// An error occurred while parsing a theme file referenced by `path`.
+    Theme { path: String, cause: String },
+
     /// A regular expression `pattern` is invalid or too large in compiled form.
     InvalidRegex { pattern: String, cause: String },
 
     /// The color `name` is not valid.
     InvalidColor { name: String },
+
+    /// The theme `name` does not exist.
+    InvalidTheme { name: String },
+
+    /// The banner `format` is not valid.
+    InvalidBannerFormat { format: String, cause: String },
+
+    /// The syntax `name` includes the syntax `include`, which does not exist.
+    InvalidInclude { name: String, include: String },
 }
 
 impl error::Error for Error {}
@@ -111,6 +126,10 @@ impl Error {
         Error::InvalidOp { op: op.to_string() }
     }
 
+    pub fn redefined_op(op: &str) -> Error {
+        Error::RedefinedOp { op: op.to_string() }
+    }
+
     pub fn restricted_key(key_seq: &str) -> Error {
         Error::RestrictedKey {
             key_seq: key_seq.to_string(),
@@ -131,6 +150,13 @@ impl Error {
         }
     }
 
+    pub fn theme(path: &str, e: &de::Error) -> Error {
+        Error::Theme {
+            path: path.to_string(),
+            cause: format!("{e}"),
+        }
+    }
+
     pub fn invalid_regex(pattern: &str, e: &regex_lite::Error) -> Error {
         Error::InvalidRegex {
             pattern: pattern.to_string(),
@@ -143,6 +169,26 @@ impl Error {
             name: name.to_string(),
         }
     }
+
+    pub fn invalid_theme(name: &str) -> Error {
+        Error::InvalidTheme {
+            name: name.to_string(),
+        }
+    }
+
+    pub fn invalid_banner_format(format: &str, cause: &str) -> Error {
+        Error::InvalidBannerFormat {
+            format: format.to_string(),
+            cause: cause.to_string(),
+        }
+    }
+
+    pub fn invalid_include(name: &str, include: &str) -> Error {
+        Error::InvalidInclude {
+            name: name.to_string(),
+            include: include.to_string(),
+        }
+    }
 }
 
 impl Display for Error {
@@ -158,6 +204,9 @@ impl Display for Error {
             }
             Error::InvalidKey { key } => write!(f, "{key}: invalid key"),
             Error::InvalidOp { op } => write!(f, "{op}: invalid operation"),
+            Error::RedefinedOp { op } => {
+                write!(f, "{op}: operation cannot be redefined")
+            }
             Error::RestrictedKey { key_seq } => {
                 write!(f, "{key_seq}: key sequence cannot be rebound")
             }
@@ -167,12 +216,24 @@ impl Display for Error {
             Error::Syntax { path, cause } => {
                 write!(f, "{path}: syntax configuration error: {cause}")
             }
+            Error::Theme { path, cause } => {
+                write!(f, "{path}: theme configuration error: {cause}")
+            }
             Error::InvalidRegex { pattern, cause } => {
                 write!(f, "{pattern}: invalid regular expression: {cause}")
             }
             Error::InvalidColor { name } => {
                 write!(f, "{name}: invalid color")
             }
+            Error::InvalidTheme { name } => {
+                write!(f, "{name}: theme not found")
+            }
+            Error::InvalidBannerFormat { format, cause } => {
+                write!(f, "{format}: invalid banner format: {cause}")
+            }
+            Error::InvalidInclude { name, include } => {
+                write!(f, "{name}: included syntax not found: {include}")
+            }
         }
     }
 }
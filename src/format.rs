@@ -0,0 +1,228 @@
+//! Contains everything related to formatting the window banner.
+//!
+//! A banner format is a template consisting of literal text and placeholders of the
+//! form `{name}`, which are replaced with information about the editor attached to
+//! the window. The special placeholder `{>}` separates the _left_-aligned section of
+//! the banner from the _right_-aligned section, and literal braces are written as
+//! `{{` and `}}`.
+//!
+//! The default banner is equivalent to the following format:
+//!
+//! ```text
+//! {dirty}{path} ({syntax}){>}{line}:{col}
+//! ```
+
+use crate::error::{Error, Result};
+use std::mem;
+
+/// The pieces of information about an editor that can be referenced by placeholders
+/// in a banner format.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Field {
+    /// Path of the file or name of the ephemeral editor.
+    Path,
+
+    /// `*` if the buffer has unsaved changes, otherwise a blank.
+    Dirty,
+
+    /// Name of the syntax.
+    Syntax,
+
+    /// Line number of the cursor, starting at `1`.
+    Line,
+
+    /// Column number of the cursor, starting at `1`.
+    Col,
+
+    /// Position of the cursor relative to the size of the buffer as a percentage.
+    Percent,
+
+    /// Character encoding of the buffer.
+    Encoding,
+
+    /// Either `hard` or `soft` depending on how tabs are inserted.
+    TabMode,
+
+    /// Number of characters in the buffer.
+    Chars,
+
+    /// Buffer position of the cursor, starting at `0`.
+    Pos,
+}
+
+/// A piece of a banner format, which is either literal text or a placeholder.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Piece {
+    Text(String),
+    Field(Field),
+}
+
+/// A parsed banner format with _left_ and _right_ sections.
+#[derive(Clone, Debug)]
+pub struct BannerFormat {
+    left: Vec<Piece>,
+    right: Vec<Piece>,
+}
+
+impl Field {
+    const FIELD_NAMES: [(&'static str, Field); 10] = [
+        ("path", Field::Path),
+        ("dirty", Field::Dirty),
+        ("syntax", Field::Syntax),
+        ("line", Field::Line),
+        ("col", Field::Col),
+        ("percent", Field::Percent),
+        ("encoding", Field::Encoding),
+        ("tab-mode", Field::TabMode),
+        ("chars", Field::Chars),
+        ("pos", Field::Pos),
+    ];
+
+    fn find(name: &str) -> Option<Field> {
+        Self::FIELD_NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, field)| *field)
+    }
+}
+
+impl BannerFormat {
+    /// Placeholder that separates the left and right sections.
+    const SEPARATOR: &'static str = ">";
+
+    /// Parses `format` into its left and right sections, returning an error if the
+    /// format contains unknown placeholders, more than one separator, or unbalanced
+    /// braces.
+    pub fn parse(format: &str) -> Result<BannerFormat> {
+        let error = |cause: &str| Error::invalid_banner_format(format, cause);
+
+        let mut sections = vec![Vec::new()];
+        let mut text = String::new();
+        let mut rest = format;
+        while let Some(i) = rest.find(['{', '}']) {
+            text.push_str(&rest[..i]);
+            let brace = if rest[i..].starts_with('{') { "{" } else { "}" };
+            let after = &rest[i + 1..];
+            if let Some(after) = after.strip_prefix(brace) {
+                // Doubled braces are literal.
+                text.push_str(brace);
+                rest = after;
+            } else if brace == "}" {
+                return Err(error("expecting }}"));
+            } else {
+                let end = after.find('}').ok_or_else(|| error("expecting }"))?;
+                let name = &after[..end];
+                if !text.is_empty() {
+                    sections
+                        .last_mut()
+                        .unwrap()
+                        .push(Piece::Text(mem::take(&mut text)));
+                }
+                if name == Self::SEPARATOR {
+                    if sections.len() > 1 {
+                        return Err(error("only one {>} allowed"));
+                    }
+                    sections.push(Vec::new());
+                } else if let Some(field) = Field::find(name) {
+                    sections.last_mut().unwrap().push(Piece::Field(field));
+                } else {
+                    return Err(error(&format!("{{{name}}}: unknown placeholder")));
+                }
+                rest = &after[end + 1..];
+            }
+        }
+        text.push_str(rest);
+        if !text.is_empty() {
+            sections.last_mut().unwrap().push(Piece::Text(text));
+        }
+
+        let right = if sections.len() > 1 {
+            sections.pop().unwrap()
+        } else {
+            Vec::new()
+        };
+        let left = sections.pop().unwrap();
+        Ok(BannerFormat { left, right })
+    }
+
+    /// Returns the pieces of the left-aligned section.
+    pub fn left(&self) -> &[Piece] {
+        &self.left
+    }
+
+    /// Returns the pieces of the right-aligned section.
+    pub fn right(&self) -> &[Piece] {
+        &self.right
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Piece {
+        Piece::Text(s.to_string())
+    }
+
+    #[test]
+    fn parse_sections() {
+        let format = BannerFormat::parse("{dirty}{path} ({syntax}){>}{line}:{col}").unwrap();
+        assert_eq!(
+            format.left(),
+            &[
+                Piece::Field(Field::Dirty),
+                Piece::Field(Field::Path),
+                text(" ("),
+                Piece::Field(Field::Syntax),
+                text(")"),
+            ]
+        );
+        assert_eq!(
+            format.right(),
+            &[
+                Piece::Field(Field::Line),
+                text(":"),
+                Piece::Field(Field::Col)
+            ]
+        );
+
+        let format = BannerFormat::parse("{path}").unwrap();
+        assert_eq!(format.left(), &[Piece::Field(Field::Path)]);
+        assert!(format.right().is_empty());
+
+        let format = BannerFormat::parse("{>}{tab-mode} {percent}%").unwrap();
+        assert!(format.left().is_empty());
+        assert_eq!(
+            format.right(),
+            &[
+                Piece::Field(Field::TabMode),
+                text(" "),
+                Piece::Field(Field::Percent),
+                text("%"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_escapes() {
+        let format = BannerFormat::parse("{{{pos}}} of {chars}").unwrap();
+        assert_eq!(
+            format.left(),
+            &[
+                text("{"),
+                Piece::Field(Field::Pos),
+                text("} of "),
+                Piece::Field(Field::Chars),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(BannerFormat::parse("{nope}").is_err());
+        assert!(BannerFormat::parse("{path").is_err());
+        assert!(BannerFormat::parse("path}").is_err());
+        assert!(BannerFormat::parse("{path}{>}{line}{>}").is_err());
+        assert!(BannerFormat::parse("").is_ok());
+    }
+}
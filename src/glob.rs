@@ -0,0 +1,156 @@
+//! Translation of glob patterns into regular expressions.
+//!
+//! The following constructs are recognized, where `/` is the path separator:
+//!
+//! * `*` matches any sequence of characters except `/`
+//! * `**` matches any sequence of characters, including `/`, and `**/` also matches
+//!   an empty sequence
+//! * `?` matches any single character except `/`
+//! * `[...]` matches any single character in the set, or any character not in the set
+//!   if the first character is `!`
+//! * `{a,b,...}` matches any one of the comma-separated alternatives
+//! * `\` escapes the character that follows
+//!
+//! All other characters, including unbalanced brackets and braces, match themselves.
+
+use regex_lite::escape;
+
+/// Returns a regular expression equivalent to `glob`, which is not anchored at either
+/// end.
+pub fn to_pattern(glob: &str) -> String {
+    let chars = glob.chars().collect::<Vec<_>>();
+    let mut pattern = String::new();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    pattern.push_str("(?:.*/)?");
+                    i += 2;
+                } else {
+                    pattern.push_str(".*");
+                    i += 1;
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            '[' => {
+                if let Some(end) = find_class_end(&chars, i) {
+                    pattern.push('[');
+                    let mut j = i + 1;
+                    if chars[j] == '!' {
+                        pattern.push('^');
+                        j += 1;
+                    }
+                    for c in &chars[j..end] {
+                        if matches!(c, '\\' | '[' | ']' | '&' | '~') {
+                            pattern.push('\\');
+                        }
+                        pattern.push(*c);
+                    }
+                    pattern.push(']');
+                    i = end;
+                } else {
+                    pattern.push_str(r"\[");
+                }
+            }
+            '{' if is_group(&chars, i) => {
+                pattern.push_str("(?:");
+                depth += 1;
+            }
+            ',' if depth > 0 => pattern.push('|'),
+            '}' if depth > 0 => {
+                pattern.push(')');
+                depth -= 1;
+            }
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                pattern.push_str(&escape(&chars[i].to_string()));
+            }
+            c => pattern.push_str(&escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    pattern
+}
+
+/// Returns the index of `]` that closes the character class opened at `start`, or
+/// `None` if the class is unterminated or empty.
+fn find_class_end(chars: &[char], start: usize) -> Option<usize> {
+    let from = if chars.get(start + 1) == Some(&'!') {
+        start + 2
+    } else {
+        start + 1
+    };
+    chars[from..]
+        .iter()
+        .position(|c| *c == ']')
+        .filter(|n| *n > 0)
+        .map(|n| from + n)
+}
+
+/// Returns `true` if the `{` at `start` has a matching `}`.
+fn is_group(chars: &[char], start: usize) -> bool {
+    let mut depth = 0;
+    let mut escaped = false;
+    for c in &chars[start..] {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return true;
+                }
+            }
+            _ => (),
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex_lite::Regex;
+
+    fn is_match(glob: &str, path: &str) -> bool {
+        let re = Regex::new(&format!("^{}$", to_pattern(glob))).unwrap();
+        re.is_match(path)
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(is_match("*.rs", "main.rs"));
+        assert!(!is_match("*.rs", "src/main.rs"));
+        assert!(is_match("src/*.rs", "src/main.rs"));
+        assert!(is_match("**/*.rs", "main.rs"));
+        assert!(is_match("**/*.rs", "a/b/main.rs"));
+        assert!(is_match("a/**", "a/b/c"));
+        assert!(is_match("?.c", "x.c"));
+        assert!(!is_match("?.c", "xy.c"));
+        assert!(!is_match("?", "/"));
+    }
+
+    #[test]
+    fn classes_and_groups() {
+        assert!(is_match("[Mm]akefile", "makefile"));
+        assert!(is_match("[!a-c].txt", "d.txt"));
+        assert!(!is_match("[!a-c].txt", "b.txt"));
+        assert!(is_match("*.{yml,yaml}", "a.yaml"));
+        assert!(is_match("*.{c,{h,hpp}}", "a.hpp"));
+        assert!(!is_match("*.{yml,yaml}", "a.toml"));
+    }
+
+    #[test]
+    fn literals() {
+        assert!(is_match("a+b.(c)", "a+b.(c)"));
+        assert!(!is_match("a.c", "abc"));
+        assert!(is_match("[x", "[x"));
+        assert!(is_match("{a,b", "{a,b"));
+        assert!(is_match(r"\*.c", "*.c"));
+        assert!(!is_match(r"\*.c", "a.c"));
+    }
+}
@@ -18,6 +18,14 @@ pub struct Cell {
 
     /// The foreground and background color of the character.
     pub color: Color,
+
+    /// Zero-width characters, such as combining marks, that attach to `value`, where
+    /// unused slots contain `\0`.
+    pub marks: [char; Cell::MARKS],
+
+    /// Indicates that the cell is occupied by the trailing half of a wide character in
+    /// the preceding cell, which is never drawn.
+    pub tail: bool,
 }
 
 impl Cell {
@@ -27,10 +35,43 @@ impl Cell {
     /// `0`.
     pub const EMPTY: Cell = Cell::new('\0', Color::ZERO);
 
+    /// Maximum number of zero-width characters that can be attached to a cell.
+    pub const MARKS: usize = 2;
+
     /// Creates a new cell from `value` and `color`.
     #[inline(always)]
     pub const fn new(value: char, color: Color) -> Cell {
-        Cell { value, color }
+        Cell {
+            value,
+            color,
+            marks: ['\0'; Cell::MARKS],
+            tail: false,
+        }
+    }
+
+    /// Creates a new cell occupied by the trailing half of a wide character whose
+    /// color is `color`.
+    #[inline(always)]
+    pub const fn tail(color: Color) -> Cell {
+        Cell {
+            tail: true,
+            ..Cell::new(' ', color)
+        }
+    }
+
+    /// Returns a copy of this cell with the zero-width character `c` attached, though
+    /// `c` is silently dropped if all slots are already in use.
+    pub fn with_mark(self, c: char) -> Cell {
+        let mut cell = self;
+        if let Some(mark) = cell.marks.iter_mut().find(|mark| **mark == '\0') {
+            *mark = c;
+        }
+        cell
+    }
+
+    /// Returns an iterator over the zero-width characters attached to this cell.
+    pub fn marks(&self) -> impl Iterator<Item = char> + '_ {
+        self.marks.iter().copied().take_while(|c| *c != '\0')
     }
 }
 
@@ -64,6 +105,26 @@ impl Grid {
         self.content[(row * self.size.cols + col) as usize] = cell;
     }
 
+    /// Returns the `row`:`col` cell.
+    #[cfg(test)]
+    pub fn get_cell(&self, row: u32, col: u32) -> Cell {
+        self.content[(row * self.size.cols + col) as usize]
+    }
+
+    /// Attaches the zero-width character `c` to the `row`:`col` cell, or to the
+    /// preceding cell if `col` is occupied by the trailing half of a wide character.
+    pub fn add_mark(&mut self, row: u32, col: u32, c: char) {
+        debug_assert!(row < self.size.rows);
+        debug_assert!(col < self.size.cols);
+        let i = (row * self.size.cols + col) as usize;
+        let i = if col > 0 && self.content[i].tail {
+            i - 1
+        } else {
+            i
+        };
+        self.content[i] = self.content[i].with_mark(c);
+    }
+
     /// Fills all cells in `row` in the range [`start_col`, `end_col`) to `cell`.
     pub fn fill_range(&mut self, row: u32, start_col: u32, end_col: u32, cell: Cell) {
         debug_assert!(row < self.size.rows);
@@ -174,6 +235,35 @@ mod tests {
         assert_eq!(this_grid.content, that_grid.content);
     }
 
+    #[test]
+    fn add_marks() {
+        let mut grid = zig_grid();
+        grid.add_mark(0, 1, '\u{301}');
+        assert_eq!(grid.content[1].marks().collect::<Vec<_>>(), vec!['\u{301}']);
+
+        // Marks beyond capacity are dropped.
+        grid.add_mark(0, 1, '\u{302}');
+        grid.add_mark(0, 1, '\u{303}');
+        assert_eq!(
+            grid.content[1].marks().collect::<Vec<_>>(),
+            vec!['\u{301}', '\u{302}']
+        );
+
+        // Mark on trailing half of wide character attaches to preceding cell.
+        grid.set_cell(1, 0, Cell::new('\u{4e2d}', Color::ZERO));
+        grid.set_cell(1, 1, Cell::tail(Color::ZERO));
+        grid.add_mark(1, 1, '\u{301}');
+        assert_eq!(grid.content[3].marks().collect::<Vec<_>>(), vec!['\u{301}']);
+        assert_eq!(grid.content[4].marks().count(), 0);
+
+        // Mark on empty cell is not mistaken for trailing half of wide character.
+        let mut grid = empty_grid();
+        grid.set_cell(2, 0, Cell::new('a', Color::ZERO));
+        grid.add_mark(2, 1, '\u{301}');
+        assert_eq!(grid.content[6].marks().count(), 0);
+        assert_eq!(grid.content[7].marks().collect::<Vec<_>>(), vec!['\u{301}']);
+    }
+
     #[test]
     fn clear_grid() {
         // Verify that grid is something other than empty.
@@ -5,11 +5,12 @@
 #![allow(unused_must_use)]
 
 use crate::buffer::Buffer;
+use crate::color::{Attrs, ColorCode, Shade};
 use crate::config::{ConfigurationRef, Theme};
 use crate::editor::{Editor, EditorRef};
 use crate::etc;
 use crate::key::{self, Key, KEY_MAPPINGS};
-use crate::op::OP_MAPPINGS;
+use crate::op::{CommandMap, OP_MAPPINGS};
 use crate::source::Source;
 use indexmap::IndexMap;
 use std::collections::{BTreeMap, HashMap};
@@ -84,14 +85,16 @@ fn prepare_keys() -> Vec<String> {
 }
 
 /// Returns an ephemeral editor, named `@operations`, containing a list of available
-/// editing operations.
+/// editing operations, including user-defined commands.
 pub fn ops_editor(config: ConfigurationRef) -> EditorRef {
-    Editor::readonly(config, Source::as_ephemeral(OPS_EDITOR_NAME), ops_buffer()).to_ref()
+    let buffer = ops_buffer(config.bindings.commands());
+    Editor::readonly(config, Source::as_ephemeral(OPS_EDITOR_NAME), buffer).to_ref()
 }
 
-/// Returns a formatted list of available editing operations.
-pub fn ops_content() -> String {
-    let ops = prepare_ops();
+/// Returns a formatted list of available editing operations, including the
+/// user-defined `commands`.
+pub fn ops_content(commands: &CommandMap) -> String {
+    let ops = prepare_ops(commands);
     let mut out = String::new();
     for op in ops {
         writeln!(out, "{op}");
@@ -99,10 +102,10 @@ pub fn ops_content() -> String {
     out
 }
 
-fn ops_buffer() -> Buffer {
+fn ops_buffer(commands: &CommandMap) -> Buffer {
     const HEADER: &str = "[Operations]";
 
-    let ops = prepare_ops();
+    let ops = prepare_ops(commands);
     let mut buf = Buffer::new();
     writeln!(buf, "{HEADER}");
     for op in ops {
@@ -112,10 +115,11 @@ fn ops_buffer() -> Buffer {
     buf
 }
 
-fn prepare_ops() -> Vec<String> {
+fn prepare_ops(commands: &CommandMap) -> Vec<String> {
     let mut ops = OP_MAPPINGS
         .iter()
         .map(|(op, _)| op.to_string())
+        .chain(commands.keys().cloned())
         .collect::<Vec<_>>();
     ops.sort();
     ops
@@ -179,16 +183,16 @@ pub fn colors_editor(config: ConfigurationRef) -> EditorRef {
 }
 
 /// Returns a TOML-formatted list of color names and values.
-pub fn colors_content(colors: &HashMap<String, u8>) -> String {
+pub fn colors_content(colors: &HashMap<String, ColorCode>) -> String {
     let colors = prepare_colors(colors);
     let mut out = String::new();
     for (name, color) in colors {
-        writeln!(out, "{name} = {color}");
+        writeln!(out, "{name} = {}", toml_color(color));
     }
     out
 }
 
-fn colors_buffer(colors: &HashMap<String, u8>) -> Buffer {
+fn colors_buffer(colors: &HashMap<String, ColorCode>) -> Buffer {
     const HEADER_NAME: &str = "[Name]";
     const HEADER_VALUE: &str = "[Value]";
 
@@ -212,7 +216,7 @@ fn colors_buffer(colors: &HashMap<String, u8>) -> Buffer {
     buf
 }
 
-fn prepare_colors(colors: &HashMap<String, u8>) -> IndexMap<String, u8> {
+fn prepare_colors(colors: &HashMap<String, ColorCode>) -> IndexMap<String, ColorCode> {
     // Sort by color value rather than color name.
     let mut colors = colors.iter().collect::<Vec<_>>();
     colors.sort_by(|a, b| a.1.cmp(b.1));
@@ -227,12 +231,15 @@ fn prepare_colors(colors: &HashMap<String, u8>) -> IndexMap<String, u8> {
 
 /// Returns a TOML-formatted list of theme color names and values.
 pub fn theme_content(theme: &Theme) -> String {
-    const COLORS: [(&str, fn(&Theme) -> u8); 13] = [
+    const COLORS: [(&str, fn(&Theme) -> Shade); 16] = [
         ("text-fg", |t| t.text_fg),
         ("text-bg", |t| t.text_bg),
         ("select-bg", |t| t.select_bg),
         ("spotlight-bg", |t| t.spotlight_bg),
+        ("bracket-bg", |t| t.bracket_bg),
         ("whitespace-fg", |t| t.whitespace_fg),
+        ("trailing-bg", |t| t.trailing_bg),
+        ("ruler-bg", |t| t.ruler_bg),
         ("accent-fg", |t| t.accent_fg),
         ("echo-fg", |t| t.echo_fg),
         ("prompt-fg", |t| t.prompt_fg),
@@ -245,7 +252,31 @@ pub fn theme_content(theme: &Theme) -> String {
 
     let mut out = String::new();
     for (name, t_fn) in COLORS {
-        writeln!(out, "{name} = {}", t_fn(theme));
+        let shade = t_fn(theme);
+        if shade.attrs.is_empty() {
+            writeln!(out, "{name} = {}", toml_color(shade.color));
+        } else {
+            // Style tables use the key matching the kind of color for this entry.
+            let key = if name.ends_with("-fg") { "fg" } else { "bg" };
+            let attrs = Attrs::NAMES
+                .iter()
+                .filter(|(_, attr)| shade.attrs.contains(*attr))
+                .map(|(attr_name, _)| format!(", {attr_name} = true"))
+                .collect::<String>();
+            writeln!(
+                out,
+                "{name} = {{ {key} = {}{attrs} }}",
+                toml_color(shade.color)
+            );
+        }
     }
     out
 }
+
+/// Returns `color` as a TOML value, where RGB values must be quoted as strings.
+fn toml_color(color: ColorCode) -> String {
+    match color {
+        ColorCode::Index(_) => color.to_string(),
+        ColorCode::Rgb(..) => format!("\"{color}\""),
+    }
+}
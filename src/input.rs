@@ -88,16 +88,11 @@ impl InputEditor {
     const MIN_COLS: u32 = 2;
 
     pub fn new(workspace: WorkspaceRef) -> InputEditor {
-        let config = workspace.borrow().config().clone();
-        let prompt_color = Color::new(config.theme.prompt_fg, config.theme.text_bg);
-        let input_color = Color::new(config.theme.text_fg, config.theme.text_bg);
-        let hint_color = Color::new(config.theme.echo_fg, config.theme.text_bg);
-
         InputEditor {
             workspace,
-            prompt_color,
-            input_color,
-            hint_color,
+            prompt_color: Color::ZERO,
+            input_color: Color::ZERO,
+            hint_color: Color::ZERO,
             prompt: None,
             completer: user::null_completer(),
             prompt_cols: 0,
@@ -115,6 +110,7 @@ impl InputEditor {
     pub fn enable(&mut self, prompt: &str, completer: Box<dyn Completer>) {
         self.prompt = Some(prompt.to_string());
         self.completer = completer;
+        self.set_colors();
         self.set_sizes();
         self.set_input(None);
         let hint = self.completer.prepare();
@@ -126,6 +122,7 @@ impl InputEditor {
     pub fn disable(&mut self) {
         self.prompt = None;
         self.completer = user::null_completer();
+        self.set_colors();
         self.set_sizes();
         self.set_input(None);
         self.hint = None;
@@ -335,6 +332,14 @@ impl InputEditor {
         cmp::min(cursor, self.input_cols.saturating_sub(1))
     }
 
+    /// Derives colors from the current theme, which may change at runtime.
+    fn set_colors(&mut self) {
+        let theme = self.workspace.borrow().config().theme();
+        self.prompt_color = Color::from_shades(theme.prompt_fg, theme.text_bg);
+        self.input_color = Color::from_shades(theme.text_fg, theme.text_bg);
+        self.hint_color = Color::from_shades(theme.echo_fg, theme.text_bg);
+    }
+
     /// Sets column sizes for the _prompt_ and _input_ areas, and allocates an
     /// appropriately-sized canvas.
     fn set_sizes(&mut self) {
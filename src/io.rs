@@ -4,13 +4,36 @@ use crate::buffer::Buffer;
 use crate::error::{Error, Result};
 use crate::sys::AsString;
 use std::fs::{self, File};
-use std::io::{self, BufReader, BufWriter};
+use std::io::{self, BufReader, BufWriter, Write};
 use std::path::Path;
 use std::time::SystemTime;
 
 /// Suggested capacity of internal buffers for readers and writers.
 const BUFFER_SIZE: usize = 65_536;
 
+/// Byte order mark, which is decoded as an ordinary character when reading a file.
+const BOM: char = '\u{feff}';
+
+/// Line endings that may replace line breaks when writing a file.
+#[derive(Copy, Clone, PartialEq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    Cr,
+}
+
+/// Conventions applied when writing a file, where each value that is `None` leaves
+/// the contents of the buffer unchanged.
+#[derive(Copy, Clone, Default)]
+pub struct Format {
+    /// Line ending that replaces every `\n`, `\r\n` and `\r` line break.
+    pub eol: Option<LineEnding>,
+
+    /// Indicates whether a byte order mark is added to the start of the file if
+    /// `true`, or removed if `false`.
+    pub bom: Option<bool>,
+}
+
 /// Opens the file at `path` and reads the contents into `buf`, returning the
 /// number of bytes read.
 pub fn read_file<P: AsRef<Path>>(path: P, buf: &mut Buffer) -> Result<usize> {
@@ -20,13 +43,18 @@ pub fn read_file<P: AsRef<Path>>(path: P, buf: &mut Buffer) -> Result<usize> {
     buf.read(&mut reader).map_err(|e| to_error(e, path))
 }
 
-/// Creates a new file at `path` and writes the contents of `buf`, returning the
-/// number of bytes written.
-pub fn write_file<P: AsRef<Path>>(path: P, buf: &Buffer) -> Result<usize> {
+/// Creates a new file at `path` and writes the contents of `buf` according to
+/// `format`, returning the number of bytes written.
+pub fn write_file<P: AsRef<Path>>(path: P, buf: &Buffer, format: &Format) -> Result<usize> {
     let path = path.as_ref();
     let file = create_file(path)?;
     let mut writer = BufWriter::with_capacity(BUFFER_SIZE, file);
-    buf.write(&mut writer).map_err(|e| to_error(e, path))
+    if format.eol.is_none() && format.bom.is_none() {
+        buf.write(&mut writer)
+    } else {
+        write_format(buf, &mut writer, format)
+    }
+    .map_err(|e| to_error(e, path))
 }
 
 /// Opens the file at `path` for reading.
@@ -47,7 +75,85 @@ pub fn get_time<P: AsRef<Path>>(path: P) -> Result<SystemTime> {
         .and_then(|info| info.modified().map_err(|e| to_error(e, path)))
 }
 
+/// Writes all characters in `buf` to `writer` after applying the conventions in
+/// `format`, returning the total number of UTF-8 encoded bytes written.
+fn write_format<W: Write>(buf: &Buffer, writer: &mut W, format: &Format) -> io::Result<usize> {
+    let mut chunk = String::with_capacity(BUFFER_SIZE);
+    let mut count = 0;
+    let mut chars = buf.forward(0).peekable();
+
+    match format.bom {
+        Some(true) if chars.peek() != Some(&BOM) => chunk.push(BOM),
+        Some(false) if chars.peek() == Some(&BOM) => {
+            chars.next();
+        }
+        _ => (),
+    }
+
+    while let Some(c) = chars.next() {
+        match (c, format.eol) {
+            ('\r' | '\n', Some(eol)) => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                chunk.push_str(match eol {
+                    LineEnding::Lf => "\n",
+                    LineEnding::Crlf => "\r\n",
+                    LineEnding::Cr => "\r",
+                });
+            }
+            _ => chunk.push(c),
+        }
+        if chunk.len() >= BUFFER_SIZE {
+            writer.write_all(chunk.as_bytes())?;
+            count += chunk.len();
+            chunk.clear();
+        }
+    }
+    writer.write_all(chunk.as_bytes())?;
+    Ok(count + chunk.len())
+}
+
 /// Converts an I/O error into its corresponding `Error` adorned with `path`.
 fn to_error<P: AsRef<Path>>(e: io::Error, path: P) -> Error {
     Error::io(&path.as_ref().as_string(), e)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(text: &str, eol: Option<LineEnding>, bom: Option<bool>) -> String {
+        let mut buf = Buffer::new();
+        buf.insert_str(text);
+        let mut out = Vec::new();
+        let n = write_format(&buf, &mut out, &Format { eol, bom }).unwrap();
+        assert_eq!(n, out.len());
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn write_line_endings() {
+        const TEXT: &str = "a\nb\r\nc\rd\r\r\n";
+        assert_eq!(write(TEXT, Some(LineEnding::Lf), None), "a\nb\nc\nd\n\n");
+        assert_eq!(
+            write(TEXT, Some(LineEnding::Crlf), None),
+            "a\r\nb\r\nc\r\nd\r\n\r\n"
+        );
+        assert_eq!(write(TEXT, Some(LineEnding::Cr), None), "a\rb\rc\rd\r\r");
+        assert_eq!(write(TEXT, None, None), TEXT);
+    }
+
+    #[test]
+    fn write_bom() {
+        assert_eq!(write("abc", None, Some(true)), "\u{feff}abc");
+        assert_eq!(write("\u{feff}abc", None, Some(true)), "\u{feff}abc");
+        assert_eq!(write("\u{feff}abc", None, Some(false)), "abc");
+        assert_eq!(write("abc", None, Some(false)), "abc");
+        assert_eq!(write("", None, Some(true)), "\u{feff}");
+        assert_eq!(
+            write("\u{feff}\r\n", Some(LineEnding::Lf), None),
+            "\u{feff}\n"
+        );
+    }
+}
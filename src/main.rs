@@ -22,9 +22,12 @@ mod config;
 mod control;
 mod echo;
 mod editor;
+mod editorconfig;
 mod env;
 mod error;
 mod etc;
+mod format;
+mod glob;
 mod grid;
 mod help;
 mod input;
@@ -40,11 +43,12 @@ mod sys;
 mod term;
 mod token;
 mod user;
+mod width;
 mod window;
 mod workspace;
 mod writer;
 
-use crate::config::Configuration;
+use crate::config::{Configuration, Themes};
 use crate::control::Controller;
 use crate::error::Result;
 use crate::key::Keyboard;
@@ -90,9 +94,6 @@ fn run() -> Result<()> {
     } else if opts.keys {
         print!("{}", help::keys_content());
         Ok(())
-    } else if opts.ops {
-        print!("{}", help::ops_content());
-        Ok(())
     } else {
         run_opts(&opts)
     }
@@ -111,23 +112,36 @@ fn run_opts(opts: &Options) -> Result<()> {
     };
     config.apply_opts(opts);
 
-    // Load optional syntax configurations via registry and update configuration.
+    // Load optional syntax configurations via registry and update configuration,
+    // noting that built-in syntax configurations are always available.
     config.registry = if opts.bare || opts.bare_syntax {
-        Registry::default()
+        Registry::builtin(&config.colors)?
     } else if let Some(ref syntax_dir) = opts.syntax_dir {
         Registry::load_dir(syntax_dir, &config.colors)?
     } else {
         Registry::load(&config.colors)?
     };
 
-    if opts.bindings {
+    // Load optional theme files via registry, which also selects the theme named in
+    // the configuration, if any.
+    let themes = if opts.bare {
+        Themes::default()
+    } else {
+        Themes::load(&config.colors)?
+    };
+    config.apply_themes(themes)?;
+
+    if opts.ops {
+        print!("{}", help::ops_content(config.bindings.commands()));
+        Ok(())
+    } else if opts.bindings {
         print!("{}", help::bindings_content(config.bindings.bindings()));
         Ok(())
     } else if opts.colors {
         print!("{}", help::colors_content(config.colors.colors()));
         Ok(())
     } else if opts.theme {
-        print!("{}", help::theme_content(&config.theme));
+        print!("{}", help::theme_content(&config.theme()));
         Ok(())
     } else {
         run_config(opts, config)
@@ -159,7 +173,8 @@ fn prepare_term() -> Result<()> {
 
 fn restore_term() -> Result<()> {
     print!(
-        "{}{}{}",
+        "{}{}{}{}",
+        ansi::reset_color(),
         ansi::clear_screen(),
         ansi::track_mouse(false),
         ansi::alt_screen(false)
@@ -10,19 +10,21 @@
 //! at runtime.
 
 use crate::buffer::Buffer;
-use crate::config::ConfigurationRef;
+use crate::config::{ConfigurationRef, EditorSettings, Whitespace};
 use crate::editor::{Align, Capture, Editor, EditorRef, ImmutableEditor};
-use crate::env::{Environment, Focus};
+use crate::env::{Environment, Focus, Maximize};
 use crate::error::{Error, Result};
 use crate::help;
-use crate::io;
+use crate::io::{self, Format};
 use crate::key::{Key, TAB};
 use crate::search::{self, Pattern};
 use crate::size::{Point, Size};
 use crate::source::Source;
+use crate::syntax::Syntax;
 use crate::sys::{self, AsString};
 use crate::user::{self, Completer, Inquirer};
 use crate::workspace::Placement;
+use indexmap::IndexMap;
 use regex_lite::RegexBuilder;
 use std::collections::HashMap;
 use std::io::ErrorKind;
@@ -44,6 +46,20 @@ pub enum Action {
 /// Map of editing operations to editing functions.
 pub type OpMap = HashMap<&'static str, OpFn>;
 
+/// A single step in a user-defined command.
+#[derive(Clone)]
+pub enum Step {
+    /// A predefined editing operation.
+    Op(OpFn),
+
+    /// Literal text inserted at the cursor.
+    Text(String),
+}
+
+/// Map of user-defined command names to sequences of steps, which preserves the order
+/// in which commands were defined.
+pub type CommandMap = IndexMap<String, Vec<Step>>;
+
 impl Action {
     fn as_quit() -> Option<Action> {
         Some(Action::Quit)
@@ -596,6 +612,18 @@ fn set_mark(env: &mut Environment) -> Option<Action> {
     None
 }
 
+/// Operation: `match-bracket`
+fn match_bracket(env: &mut Environment) -> Option<Action> {
+    let mut editor = env.get_active_editor().borrow_mut();
+    editor.clear_soft_mark();
+    if editor.move_bracket() {
+        editor.render();
+        None
+    } else {
+        Action::as_echo("no matching bracket")
+    }
+}
+
 /// Operation: `goto-line`
 fn goto_line(env: &mut Environment) -> Option<Action> {
     GotoLine::question(env.get_active_editor().clone())
@@ -661,6 +689,29 @@ impl Inquirer for GotoLine {
     }
 }
 
+/// Carries out the sequence of `steps` that form a user-defined command, returning
+/// the action of the last step.
+///
+/// Steps that return [`Action::Quit`] or [`Action::Question`] terminate the command
+/// and the remaining steps are discarded, since neither action can be resumed. An
+/// [`Action::Echo`] does not interrupt the command, though only the last such echo is
+/// returned.
+pub fn run_command(env: &mut Environment, steps: &[Step]) -> Option<Action> {
+    let mut last_action = None;
+    for step in steps {
+        let action = match step {
+            Step::Op(op_fn) => op_fn(env),
+            Step::Text(text) => insert_text(env, text),
+        };
+        match action {
+            Some(Action::Echo(text)) => last_action = Some(Action::Echo(text)),
+            None => (),
+            action => return action,
+        }
+    }
+    last_action
+}
+
 pub fn insert_char(env: &mut Environment, c: char) -> Option<Action> {
     let mut editor = env.get_active_editor().borrow_mut();
     if let Some(editor) = editor.modify() {
@@ -673,12 +724,25 @@ pub fn insert_char(env: &mut Environment, c: char) -> Option<Action> {
     }
 }
 
+/// Inserts `text` at the cursor of the active editor.
+fn insert_text(env: &mut Environment, text: &str) -> Option<Action> {
+    let mut editor = env.get_active_editor().borrow_mut();
+    if let Some(editor) = editor.modify() {
+        editor.clear_mark();
+        editor.insert_str(text);
+        editor.render();
+        None
+    } else {
+        Action::echo_readonly()
+    }
+}
+
 /// Operation: `insert-line`
 fn insert_line(env: &mut Environment) -> Option<Action> {
     let mut editor = env.get_active_editor().borrow_mut();
     if let Some(editor) = editor.modify() {
         editor.clear_mark();
-        editor.insert_char('\n');
+        editor.insert_line();
         editor.render();
         None
     } else {
@@ -699,6 +763,75 @@ fn insert_tab(env: &mut Environment) -> Option<Action> {
     }
 }
 
+/// Operation: `indent-region`
+fn indent_region(env: &mut Environment) -> Option<Action> {
+    let mut editor = env.get_active_editor().borrow_mut();
+    if let Some(editor) = editor.modify() {
+        editor.indent_region();
+        editor.render();
+        None
+    } else {
+        Action::echo_readonly()
+    }
+}
+
+/// Operation: `outdent-region`
+fn outdent_region(env: &mut Environment) -> Option<Action> {
+    let mut editor = env.get_active_editor().borrow_mut();
+    if let Some(editor) = editor.modify() {
+        editor.outdent_region();
+        editor.render();
+        None
+    } else {
+        Action::echo_readonly()
+    }
+}
+
+/// Operation: `toggle-comment`
+fn toggle_comment(env: &mut Environment) -> Option<Action> {
+    let mut editor = env.get_active_editor().borrow_mut();
+    if let Some(editor) = editor.modify() {
+        if editor.toggle_comment() {
+            editor.render();
+            None
+        } else {
+            Action::as_echo("comments not defined for syntax")
+        }
+    } else {
+        Action::echo_readonly()
+    }
+}
+
+/// Operation: `trim-whitespace`
+fn trim_whitespace(env: &mut Environment) -> Option<Action> {
+    let mut editor = env.get_active_editor().borrow_mut();
+    if let Some(editor) = editor.modify() {
+        if editor.tidy(true, false) {
+            editor.render();
+            None
+        } else {
+            Action::as_echo("no trailing whitespace")
+        }
+    } else {
+        Action::echo_readonly()
+    }
+}
+
+/// Operation: `final-newline`
+fn final_newline(env: &mut Environment) -> Option<Action> {
+    let mut editor = env.get_active_editor().borrow_mut();
+    if let Some(editor) = editor.modify() {
+        if editor.tidy(false, true) {
+            editor.render();
+            None
+        } else {
+            Action::as_echo("final newline not needed")
+        }
+    } else {
+        Action::echo_readonly()
+    }
+}
+
 /// Operation: `remove-before`
 fn remove_before(env: &mut Environment) -> Option<Action> {
     let text = {
@@ -1042,6 +1175,22 @@ fn open_file_below(env: &mut Environment) -> Option<Action> {
     )
 }
 
+/// Operation: `open-file-left`
+fn open_file_left(env: &mut Environment) -> Option<Action> {
+    Open::question(
+        derive_dir(env),
+        Some(Placement::Left(env.get_active_view_id())),
+    )
+}
+
+/// Operation: `open-file-right`
+fn open_file_right(env: &mut Environment) -> Option<Action> {
+    Open::question(
+        derive_dir(env),
+        Some(Placement::Right(env.get_active_view_id())),
+    )
+}
+
 /// An inquirer that orchestrates the process of opening a file.
 struct Open {
     /// Base directory used for joining paths entered by the user, which is typically
@@ -1153,7 +1302,9 @@ impl Save {
     }
 
     fn save_ephemeral(editor: &EditorRef, env: &mut Environment, path: &str) -> Option<Action> {
-        let timestamp = write_editor(editor, path);
+        let settings = editor.borrow().settings_for(path);
+        tidy_editor(editor, &settings);
+        let timestamp = write_editor(editor, path, &settings.format);
         match timestamp {
             Ok(timestamp) => {
                 let cloned_editor = editor
@@ -1290,8 +1441,9 @@ impl Inquirer for SaveOverride {
 /// Operation: `kill-window`
 fn kill_window(env: &mut Environment) -> Option<Action> {
     if env.view_map().len() > 1 {
+        // Unsaved changes are not at risk if buffer is still visible in another window.
         let editor = env.get_active_editor();
-        if is_dirty_file(editor) {
+        if is_dirty_file(editor) && !editor.borrow().is_shared() {
             Kill::question(editor.clone(), None)
         } else {
             env.kill_window();
@@ -1467,6 +1619,60 @@ fn close_other_windows(env: &mut Environment) -> Option<Action> {
     None
 }
 
+/// Operation: `split-window-below`
+fn split_window_below(env: &mut Environment) -> Option<Action> {
+    split_window(env, Placement::Below(env.get_active_view_id()))
+}
+
+/// Operation: `split-window-right`
+fn split_window_right(env: &mut Environment) -> Option<Action> {
+    split_window(env, Placement::Right(env.get_active_view_id()))
+}
+
+/// Opens a new window whose placement is specified by `place`, attaching an editor
+/// that shares the buffer of the active editor.
+fn split_window(env: &mut Environment, place: Placement) -> Option<Action> {
+    let editor = env.get_active_editor().borrow().share().to_ref();
+    if let Some((view_id, _)) = env.open_editor(editor, place, Align::Auto) {
+        env.set_active(Focus::To(view_id));
+        None
+    } else {
+        Action::echo_no_window()
+    }
+}
+
+/// Operation: `grow-window`
+fn grow_window(env: &mut Environment) -> Option<Action> {
+    if env.resize_window(1) {
+        None
+    } else {
+        Action::as_echo("unable to grow window")
+    }
+}
+
+/// Operation: `shrink-window`
+fn shrink_window(env: &mut Environment) -> Option<Action> {
+    if env.resize_window(-1) {
+        None
+    } else {
+        Action::as_echo("unable to shrink window")
+    }
+}
+
+/// Operation: `balance-windows`
+fn balance_windows(env: &mut Environment) -> Option<Action> {
+    env.balance_windows();
+    None
+}
+
+/// Operation: `maximize-window`
+fn maximize_window(env: &mut Environment) -> Option<Action> {
+    match env.toggle_maximize() {
+        Maximize::Maximized | Maximize::Restored => None,
+        Maximize::Unchanged => Action::as_echo("cannot maximize only window"),
+    }
+}
+
 /// Operation: `top-window`
 fn top_window(env: &mut Environment) -> Option<Action> {
     env.set_active(Focus::Top);
@@ -1481,13 +1687,37 @@ fn bottom_window(env: &mut Environment) -> Option<Action> {
 
 /// Operation: `prev-window`
 fn prev_window(env: &mut Environment) -> Option<Action> {
-    env.set_active(Focus::Above);
+    env.set_active(Focus::Prev);
     None
 }
 
 /// Operation: `next-window`
 fn next_window(env: &mut Environment) -> Option<Action> {
-    env.set_active(Focus::Below);
+    env.set_active(Focus::Next);
+    None
+}
+
+/// Operation: `up-window`
+fn up_window(env: &mut Environment) -> Option<Action> {
+    env.set_active(Focus::Up);
+    None
+}
+
+/// Operation: `down-window`
+fn down_window(env: &mut Environment) -> Option<Action> {
+    env.set_active(Focus::Down);
+    None
+}
+
+/// Operation: `left-window`
+fn left_window(env: &mut Environment) -> Option<Action> {
+    env.set_active(Focus::Left);
+    None
+}
+
+/// Operation: `right-window`
+fn right_window(env: &mut Environment) -> Option<Action> {
+    env.set_active(Focus::Right);
     None
 }
 
@@ -1632,13 +1862,23 @@ fn describe_editor(env: &mut Environment) -> Option<Action> {
     } else {
         ("EOF".to_string(), "".to_string())
     };
-    let text = format!(
-        "characters: {} | lines: {} | cursor: {}{}",
+    let settings = editor.settings();
+    let on_off = |b: bool| if b { "on" } else { "off" };
+    let mut text = format!(
+        "characters: {} | lines: {} | cursor: {}{} | tab: {} {} | wrap: {} | eol: {} | whitespace: {}",
         buffer.size(),
         buffer.line_of(usize::MAX) + 1,
         c_char,
         c_code,
+        if settings.tab_hard { "hard" } else { "soft" },
+        settings.tab_size,
+        on_off(settings.wrap),
+        on_off(settings.eol),
+        settings.whitespace.as_str(),
     );
+    if !settings.overrides.is_empty() {
+        text.push_str(&format!(" | overrides: {}", settings.overrides.join(", ")));
+    }
     Action::as_echo(&text)
 }
 
@@ -1654,6 +1894,147 @@ fn tab_mode(env: &mut Environment) -> Option<Action> {
     }
 }
 
+/// Operation: `wrap-mode`
+fn wrap_mode(env: &mut Environment) -> Option<Action> {
+    let mut editor = env.get_active_editor().borrow_mut();
+    let wrap = editor.get_wrap();
+    editor.set_wrap(!wrap);
+    editor.render();
+    if wrap {
+        Action::as_echo("line wrapping disabled")
+    } else {
+        Action::as_echo("line wrapping enabled")
+    }
+}
+
+/// Operation: `whitespace-mode`
+fn whitespace_mode(env: &mut Environment) -> Option<Action> {
+    let mut editor = env.get_active_editor().borrow_mut();
+    let whitespace = editor.get_whitespace().next();
+    editor.set_whitespace(whitespace);
+    editor.render();
+    match whitespace {
+        Whitespace::None => Action::as_echo("whitespace hidden"),
+        Whitespace::Trailing => Action::as_echo("trailing whitespace visible"),
+        Whitespace::All => Action::as_echo("all whitespace visible"),
+    }
+}
+
+/// Operation: `select-theme`
+fn select_theme(env: &mut Environment) -> Option<Action> {
+    let names = env.workspace().config().themes.names().cloned().collect();
+    SelectTheme::question(names)
+}
+
+/// An inquirer that orchestrates the selection of a theme by name, redrawing the
+/// entire workspace using the colors of the selected theme.
+struct SelectTheme {
+    /// Names of themes available for selection.
+    names: Vec<String>,
+}
+
+impl SelectTheme {
+    const PROMPT: &str = "select theme:";
+
+    fn question(names: Vec<String>) -> Option<Action> {
+        Action::as_question(SelectTheme { names }.to_box())
+    }
+
+    fn to_box(self) -> Box<dyn Inquirer> {
+        Box::new(self)
+    }
+}
+
+impl Inquirer for SelectTheme {
+    fn prompt(&self) -> String {
+        Self::PROMPT.to_string()
+    }
+
+    fn completer(&self) -> Box<dyn Completer> {
+        user::list_completer(self.names.clone())
+    }
+
+    fn respond(&mut self, env: &mut Environment, value: Option<&str>) -> Option<Action> {
+        if let Some(value) = value {
+            let config = env.workspace().config().clone();
+            if let Some(theme) = config.themes.find(value) {
+                config.set_theme(theme);
+                env.refresh();
+                None
+            } else {
+                Action::as_echo(&format!("{value}: theme not found"))
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Operation: `set-syntax`
+fn set_syntax(env: &mut Environment) -> Option<Action> {
+    let mut names = env
+        .workspace()
+        .config()
+        .registry
+        .names()
+        .cloned()
+        .collect::<Vec<_>>();
+    if !names.iter().any(|name| name == Syntax::DEFAULT_NAME) {
+        names.push(Syntax::DEFAULT_NAME.to_string());
+    }
+    SetSyntax::question(names)
+}
+
+/// An inquirer that orchestrates the selection of a syntax by name, tokenizing the
+/// active editor using the selected syntax.
+struct SetSyntax {
+    /// Names of syntaxes available for selection.
+    names: Vec<String>,
+}
+
+impl SetSyntax {
+    const PROMPT: &str = "set syntax:";
+
+    fn question(names: Vec<String>) -> Option<Action> {
+        Action::as_question(SetSyntax { names }.to_box())
+    }
+
+    fn to_box(self) -> Box<dyn Inquirer> {
+        Box::new(self)
+    }
+}
+
+impl Inquirer for SetSyntax {
+    fn prompt(&self) -> String {
+        Self::PROMPT.to_string()
+    }
+
+    fn completer(&self) -> Box<dyn Completer> {
+        user::list_completer(self.names.clone())
+    }
+
+    fn respond(&mut self, env: &mut Environment, value: Option<&str>) -> Option<Action> {
+        if let Some(value) = value {
+            let config = env.workspace().config().clone();
+            let syntax = config.registry.get(value).cloned().or_else(|| {
+                value
+                    .eq_ignore_ascii_case(Syntax::DEFAULT_NAME)
+                    .then(Syntax::default)
+            });
+            if let Some(syntax) = syntax {
+                let mut editor = env.get_active_editor().borrow_mut();
+                editor.set_syntax(syntax);
+                editor.render();
+                None
+            } else {
+                Action::as_echo(&format!("{value}: syntax not found"))
+            }
+        } else {
+            None
+        }
+    }
+}
+
 /// Scrolls the display down for the editor associated with `p`, which represents a
 /// point whose origin is the top-left position of the terminal display.
 pub fn track_up(env: &mut Environment, p: Point, select: bool) {
@@ -1793,13 +2174,32 @@ fn save_editor_as(editor: &EditorRef, path: Option<&str>) -> Result<()> {
     let path = path
         .map(|path| path.to_string())
         .unwrap_or_else(|| path_of(editor).as_string());
-    write_editor(editor, &path).map(|time| update_editor(editor, &path, time))
+    // Settings are resolved for the target path since it may differ from the path
+    // of the editor.
+    let settings = editor.borrow().settings_for(&path);
+    tidy_editor(editor, &settings);
+    write_editor(editor, &path, &settings.format).map(|time| update_editor(editor, &path, time))
+}
+
+/// Applies the transforms in `settings` that precede saving `editor`, such as removing
+/// trailing whitespace.
+fn tidy_editor(editor: &EditorRef, settings: &EditorSettings) {
+    let (trim, final_newline) = (settings.trim_trailing_whitespace, settings.final_newline);
+    if trim || final_newline {
+        let mut editor = editor.borrow_mut();
+        if let Some(editor) = editor.modify() {
+            if editor.tidy(trim, final_newline) {
+                editor.render();
+            }
+        }
+    }
 }
 
-/// Writes the buffer of `editor` to `path` and returns the resulting file modification
-/// time.
-fn write_editor(editor: &EditorRef, path: &str) -> Result<SystemTime> {
-    let _ = io::write_file(path, &editor.borrow().buffer())?;
+/// Writes the buffer of `editor` to `path` according to `format`, and returns the
+/// resulting file modification time.
+fn write_editor(editor: &EditorRef, path: &str, format: &Format) -> Result<SystemTime> {
+    let editor = editor.borrow();
+    let _ = io::write_file(path, &editor.buffer(), format)?;
     io::get_time(path)
 }
 
@@ -1828,12 +2228,21 @@ fn stale_editor(editor: &EditorRef) -> Result<bool> {
 }
 
 /// Returns an ordered collection of _dirty_ editors.
+///
+/// Editors that share a buffer are represented only once in the collection.
 fn dirty_editors(env: &Environment) -> Vec<EditorRef> {
-    env.editor_map()
-        .iter()
-        .filter(|(_, e)| is_dirty_file(e))
-        .map(|(_, e)| e.clone())
-        .collect()
+    env.editor_map().values().filter(|e| is_dirty_file(e)).fold(
+        Vec::new(),
+        |mut editors: Vec<EditorRef>, e| {
+            if !editors
+                .iter()
+                .any(|other| other.borrow().is_shared_with(&e.borrow()))
+            {
+                editors.push(e.clone());
+            }
+            editors
+        },
+    )
 }
 
 /// Returns an ordered collection of editor ids and editors for those editors that are
@@ -1941,7 +2350,7 @@ fn base_dir(editor: &EditorRef) -> PathBuf {
 }
 
 /// Predefined mapping of editing operations to editing functions.
-pub const OP_MAPPINGS: [(&'static str, OpFn); 76] = [
+pub const OP_MAPPINGS: [(&'static str, OpFn); 98] = [
     // --- exit and cancellation ---
     ("quit", quit),
     // --- help ---
@@ -1982,9 +2391,15 @@ pub const OP_MAPPINGS: [(&'static str, OpFn); 76] = [
     ("scroll-center", scroll_center),
     ("set-mark", set_mark),
     ("goto-line", goto_line),
+    ("match-bracket", match_bracket),
     // --- insertion and removal ---
     ("insert-line", insert_line),
     ("insert-tab", insert_tab),
+    ("indent-region", indent_region),
+    ("outdent-region", outdent_region),
+    ("toggle-comment", toggle_comment),
+    ("trim-whitespace", trim_whitespace),
+    ("final-newline", final_newline),
     ("remove-before", remove_before),
     ("remove-after", remove_after),
     ("remove-start", remove_start),
@@ -2007,6 +2422,8 @@ pub const OP_MAPPINGS: [(&'static str, OpFn); 76] = [
     ("open-file-bottom", open_file_bottom),
     ("open-file-above", open_file_above),
     ("open-file-below", open_file_below),
+    ("open-file-left", open_file_left),
+    ("open-file-right", open_file_right),
     ("save-file", save_file),
     ("save-file-as", save_file_as),
     // --- editor handling ---
@@ -2021,13 +2438,27 @@ pub const OP_MAPPINGS: [(&'static str, OpFn); 76] = [
     ("kill-window", kill_window),
     ("close-window", close_window),
     ("close-other-windows", close_other_windows),
+    ("split-window-below", split_window_below),
+    ("split-window-right", split_window_right),
+    ("grow-window", grow_window),
+    ("shrink-window", shrink_window),
+    ("balance-windows", balance_windows),
+    ("maximize-window", maximize_window),
     ("top-window", top_window),
     ("bottom-window", bottom_window),
     ("prev-window", prev_window),
     ("next-window", next_window),
+    ("up-window", up_window),
+    ("down-window", down_window),
+    ("left-window", left_window),
+    ("right-window", right_window),
     // --- behaviors ---
     ("describe-editor", describe_editor),
     ("tab-mode", tab_mode),
+    ("wrap-mode", wrap_mode),
+    ("whitespace-mode", whitespace_mode),
+    ("select-theme", select_theme),
+    ("set-syntax", set_syntax),
 ];
 
 pub fn init_op_map() -> OpMap {
@@ -2037,3 +2468,42 @@ pub fn init_op_map() -> OpMap {
     }
     op_map
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Configuration;
+    use crate::workspace::Workspace;
+
+    #[test]
+    fn dirty_editors_once_per_buffer() {
+        let workspace = Workspace::with_size(Configuration::default(), Size::new(25, 80));
+        let mut env = Environment::new(workspace.to_ref());
+        let config = env.workspace().config().clone();
+        let open = |env: &mut Environment, editor: EditorRef| {
+            let (view_id, _) = env
+                .open_editor(editor, Placement::Bottom, Align::Auto)
+                .unwrap();
+            env.set_active(Focus::To(view_id))
+        };
+        let a_view_id = open(&mut env, file_editor(&config, "a.txt"));
+        open(&mut env, file_editor(&config, "b.txt"));
+        let shared = env.get_active_editor().borrow().share().to_ref();
+        open(&mut env, shared);
+        assert_eq!(dirty_editors(&env).len(), 0);
+
+        // Active editor shares buffer with "b.txt".
+        insert_char(&mut env, 'x');
+        let editors = dirty_editors(&env);
+        assert_eq!(editors.len(), 1);
+        assert_eq!(editors[0].borrow().source().to_string(), "b.txt");
+
+        env.set_active(Focus::To(a_view_id));
+        insert_char(&mut env, 'x');
+        assert_eq!(dirty_editors(&env).len(), 2);
+    }
+
+    fn file_editor(config: &ConfigurationRef, path: &str) -> EditorRef {
+        Editor::mutable(config.clone(), Source::as_file(path, None), None).to_ref()
+    }
+}
@@ -7,9 +7,12 @@ use std::str::FromStr;
 pub struct Options {
     pub spotlight: Option<bool>,
     pub lines: Option<bool>,
+    pub wrap: Option<bool>,
     pub eol: Option<bool>,
+    pub tab_glyph: Option<bool>,
     pub tab_hard: Option<bool>,
     pub tab_size: Option<u32>,
+    pub theme_name: Option<String>,
     pub keys: bool,
     pub ops: bool,
     pub bindings: bool,
@@ -30,9 +33,12 @@ impl Default for Options {
         Options {
             spotlight: None,
             lines: None,
+            wrap: None,
             eol: None,
+            tab_glyph: None,
             tab_hard: None,
             tab_size: None,
+            theme_name: None,
             keys: false,
             ops: false,
             bindings: false,
@@ -63,11 +69,16 @@ impl Options {
                 "--no-spotlight" => opts.spotlight = Some(false),
                 "--lines" => opts.lines = Some(true),
                 "--no-lines" => opts.lines = Some(false),
+                "--wrap" => opts.wrap = Some(true),
+                "--no-wrap" => opts.wrap = Some(false),
                 "--eol" => opts.eol = Some(true),
                 "--no-eol" => opts.eol = Some(false),
+                "--tab-glyph" => opts.tab_glyph = Some(true),
+                "--no-tab-glyph" => opts.tab_glyph = Some(false),
                 "--tab-hard" => opts.tab_hard = Some(true),
                 "--tab-soft" => opts.tab_hard = Some(false),
                 "--tab-size" | "-t" => opts.tab_size = Some(parse_arg(&arg, it.next())?),
+                "--theme-name" => opts.theme_name = Some(expect_value(&arg, it.next())?),
                 "--keys" => opts.keys = true,
                 "--ops" => opts.ops = true,
                 "--bindings" => opts.bindings = true,
@@ -40,6 +40,14 @@ impl Source {
             _ => false,
         }
     }
+
+    /// Returns the path if this is a _file_ source, otherwise `None`.
+    pub fn file_path(&self) -> Option<&str> {
+        match self {
+            Self::File(path, _) => Some(path.as_str()),
+            _ => None,
+        }
+    }
 }
 
 impl Display for Source {
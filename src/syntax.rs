@@ -4,9 +4,22 @@
 //! [`Syntax`] types are essentially collections of regular expressions that match
 //! tokens for various kinds of file formats.
 //!
+//! A syntax may also define _regions_, which are delimited by _start_ and _end_
+//! patterns and contain their own set of tokens and nested regions. Examples are
+//! string interpolation, or code fences in Markdown that are highlighted using the
+//! fenced language. A region may _include_ another syntax by name, in which case the
+//! tokens and regions of that syntax also apply inside the region. Text is scanned
+//! in the _context_ of either the syntax itself or the innermost region, so the
+//! tokenizer keeps a stack of contexts while scanning.
+//!
 //! External configuration files are expected to be formatted according to the
 //! [TOML specification](https://toml.io).
 //!
+//! The syntax of a file is detected in the following order of precedence: a
+//! _modeline_ of the form `ped: syntax=<name>` in the first or last few lines of the
+//! file, a match of the file name against `files` patterns, and finally a match of
+//! the first line, such as a shebang, against `first-line` patterns.
+//!
 //! Syntax files are enumerated and loaded by searching predefined directories via
 //! [`Registry::load()`], or from a custom directory using [`Registry::load_dir()`].
 //! Any file in the applicable directory with an extension of `.toml` is assumed to
@@ -16,14 +29,20 @@
 //!
 //! * `$HOME/.ped/syntax`
 //! * `$HOME/.config/ped/syntax`
+//!
+//! A set of common syntax configurations is compiled into the binary and forms the
+//! lowest-precedence layer of every registry. A syntax file whose name matches that
+//! of a built-in syntax replaces the built-in syntax entirely.
 
-use crate::color::{ColorValue, Colors};
+use crate::buffer::Buffer;
+use crate::color::{Colors, Style, StyleValue};
+use crate::config::SettingsOverride;
 use crate::error::{Error, Result};
 use crate::sys::{self, AsString};
 use indexmap::IndexMap;
-use regex_lite::{Captures, Regex, RegexBuilder};
+use regex_lite::{Regex, RegexBuilder};
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::ops::Range;
 use std::path::Path;
@@ -31,10 +50,14 @@ use std::path::Path;
 /// A registry of syntax configurations.
 pub struct Registry {
     /// A map of canonical syntax names to syntax configurations.
-    syntax_map: HashMap<String, Syntax>,
+    syntax_map: BTreeMap<String, Syntax>,
 
     /// A list of regular expressions that map to canonical syntax names.
     re_list: Vec<(Regex, String)>,
+
+    /// A list of regular expressions matching the first line of a file that map to
+    /// canonical syntax names.
+    first_list: Vec<(Regex, String)>,
 }
 
 /// A syntax configuration.
@@ -43,13 +66,31 @@ pub struct Syntax {
     /// The canonical name of the syntax configuration.
     pub name: String,
 
-    /// A single regular expression aggregating all token definitions, each adorned
-    /// with its own capture group name.
-    pub re: Regex,
-
-    /// A collection of token definitions whose order is crucial since [`re`](Self::re)
-    /// is constructed in the order of iteration.
+    /// A collection of token definitions, including those of regions and included
+    /// syntaxes, where the id of each token is one more than its index.
     tokens: Vec<Token>,
+
+    /// A collection of contexts in which text is scanned, where the first is the
+    /// _root_ context and the remaining contexts correspond to regions.
+    contexts: Vec<Context>,
+
+    /// An optional regular expression that, when matching the text preceding the
+    /// cursor upon insertion of a new line, causes the new line to be indented one
+    /// level deeper than the current line.
+    indent: Option<Regex>,
+
+    /// An optional regular expression that, when matching the text of a line up to
+    /// and including a typed character, causes the line to be dedented by one level.
+    dedent: Option<Regex>,
+
+    /// An optional marker that starts a comment extending to the end of the line.
+    line_comment: Option<String>,
+
+    /// An optional pair of markers that open and close a comment.
+    block_comment: Option<(String, String)>,
+
+    /// Editor settings that apply to files using this syntax.
+    settings: SettingsOverride,
 }
 
 /// A token represents a regular expression with a unique identifier that is used in
@@ -63,18 +104,120 @@ struct Token {
     /// [`id`](Self::id).
     name: String,
 
-    /// The regular expression for this token.
+    /// The regular expression for this token, or the _start_ pattern if the token
+    /// represents the delimiters of a region.
     pattern: String,
 
-    /// The foreground color associated with this token.
-    color: u8,
+    /// The style associated with this token.
+    style: Style,
+
+    /// Indicates that the token contains literal text, such as a string or comment,
+    /// in which brackets are not matched.
+    literal: bool,
+}
+
+/// A context in which text is scanned, which is either the root context of a syntax
+/// or the inside of a region.
+#[derive(Clone)]
+struct Context {
+    /// A single regular expression aggregating the _end_ pattern of the region, if
+    /// applicable, followed by all rules, each adorned with its own capture group
+    /// name.
+    re: Regex,
+
+    /// A collection of rules paired with their capture group names whose order is
+    /// crucial since [`re`](Self::re) is constructed in the order of iteration.
+    rules: Vec<(String, Rule)>,
+
+    /// The id of the token applied to text not matching any rule, or `0` if such
+    /// text is not styled.
+    content_id: usize,
+
+    /// The id of the token applied to the _start_ and _end_ delimiters of the
+    /// region, or `0` if delimiters are not styled.
+    delimiter_id: usize,
+
+    /// Indicates that text inside the region is literal, such as a string or
+    /// comment, in which brackets are not matched.
+    literal: bool,
+}
+
+/// A rule that matches text in a particular context.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Rule {
+    /// A token identified by its id.
+    Token(usize),
+
+    /// The start of a region whose context is identified by its index.
+    Start(usize),
+
+    /// The end of the region corresponding to the current context.
+    End,
+}
+
+/// The definition of a token as a tuple containing a regular expression, a style,
+/// and whether the token contains literal text.
+type TokenDef = (String, Style, bool);
+
+/// The definition of a syntax whose included syntaxes are not yet resolved.
+struct Definition {
+    tokens: Vec<TokenDef>,
+    regions: Vec<Region>,
+}
+
+/// The definition of a region.
+struct Region {
+    start: String,
+    end: String,
+    style: Option<Style>,
+    delimiter_style: Option<Style>,
+    literal: bool,
+    tokens: Vec<TokenDef>,
+    regions: Vec<Region>,
+    include: Option<String>,
+}
+
+/// A context under construction, whose rules may refer to contexts that are not yet
+/// complete.
+struct ContextDef {
+    start: String,
+    end: Option<String>,
+    rules: Vec<RuleDef>,
+    content_id: usize,
+    delimiter_id: usize,
+    literal: bool,
+}
+
+/// A rule under construction.
+#[derive(Copy, Clone)]
+enum RuleDef {
+    /// A token identified by its id.
+    Token(usize),
+
+    /// A region whose context is identified by its index.
+    Region(usize),
+
+    /// All rules of the context identified by its index.
+    Include(usize),
+}
+
+/// Builds the contexts of a syntax by flattening its regions and resolving included
+/// syntaxes.
+struct Builder<'a> {
+    name: &'a str,
+    defs: &'a HashMap<String, &'a Definition>,
+    tokens: Vec<Token>,
+    contexts: Vec<ContextDef>,
+    included: HashMap<String, usize>,
 }
 
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
 struct ExternalConfig {
     syntax: ExternalSyntax,
-    tokens: Option<IndexMap<String, ColorValue>>,
+    tokens: Option<IndexMap<String, ExternalToken>>,
+    regions: Option<Vec<ExternalRegion>>,
+    settings: Option<SettingsOverride>,
 }
 
 #[derive(Deserialize)]
@@ -82,76 +225,212 @@ struct ExternalConfig {
 struct ExternalSyntax {
     name: String,
     files: Vec<String>,
+
+    #[serde(rename = "first-line")]
+    first_line: Option<Vec<String>>,
+
+    indent: Option<String>,
+    dedent: Option<String>,
+
+    #[serde(rename = "line-comment")]
+    line_comment: Option<String>,
+
+    #[serde(rename = "block-comment")]
+    block_comment: Option<(String, String)>,
+
+    /// Editor settings from the `[settings]` table, which is a sibling of `[syntax]`.
+    #[serde(skip)]
+    settings: SettingsOverride,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExternalRegion {
+    start: String,
+    end: String,
+    style: Option<StyleValue>,
+
+    #[serde(rename = "delimiter-style")]
+    delimiter_style: Option<StyleValue>,
+
+    literal: Option<bool>,
+    tokens: Option<IndexMap<String, ExternalToken>>,
+    regions: Option<Vec<ExternalRegion>>,
+    include: Option<String>,
+}
+
+/// A token whose value is either a style, or a table of the form
+/// `{ style = "...", literal = true }` that marks the token as containing literal
+/// text.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExternalToken {
+    Style(StyleValue),
+    Rule(ExternalRule),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExternalRule {
+    style: StyleValue,
+    literal: Option<bool>,
 }
 
 impl Syntax {
     /// Name of default syntax.
-    const DEFAULT_NAME: &str = "Text";
+    pub const DEFAULT_NAME: &str = "Text";
 
     /// A regular expression that never matches, which is used when no tokens are
     /// provided.
     const EMPTY_REGEX: &str = "^$a";
 
+    /// Index of the root context.
+    pub const ROOT: usize = 0;
+
     /// Creates a new syntax identified by `name` and using `tokens`, which are
-    /// tuples containing a regular expression and a foreground color.
+    /// tuples containing a regular expression and a style.
     ///
     /// If any of the regular expressions are malformed or the aggregate size of all
     /// regular expressions is too large, then an error is returned.
-    pub fn new(name: String, tokens: Vec<(String, u8)>) -> Result<Syntax> {
+    pub fn new(name: String, tokens: Vec<(String, Style)>) -> Result<Syntax> {
+        let def = Definition {
+            tokens: tokens
+                .into_iter()
+                .map(|(pattern, style)| (pattern, style, false))
+                .collect(),
+            regions: Vec::new(),
+        };
+        Self::build(name, &def, &HashMap::new())
+    }
+
+    /// Creates a new syntax identified by `name` using the definition `def`, where
+    /// included syntaxes are resolved using `defs`.
+    fn build(
+        name: String,
+        def: &Definition,
+        defs: &HashMap<String, &Definition>,
+    ) -> Result<Syntax> {
         // Tokens are adorned with capture group names of "_<id>" where <id> is the
         // index of the token definition offset by 1. Offset is required because
         // token id 0 is reserved to represent the absence of a token.
-        let tokens = if tokens.len() > 0 {
-            tokens
+        let empty;
+        let def = if !def.tokens.is_empty() || !def.regions.is_empty() {
+            def
         } else {
-            vec![(Self::EMPTY_REGEX.to_string(), 0)]
+            empty = Definition {
+                tokens: vec![(Self::EMPTY_REGEX.to_string(), Style::default(), false)],
+                regions: Vec::new(),
+            };
+            &empty
         };
 
-        let tokens = tokens
-            .iter()
-            .enumerate()
-            .map(|(i, (pattern, color))| Token {
-                id: i + 1,
-                name: format!("_{}", i + 1),
-                pattern: pattern.clone(),
-                color: *color,
-            })
-            .collect::<Vec<_>>();
-
-        // Join all token regular expressions using capture group names.
-        let pattern = tokens
-            .iter()
-            .map(|def| format!("(?<{}>{})", def.name, def.pattern))
-            .collect::<Vec<_>>()
-            .join("|");
-
-        let re = match RegexBuilder::new(&pattern).multi_line(true).build() {
-            Ok(re) => re,
-            Err(e) => return Err(Error::invalid_regex(&pattern, &e)),
+        let mut builder = Builder {
+            name: &name,
+            defs,
+            tokens: Vec::new(),
+            contexts: Vec::new(),
+            included: HashMap::new(),
         };
+        let root = builder.add_context(String::new(), None, 0, 0, false);
+        builder.included.insert(name.clone(), root);
+        builder.fill_context(root, &def.tokens, &def.regions, None)?;
+        let (tokens, contexts) = builder.finish()?;
 
-        let this = Syntax { name, re, tokens };
+        let this = Syntax {
+            name,
+            tokens,
+            contexts,
+            indent: None,
+            dedent: None,
+            line_comment: None,
+            block_comment: None,
+            settings: SettingsOverride::default(),
+        };
         Ok(this)
     }
 
-    /// Returns the token id and the byte offset range for the matching capture group
-    /// `cap`.
+    /// Finds the first rule applicable to the context `ctx` that matches `text` at or
+    /// after the byte offset `offset`, returning the rule and the byte offset range
+    /// of the match.
     ///
-    /// This function panics if the capture group does not match any of the expected
-    /// names, as such condition would indicate a correctness problem.
-    pub fn lookup(&self, cap: &Captures) -> (usize, Range<usize>) {
-        self.tokens
-            .iter()
-            .find_map(|token| cap.name(&token.name).map(|m| (token.id, m.range())))
-            .unwrap_or_else(|| panic!("{}: capture group expected for token", &cap[0]))
+    /// This function panics if the match does not correspond to any of the expected
+    /// capture group names, as such condition would indicate a correctness problem.
+    pub fn find_at(&self, ctx: usize, text: &str, offset: usize) -> Option<(Rule, Range<usize>)> {
+        let context = &self.contexts[ctx];
+        context.re.captures_at(text, offset).map(|cap| {
+            context
+                .rules
+                .iter()
+                .find_map(|(name, rule)| cap.name(name).map(|m| (*rule, m.range())))
+                .unwrap_or_else(|| panic!("{}: capture group expected for rule", &cap[0]))
+        })
+    }
+
+    /// Returns the id of the token corresponding to `rule` when matched in the
+    /// context `ctx`.
+    pub fn token_id(&self, ctx: usize, rule: Rule) -> usize {
+        match rule {
+            Rule::Token(id) => id,
+            Rule::Start(c) => self.contexts[c].delimiter_id,
+            Rule::End => self.contexts[ctx].delimiter_id,
+        }
+    }
+
+    /// Returns the id of the token applied to text in the context `ctx` that does
+    /// not match any rule, which is `0` if such text is not styled.
+    pub fn content_id(&self, ctx: usize) -> usize {
+        self.contexts[ctx].content_id
+    }
+
+    /// Returns `true` if the token referenced by `id`, or unmatched text in the
+    /// context `ctx` if `id` is `0`, contains literal text, such as a string or
+    /// comment.
+    pub fn is_literal(&self, ctx: usize, id: usize) -> bool {
+        if id > 0 {
+            self.tokens[id - 1].literal
+        } else {
+            self.contexts[ctx].literal
+        }
+    }
+
+    /// Returns `true` if `text` matches the _indent_ rule, if defined.
+    pub fn is_indent(&self, text: &str) -> bool {
+        self.indent.as_ref().is_some_and(|re| re.is_match(text))
     }
 
-    /// Returns the foreground color associated with the token referenced by `id`.
-    pub fn color(&self, id: usize) -> Option<u8> {
+    /// Returns `true` if the _dedent_ rule is defined.
+    pub fn has_dedent(&self) -> bool {
+        self.dedent.is_some()
+    }
+
+    /// Returns `true` if `text` matches the _dedent_ rule, if defined.
+    pub fn is_dedent(&self, text: &str) -> bool {
+        self.dedent.as_ref().is_some_and(|re| re.is_match(text))
+    }
+
+    /// Returns the marker that starts a line comment, if defined.
+    pub fn line_comment(&self) -> Option<&str> {
+        self.line_comment.as_deref()
+    }
+
+    /// Returns the editor settings that apply to files using this syntax.
+    pub fn settings(&self) -> &SettingsOverride {
+        &self.settings
+    }
+
+    /// Returns the pair of markers that open and close a block comment, if defined.
+    pub fn block_comment(&self) -> Option<(&str, &str)> {
+        self.block_comment
+            .as_ref()
+            .map(|(open, close)| (open.as_str(), close.as_str()))
+    }
+
+    /// Returns the style associated with the token referenced by `id`.
+    pub fn style(&self, id: usize) -> Option<Style> {
         if id == 0 {
             None
         } else {
-            Some(self.tokens[id - 1].color)
+            Some(self.tokens[id - 1].style)
         }
     }
 }
@@ -163,6 +442,177 @@ impl Default for Syntax {
     }
 }
 
+impl Builder<'_> {
+    /// Adds a token with `pattern` and `style`, returning its id.
+    fn add_token(&mut self, pattern: &str, style: Style, literal: bool) -> usize {
+        let id = self.tokens.len() + 1;
+        self.tokens.push(Token {
+            id,
+            name: format!("_{id}"),
+            pattern: pattern.to_string(),
+            style,
+            literal,
+        });
+        id
+    }
+
+    /// Adds an empty context, returning its index.
+    fn add_context(
+        &mut self,
+        start: String,
+        end: Option<String>,
+        content_id: usize,
+        delimiter_id: usize,
+        literal: bool,
+    ) -> usize {
+        self.contexts.push(ContextDef {
+            start,
+            end,
+            rules: Vec::new(),
+            content_id,
+            delimiter_id,
+            literal,
+        });
+        self.contexts.len() - 1
+    }
+
+    /// Adds the rules of `tokens`, `regions`, and the optionally included syntax to
+    /// the context `ctx`, where tokens inherit the literal nature of the context.
+    fn fill_context(
+        &mut self,
+        ctx: usize,
+        tokens: &[TokenDef],
+        regions: &[Region],
+        include: Option<&str>,
+    ) -> Result<()> {
+        for (pattern, style, literal) in tokens {
+            let literal = *literal || self.contexts[ctx].literal;
+            let id = self.add_token(pattern, *style, literal);
+            self.contexts[ctx].rules.push(RuleDef::Token(id));
+        }
+        for region in regions {
+            let c = self.add_region(region)?;
+            self.contexts[ctx].rules.push(RuleDef::Region(c));
+        }
+        if let Some(name) = include {
+            let c = self.include(name)?;
+            self.contexts[ctx].rules.push(RuleDef::Include(c));
+        }
+        Ok(())
+    }
+
+    /// Adds the context for `region`, returning its index.
+    fn add_region(&mut self, region: &Region) -> Result<usize> {
+        let content_id = region
+            .style
+            .map(|style| self.add_token(&region.start, style, region.literal))
+            .unwrap_or(0);
+        let delimiter_id = region
+            .delimiter_style
+            .map(|style| self.add_token(&region.start, style, region.literal))
+            .unwrap_or(content_id);
+        let ctx = self.add_context(
+            region.start.clone(),
+            Some(region.end.clone()),
+            content_id,
+            delimiter_id,
+            region.literal,
+        );
+        self.fill_context(
+            ctx,
+            &region.tokens,
+            &region.regions,
+            region.include.as_deref(),
+        )?;
+        Ok(ctx)
+    }
+
+    /// Returns the index of the context containing the rules of the syntax `name`,
+    /// adding such context if the syntax was not already included.
+    fn include(&mut self, name: &str) -> Result<usize> {
+        if let Some(ctx) = self.included.get(name) {
+            Ok(*ctx)
+        } else {
+            let def = self
+                .defs
+                .get(name)
+                .ok_or_else(|| Error::invalid_include(self.name, name))?;
+            let ctx = self.add_context(String::new(), None, 0, 0, false);
+            self.included.insert(name.to_string(), ctx);
+            self.fill_context(ctx, &def.tokens, &def.regions, None)?;
+            Ok(ctx)
+        }
+    }
+
+    /// Compiles the regular expressions of all contexts that correspond to either
+    /// the root context or regions, since contexts of included syntaxes are merely
+    /// containers of rules.
+    fn finish(self) -> Result<(Vec<Token>, Vec<Context>)> {
+        let mut contexts = Vec::new();
+        for (ctx, def) in self.contexts.iter().enumerate() {
+            let mut rules = Vec::new();
+            if let Some(end) = &def.end {
+                rules.push(("_e".to_string(), Rule::End, end.as_str()));
+            }
+            let mut visited = HashSet::from([ctx]);
+            self.collect_rules(ctx, &mut visited, &mut rules);
+
+            // Join all regular expressions using capture group names.
+            let pattern = if !rules.is_empty() {
+                rules
+                    .iter()
+                    .map(|(name, _, pattern)| format!("(?<{name}>{pattern})"))
+                    .collect::<Vec<_>>()
+                    .join("|")
+            } else {
+                Syntax::EMPTY_REGEX.to_string()
+            };
+            let re = match RegexBuilder::new(&pattern).multi_line(true).build() {
+                Ok(re) => re,
+                Err(e) => return Err(Error::invalid_regex(&pattern, &e)),
+            };
+            contexts.push(Context {
+                re,
+                rules: rules
+                    .into_iter()
+                    .map(|(name, rule, _)| (name, rule))
+                    .collect(),
+                content_id: def.content_id,
+                delimiter_id: def.delimiter_id,
+                literal: def.literal,
+            });
+        }
+        Ok((self.tokens, contexts))
+    }
+
+    /// Appends the rules of the context `ctx` to `rules` as tuples of capture group
+    /// name, rule, and pattern, recursively expanding included contexts not yet
+    /// `visited`.
+    fn collect_rules<'a>(
+        &'a self,
+        ctx: usize,
+        visited: &mut HashSet<usize>,
+        rules: &mut Vec<(String, Rule, &'a str)>,
+    ) {
+        for rule in &self.contexts[ctx].rules {
+            match *rule {
+                RuleDef::Token(id) => {
+                    let token = &self.tokens[id - 1];
+                    rules.push((token.name.clone(), Rule::Token(token.id), &token.pattern));
+                }
+                RuleDef::Region(c) => {
+                    rules.push((format!("_r{c}"), Rule::Start(c), &self.contexts[c].start));
+                }
+                RuleDef::Include(c) => {
+                    if visited.insert(c) {
+                        self.collect_rules(c, visited, rules);
+                    }
+                }
+            }
+        }
+    }
+}
+
 impl Registry {
     /// A collection of directories to try loading syntax configurations.
     const TRY_DIRS: [&str; 2] = [".ped/syntax", ".config/ped/syntax"];
@@ -170,11 +620,37 @@ impl Registry {
     /// File extensions that identify candidate syntax configurations.
     const FILE_EXT: &str = "toml";
 
+    /// Syntax configurations compiled into the binary as pairs of file name and
+    /// content.
+    const BUILTIN_SYNTAX: [(&str, &str); 8] = [
+        ("c.toml", include_str!("include/syntax/c.toml")),
+        ("json.toml", include_str!("include/syntax/json.toml")),
+        (
+            "markdown.toml",
+            include_str!("include/syntax/markdown.toml"),
+        ),
+        ("python.toml", include_str!("include/syntax/python.toml")),
+        ("rust.toml", include_str!("include/syntax/rust.toml")),
+        ("shell.toml", include_str!("include/syntax/shell.toml")),
+        ("toml.toml", include_str!("include/syntax/toml.toml")),
+        ("yaml.toml", include_str!("include/syntax/yaml.toml")),
+    ];
+
+    /// Marker that introduces a modeline.
+    const MODELINE_MARKER: &str = "ped:";
+
+    /// Number of lines at the start and end of a file searched for a modeline.
+    const MODELINE_LINES: usize = 5;
+
+    /// Maximum number of characters at the start and end of a file searched for a
+    /// modeline, which bounds the cost of detection for files with long lines.
+    const MODELINE_CHARS: usize = 4096;
+
     /// Returns a syntax registry that is initialized using syntax configuration files
     /// from well-known directories.
     ///
-    /// An empty registry is returned if none of the well-known directories exist or
-    /// contain any configuration files.
+    /// A registry of only built-in syntax configurations is returned if none of the
+    /// well-known directories exist or contain any configuration files.
     ///
     /// An error is returned if any syntax configuration file is malformed in any way.
     pub fn load(colors: &Colors) -> Result<Registry> {
@@ -184,23 +660,30 @@ impl Registry {
             .map(|dir| root_path.join(dir))
             .find(|dir| sys::is_dir(dir))
             .map(|dir| Self::load_dir(dir, colors))
-            .unwrap_or_else(|| Ok(Registry::default()))
+            .unwrap_or_else(|| Self::builtin(colors))
     }
 
     /// Returns a syntax registry that is initialized using syntax configuration files
     /// in `dir`.
     ///
-    /// An empty registry is returned if `dir` is missing or not a directory.
+    /// A registry of only built-in syntax configurations is returned if `dir` is
+    /// missing or not a directory.
     ///
     /// An error is returned if any syntax configuration file is malformed in any way.
     pub fn load_dir<P: AsRef<Path>>(dir: P, colors: &Colors) -> Result<Registry> {
         if sys::is_dir(&dir) {
             Self::load_registry(&dir, colors)
         } else {
-            Ok(Registry::default())
+            Self::builtin(colors)
         }
     }
 
+    /// Returns a syntax registry that is initialized using only the syntax
+    /// configurations compiled into the binary.
+    pub fn builtin(colors: &Colors) -> Result<Registry> {
+        Self::build_registry(Vec::new(), colors)
+    }
+
     /// Returns the first syntax configuration matching the file name of `path`, or
     /// `None` if no match is found.
     pub fn find<P: AsRef<Path>>(&self, path: P) -> Option<&Syntax> {
@@ -212,6 +695,70 @@ impl Registry {
         })
     }
 
+    /// Returns the syntax configuration for the file `path` whose contents are in
+    /// `buffer`, or `None` if no match is found.
+    ///
+    /// A modeline naming a known syntax takes precedence, followed by matching the
+    /// file name of `path`, and finally matching the first line of `buffer`.
+    pub fn detect<P: AsRef<Path>>(&self, path: P, buffer: &Buffer) -> Option<&Syntax> {
+        let head = buffer
+            .forward(0)
+            .take(Self::MODELINE_CHARS)
+            .collect::<String>();
+        let tail = buffer
+            .backward(buffer.size())
+            .take(Self::MODELINE_CHARS)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect::<String>();
+
+        head.lines()
+            .take(Self::MODELINE_LINES)
+            .chain(tail.lines().rev().take(Self::MODELINE_LINES))
+            .filter_map(Self::modeline)
+            .find_map(|name| self.get(name))
+            .or_else(|| self.find(path))
+            .or_else(|| {
+                head.lines().next().and_then(|line| {
+                    self.first_list
+                        .iter()
+                        .find(|(re, _)| re.is_match(line))
+                        .and_then(|(_, name)| self.syntax_map.get(name))
+                })
+            })
+    }
+
+    /// Returns the syntax configuration whose canonical name matches `name`, ignoring
+    /// case, or `None` if no match is found.
+    pub fn get(&self, name: &str) -> Option<&Syntax> {
+        self.syntax_map
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, syntax)| syntax)
+    }
+
+    /// Returns an iterator over the canonical syntax names in lexicographical order.
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.syntax_map.keys()
+    }
+
+    /// Returns the syntax name in `line` if it contains a modeline of the form
+    /// `ped: syntax=<name>`, otherwise `None`.
+    fn modeline(line: &str) -> Option<&str> {
+        let (_, rest) = line.split_once(Self::MODELINE_MARKER)?;
+        let rest = rest.trim_start().strip_prefix("syntax")?;
+        let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+        let name = rest
+            .split(|c: char| c.is_whitespace() || c == ':' || c == ';')
+            .next()?;
+        if name.is_empty() {
+            None
+        } else {
+            Some(name)
+        }
+    }
+
     /// Creates a registry by enumerating and loading files from `dir`.
     fn load_registry<P: AsRef<Path>>(dir: P, colors: &Colors) -> Result<Registry> {
         let paths = sys::list_files(dir.as_ref());
@@ -226,65 +773,149 @@ impl Registry {
             })
             .collect::<Vec<_>>();
 
-        let mut syntax_map = HashMap::new();
-        let mut re_list = Vec::new();
+        let mut configs = Vec::new();
         for path in paths {
-            let (syntax, res) = Self::load_syntax(path, colors)?;
-            let name = syntax.name.clone();
-            for re in res {
-                re_list.push((re, name.clone()));
+            configs.push(Self::load_syntax(path, colors)?);
+        }
+        Self::build_registry(configs, colors)
+    }
+
+    /// Creates a registry from `configs` loaded from files, followed by built-in
+    /// syntax configurations whose names are not already present in `configs`.
+    ///
+    /// Since syntaxes may include other syntaxes by name, all configurations are
+    /// loaded before any syntax is built.
+    fn build_registry(
+        mut configs: Vec<(ExternalSyntax, Definition)>,
+        colors: &Colors,
+    ) -> Result<Registry> {
+        for (file, content) in Self::BUILTIN_SYNTAX {
+            let (config, def) = Self::parse_syntax(file, content, colors)?;
+            if !configs.iter().any(|(c, _)| c.name == config.name) {
+                configs.push((config, def));
+            }
+        }
+
+        let defs = configs
+            .iter()
+            .map(|(config, def)| (config.name.clone(), def))
+            .collect::<HashMap<_, _>>();
+
+        let mut syntax_map = BTreeMap::new();
+        let mut re_list = Vec::new();
+        let mut first_list = Vec::new();
+        for (config, def) in &configs {
+            let mut syntax = Syntax::build(config.name.clone(), def, &defs)?;
+            syntax.indent = Self::build_regex(config.indent.clone())?;
+            syntax.dedent = Self::build_regex(config.dedent.clone())?;
+            syntax.line_comment = config.line_comment.clone();
+            syntax.block_comment = config.block_comment.clone();
+            syntax.settings = config.settings.clone();
+
+            // Convert file patterns to regular expressions.
+            for pattern in &config.files {
+                let re = Regex::new(pattern).map_err(|e| Error::invalid_regex(pattern, &e))?;
+                re_list.push((re, config.name.clone()));
+            }
+            for pattern in config.first_line.iter().flatten() {
+                let re = Regex::new(pattern).map_err(|e| Error::invalid_regex(pattern, &e))?;
+                first_list.push((re, config.name.clone()));
             }
-            syntax_map.insert(name, syntax);
+            syntax_map.insert(config.name.clone(), syntax);
         }
 
         let registry = Registry {
             syntax_map,
             re_list,
+            first_list,
         };
         Ok(registry)
     }
 
     /// Loads the syntax configuration referenced by `path`, returning the syntax
-    /// along with a vector of regular expressions for matching file names.
-    fn load_syntax<P: AsRef<Path>>(path: P, colors: &Colors) -> Result<(Syntax, Vec<Regex>)> {
-        let config = Self::read_file(path.as_ref())?;
-
-        // Build tokens and create syntax configuration.
-        let tokens = if let Some(tokens) = config.tokens {
-            let mut ts = Vec::new();
-            for (pattern, color) in &tokens {
-                if let Some(c) = colors.lookup_value(color) {
-                    ts.push((pattern.clone(), c));
-                } else {
-                    return Err(Error::invalid_color(&color.to_string()));
-                }
-            }
-            ts
-        } else {
-            Vec::new()
+    /// attributes along with its definition.
+    fn load_syntax<P: AsRef<Path>>(
+        path: P,
+        colors: &Colors,
+    ) -> Result<(ExternalSyntax, Definition)> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| Error::io(&path.as_string(), e))?;
+        Self::parse_syntax(&path.as_string(), &content, colors)
+    }
+
+    /// Parses the syntax configuration in `content`, returning the syntax attributes
+    /// along with its definition, where `origin` identifies the source of `content`
+    /// in error messages.
+    fn parse_syntax(
+        origin: &str,
+        content: &str,
+        colors: &Colors,
+    ) -> Result<(ExternalSyntax, Definition)> {
+        let mut config =
+            toml::from_str::<ExternalConfig>(content).map_err(|e| Error::syntax(origin, &e))?;
+        config.syntax.settings = config.settings.unwrap_or_default();
+        let def = Definition {
+            tokens: Self::build_tokens(config.tokens, colors)?,
+            regions: Self::build_regions(config.regions, colors)?,
         };
-        let syntax = Syntax::new(config.syntax.name, tokens)?;
+        Ok((config.syntax, def))
+    }
 
-        // Convert file patterns to regular expressions.
-        let mut res = Vec::new();
-        for pattern in config.syntax.files {
-            let re = Regex::new(&pattern).map_err(|e| Error::invalid_regex(&pattern, &e))?;
-            res.push(re);
+    /// Builds tokens from an optional map of patterns to styles.
+    fn build_tokens(
+        tokens: Option<IndexMap<String, ExternalToken>>,
+        colors: &Colors,
+    ) -> Result<Vec<TokenDef>> {
+        let mut ts = Vec::new();
+        for (pattern, token) in tokens.unwrap_or_default() {
+            let (style, literal) = match token {
+                ExternalToken::Style(style) => (style, false),
+                ExternalToken::Rule(rule) => (rule.style, rule.literal.unwrap_or(false)),
+            };
+            ts.push((pattern, colors.lookup_style(&style)?, literal));
         }
-        Ok((syntax, res))
+        Ok(ts)
     }
 
-    fn read_file(path: &Path) -> Result<ExternalConfig> {
-        let content = fs::read_to_string(path).map_err(|e| Error::io(&path.as_string(), e))?;
-        toml::from_str::<ExternalConfig>(&content).map_err(|e| Error::syntax(&path.as_string(), &e))
+    /// Builds regions, including those nested, from an optional list of external
+    /// region definitions.
+    fn build_regions(regions: Option<Vec<ExternalRegion>>, colors: &Colors) -> Result<Vec<Region>> {
+        let mut rs = Vec::new();
+        for region in regions.unwrap_or_default() {
+            rs.push(Region {
+                start: region.start,
+                end: region.end,
+                style: region
+                    .style
+                    .map(|style| colors.lookup_style(&style))
+                    .transpose()?,
+                delimiter_style: region
+                    .delimiter_style
+                    .map(|style| colors.lookup_style(&style))
+                    .transpose()?,
+                literal: region.literal.unwrap_or(false),
+                tokens: Self::build_tokens(region.tokens, colors)?,
+                regions: Self::build_regions(region.regions, colors)?,
+                include: region.include,
+            });
+        }
+        Ok(rs)
+    }
+
+    /// Builds an optional regular expression from `pattern`.
+    fn build_regex(pattern: Option<String>) -> Result<Option<Regex>> {
+        pattern
+            .map(|pattern| Regex::new(&pattern).map_err(|e| Error::invalid_regex(&pattern, &e)))
+            .transpose()
     }
 }
 
 impl Default for Registry {
     fn default() -> Registry {
         Registry {
-            syntax_map: HashMap::new(),
+            syntax_map: BTreeMap::new(),
             re_list: Vec::new(),
+            first_list: Vec::new(),
         }
     }
 }
@@ -292,6 +923,7 @@ impl Default for Registry {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::color::ColorCode;
 
     const SYNTAX_NAME: &str = "foo";
 
@@ -311,7 +943,10 @@ pub mod tests {
             assert_eq!(token.id, i + 1);
             assert_eq!(token.name, format!("_{}", i + 1));
             assert_eq!(token.pattern, SYNTAX_TOKENS[i].0);
-            assert_eq!(token.color, SYNTAX_TOKENS[i].1);
+            assert_eq!(
+                token.style,
+                Style::from(ColorCode::Index(SYNTAX_TOKENS[i].1))
+            );
         }
     }
 
@@ -325,16 +960,311 @@ pub mod tests {
         assert_eq!(token.id, 1);
         assert_eq!(token.name, "_1");
         assert_eq!(token.pattern, Syntax::EMPTY_REGEX);
-        assert_eq!(token.color, 0);
+        assert_eq!(token.style, Style::default());
+    }
+
+    #[test]
+    fn indent_rules() {
+        let mut syntax = build_empty_syntax();
+        assert!(!syntax.is_indent("foo {"));
+        assert!(!syntax.is_dedent("    }"));
+        assert!(!syntax.has_dedent());
+
+        syntax.indent = Some(Regex::new(r"\{\s*$").unwrap());
+        syntax.dedent = Some(Regex::new(r"^\s*\}$").unwrap());
+        assert!(syntax.is_indent("foo {"));
+        assert!(syntax.is_indent("foo {  "));
+        assert!(!syntax.is_indent("foo { bar"));
+        assert!(syntax.has_dedent());
+        assert!(syntax.is_dedent("    }"));
+        assert!(!syntax.is_dedent("    } else"));
     }
 
     #[test]
     fn invalid_token() {
-        let tokens = vec![("(bad".to_string(), 0)];
+        let tokens = vec![("(bad".to_string(), Style::default())];
         let syntax = Syntax::new(SYNTAX_NAME.to_string(), tokens);
         assert!(syntax.is_err());
     }
 
+    #[test]
+    fn regions() {
+        let syntax = build_region_syntax();
+        let text = r#"x "a\"{foo}" y"#;
+
+        // String region is entered from root context.
+        let (rule, range) = syntax.find_at(Syntax::ROOT, text, 0).unwrap();
+        let Rule::Start(string) = rule else {
+            panic!("{rule:?}: expecting start of region");
+        };
+        assert_eq!(range, 2..3);
+        assert_eq!(
+            syntax.style(syntax.token_id(Syntax::ROOT, rule)),
+            Some(style(2))
+        );
+        assert_eq!(syntax.style(syntax.content_id(string)), Some(style(2)));
+
+        // Escape token applies only inside string region.
+        assert_eq!(
+            syntax.find_at(string, text, 3),
+            Some((Rule::Token(4), 4..6))
+        );
+
+        // Interpolation region includes root context of syntax, but with its own end
+        // pattern that takes precedence.
+        let (rule, range) = syntax.find_at(string, text, 6).unwrap();
+        let Rule::Start(interp) = rule else {
+            panic!("{rule:?}: expecting start of region");
+        };
+        assert_eq!(range, 6..7);
+        assert_eq!(syntax.content_id(interp), 0);
+        assert_eq!(syntax.style(syntax.token_id(string, rule)), Some(style(5)));
+        assert_eq!(
+            syntax.find_at(interp, text, 7),
+            Some((Rule::Token(2), 7..10))
+        );
+        assert_eq!(syntax.find_at(interp, text, 10), Some((Rule::End, 10..11)));
+        assert_eq!(syntax.find_at(string, text, 11), Some((Rule::End, 11..12)));
+        assert_eq!(syntax.find_at(Syntax::ROOT, text, 12), None);
+    }
+
+    #[test]
+    fn include_other_syntax() {
+        let other = Definition {
+            tokens: vec![(r"\bbaz\b".to_string(), style(6), false)],
+            regions: Vec::new(),
+        };
+        let def = Definition {
+            tokens: Vec::new(),
+            regions: vec![build_region("<<", ">>", None, Some("other"))],
+        };
+        let defs = HashMap::from([("other".to_string(), &other)]);
+        let syntax = Syntax::build(SYNTAX_NAME.to_string(), &def, &defs).unwrap();
+
+        let text = "baz << baz >> baz";
+        let (rule, _) = syntax.find_at(Syntax::ROOT, text, 0).unwrap();
+        let Rule::Start(ctx) = rule else {
+            panic!("{rule:?}: expecting start of region");
+        };
+        let (rule, range) = syntax.find_at(ctx, text, 6).unwrap();
+        assert_eq!(range, 7..10);
+        assert_eq!(syntax.style(syntax.token_id(ctx, rule)), Some(style(6)));
+
+        // Unknown syntax cannot be included.
+        let syntax = Syntax::build(SYNTAX_NAME.to_string(), &def, &HashMap::new());
+        assert!(syntax.is_err());
+    }
+
+    #[test]
+    fn modelines() {
+        assert_eq!(Registry::modeline("// ped: syntax=rust"), Some("rust"));
+        assert_eq!(
+            Registry::modeline("# ped:syntax = python3 "),
+            Some("python3")
+        );
+        assert_eq!(Registry::modeline("/* ped: syntax=c; */"), Some("c"));
+        assert_eq!(Registry::modeline("ped: syntax="), None);
+        assert_eq!(Registry::modeline("ped: tabs=4"), None);
+        assert_eq!(Registry::modeline("syntax=rust"), None);
+    }
+
+    #[test]
+    fn detect_syntax() {
+        let registry = build_registry();
+        let detect = |path: &str, text: &str| {
+            let mut buffer = Buffer::new();
+            buffer.insert_str(text);
+            registry
+                .detect(path, &buffer)
+                .map(|syntax| syntax.name.as_str())
+        };
+
+        assert_eq!(detect("foo.rs", "fn main() {}\n"), Some("Rust"));
+        assert_eq!(detect("foo", "#!/usr/bin/env python3\n"), Some("Python"));
+        assert_eq!(detect("foo.rs", "#!/usr/bin/env python3\n"), Some("Rust"));
+        assert_eq!(detect("foo", "text\n"), None);
+
+        // Modeline near start or end of file takes precedence, though only if the
+        // syntax is known.
+        assert_eq!(detect("foo.rs", "# ped: syntax=python\n"), Some("Python"));
+        let text = format!("{}# ped: syntax=PYTHON\n", "x\n".repeat(20));
+        assert_eq!(detect("foo.rs", &text), Some("Python"));
+        let text = format!("\n\n\n\n\n\n# ped: syntax=python{}", "\nx".repeat(20));
+        assert_eq!(detect("foo.rs", &text), Some("Rust"));
+        assert_eq!(detect("foo.rs", "# ped: syntax=cobol\n"), Some("Rust"));
+    }
+
+    #[test]
+    fn builtin_syntax() {
+        let registry = Registry::builtin(&Colors::default()).unwrap();
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            vec!["C", "JSON", "Markdown", "Python", "Rust", "Shell", "TOML", "YAML"]
+        );
+
+        let detect = |path: &str, text: &str| {
+            let mut buffer = Buffer::new();
+            buffer.insert_str(text);
+            registry
+                .detect(path, &buffer)
+                .map(|syntax| syntax.name.as_str())
+        };
+        assert_eq!(detect("main.rs", ""), Some("Rust"));
+        assert_eq!(detect("Cargo.toml", ""), Some("TOML"));
+        assert_eq!(detect("README.md", ""), Some("Markdown"));
+        assert_eq!(detect("foo", "#!/bin/bash\n"), Some("Shell"));
+        assert_eq!(detect("foo", "#!/usr/bin/env python3\n"), Some("Python"));
+
+        // Editor settings are optional.
+        assert!(registry.get("Rust").unwrap().settings().is_empty());
+        assert!(!registry.get("YAML").unwrap().settings().is_empty());
+
+        // Code fences in Markdown include other built-in syntaxes.
+        let syntax = registry.get("markdown").unwrap();
+        let text = "```rust\nfn main() {}\n```\n";
+        let (rule, range) = syntax.find_at(Syntax::ROOT, text, 0).unwrap();
+        let Rule::Start(ctx) = rule else {
+            panic!("{rule:?}: expecting start of region");
+        };
+        assert_eq!(range, 0..7);
+        let (rule, range) = syntax.find_at(ctx, text, 7).unwrap();
+        assert!(matches!(rule, Rule::Token(_)));
+        assert_eq!(range, 8..10);
+    }
+
+    #[test]
+    fn builtin_syntax_literals() {
+        let registry = Registry::builtin(&Colors::default()).unwrap();
+        let is_literal = |syntax: &Syntax, text: &str| {
+            let (rule, _) = syntax.find_at(Syntax::ROOT, text, 0).unwrap();
+            syntax.is_literal(Syntax::ROOT, syntax.token_id(Syntax::ROOT, rule))
+        };
+
+        // Comments and strings are literal in all built-in syntaxes that have them.
+        for name in registry.names() {
+            let syntax = registry.get(name).unwrap();
+            if let Some(comment) = syntax.line_comment() {
+                assert!(is_literal(syntax, &format!("{comment} (")), "{name}");
+            }
+            if let Some((start, end)) = syntax.block_comment() {
+                assert!(is_literal(syntax, &format!("{start} ( {end}")), "{name}");
+            }
+            if name != "Markdown" {
+                assert!(is_literal(syntax, "\"(\""), "{name}");
+            }
+        }
+
+        // Code spans and fences in Markdown are literal, though not other markup.
+        let syntax = registry.get("markdown").unwrap();
+        assert!(is_literal(syntax, "`(`"));
+        assert!(is_literal(syntax, "```\n(\n```\n"));
+        assert!(!is_literal(syntax, "[a](b)"));
+    }
+
+    #[test]
+    fn builtin_syntax_override() {
+        let colors = Colors::default();
+        let content = "[syntax]\nname = \"Rust\"\nfiles = ['\\.rs$']\n";
+        let config = Registry::parse_syntax("rust.toml", content, &colors).unwrap();
+        let registry = Registry::build_registry(vec![config], &colors).unwrap();
+        assert_eq!(registry.names().count(), Registry::BUILTIN_SYNTAX.len());
+
+        let syntax = registry.get("Rust").unwrap();
+        assert_eq!(syntax.find_at(Syntax::ROOT, "fn main() {}", 0), None);
+    }
+
+    #[test]
+    fn literal_tokens() {
+        let colors = Colors::default();
+        let content = r#"
+[syntax]
+name = "Foo"
+files = ['\.foo$']
+
+[tokens]
+'//.*' = { style = "green", literal = true }
+'\)\?' = { style = "red" }
+'\bfoo\b' = "blue"
+"#;
+        let (_, def) = Registry::parse_syntax("foo.toml", content, &colors).unwrap();
+        let syntax = Syntax::build("Foo".to_string(), &def, &HashMap::new()).unwrap();
+
+        // Only tokens marked as literal contain literal text.
+        let text = "foo)? // bar";
+        let literals = [0, 3, 6]
+            .iter()
+            .map(|&pos| {
+                let (rule, _) = syntax.find_at(Syntax::ROOT, text, pos).unwrap();
+                syntax.is_literal(Syntax::ROOT, syntax.token_id(Syntax::ROOT, rule))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(literals, vec![false, false, true]);
+
+        // Token tables accept only known keys.
+        let content =
+            "[syntax]\nname = \"Foo\"\nfiles = []\n[tokens]\n'x' = { style = \"red\", foo = 1 }\n";
+        assert!(Registry::parse_syntax("foo.toml", content, &colors).is_err());
+    }
+
+    #[test]
+    fn literal_regions() {
+        let colors = Colors::default();
+        let content = r#"
+[syntax]
+name = "Foo"
+files = ['\.foo$']
+
+[[regions]]
+start = '"'
+end = '"'
+style = "yellow"
+literal = true
+tokens = { '\\.' = "red" }
+
+[[regions]]
+start = '<'
+end = '>'
+style = "blue"
+tokens = { '\\.' = "red" }
+"#;
+        let (_, def) = Registry::parse_syntax("foo.toml", content, &colors).unwrap();
+        let syntax = Syntax::build("Foo".to_string(), &def, &HashMap::new()).unwrap();
+
+        // Delimiters, tokens, and unmatched text inside literal regions are literal.
+        let text = r#""a\n" <a\n>"#;
+        for (pos, literal) in [(0, true), (6, false)] {
+            let (rule, _) = syntax.find_at(Syntax::ROOT, text, pos).unwrap();
+            let Rule::Start(ctx) = rule else {
+                panic!("{rule:?}: expecting start of region");
+            };
+            let id = syntax.token_id(Syntax::ROOT, rule);
+            assert_eq!(syntax.is_literal(Syntax::ROOT, id), literal);
+            assert_eq!(syntax.is_literal(ctx, 0), literal);
+            let (rule, _) = syntax.find_at(ctx, text, pos + 2).unwrap();
+            assert_eq!(syntax.is_literal(ctx, syntax.token_id(ctx, rule)), literal);
+        }
+    }
+
+    fn build_registry() -> Registry {
+        let mut registry = Registry::default();
+        for (name, file, first_line) in [
+            ("Rust", r"\.rs$", None),
+            ("Python", r"\.py$", Some(r"^#!.*\bpython3?\b")),
+        ] {
+            let syntax = Syntax::new(name.to_string(), Vec::new()).unwrap();
+            registry.syntax_map.insert(name.to_string(), syntax);
+            registry
+                .re_list
+                .push((Regex::new(file).unwrap(), name.to_string()));
+            if let Some(pattern) = first_line {
+                registry
+                    .first_list
+                    .push((Regex::new(pattern).unwrap(), name.to_string()));
+            }
+        }
+        registry
+    }
+
     pub fn build_syntax() -> Syntax {
         Syntax::new(SYNTAX_NAME.to_string(), build_tokens()).unwrap()
     }
@@ -343,10 +1273,86 @@ pub mod tests {
         Syntax::new(SYNTAX_NAME.to_string(), Vec::new()).unwrap()
     }
 
-    fn build_tokens() -> Vec<(String, u8)> {
+    /// Builds a syntax containing a literal string token and a non-literal attribute
+    /// token of the form `#[...]`, both of which may contain brackets.
+    pub fn build_literal_syntax() -> Syntax {
+        let def = Definition {
+            tokens: vec![
+                (r#""[^"\n]*""#.to_string(), style(2), true),
+                (r"#\[[^\]\n]*\]".to_string(), style(3), false),
+            ],
+            regions: Vec::new(),
+        };
+        Syntax::build(SYNTAX_NAME.to_string(), &def, &HashMap::new()).unwrap()
+    }
+
+    /// Builds a syntax similar to [`build_syntax`], but whose string token is instead
+    /// a region containing escape sequences and interpolated expressions that include
+    /// the syntax itself.
+    pub fn build_region_syntax() -> Syntax {
+        let mut string = build_region("\"", "\"", Some(style(2)), None);
+        string.literal = true;
+        string.tokens = vec![(r"\\.".to_string(), style(4), false)];
+        string.regions = vec![Region {
+            delimiter_style: Some(style(5)),
+            ..build_region(r"\{", r"\}", None, Some(SYNTAX_NAME))
+        }];
+        let tokens = build_tokens()
+            .into_iter()
+            .map(|(pattern, style)| (pattern, style, false))
+            .collect::<Vec<_>>();
+        let def = Definition {
+            tokens: vec![tokens[0].clone(), tokens[2].clone()],
+            regions: vec![string],
+        };
+        Syntax::build(SYNTAX_NAME.to_string(), &def, &HashMap::new()).unwrap()
+    }
+
+    /// Builds a syntax similar to [`build_empty_syntax`], but with line comments of
+    /// the form `// ...`.
+    pub fn build_line_comment_syntax() -> Syntax {
+        let mut syntax = build_empty_syntax();
+        syntax.line_comment = Some("//".to_string());
+        syntax
+    }
+
+    /// Builds a syntax similar to [`build_empty_syntax`], but with block comments of
+    /// the form `/* ... */`.
+    pub fn build_block_comment_syntax() -> Syntax {
+        let mut syntax = build_empty_syntax();
+        syntax.block_comment = Some(("/*".to_string(), "*/".to_string()));
+        syntax
+    }
+
+    /// Builds a syntax similar to [`build_empty_syntax`], but whose settings are
+    /// given by `content` in the form of a `[settings]` table.
+    pub fn build_settings_syntax(content: &str) -> Syntax {
+        let mut syntax = build_empty_syntax();
+        syntax.settings = toml::from_str(content).unwrap();
+        syntax
+    }
+
+    fn build_region(start: &str, end: &str, style: Option<Style>, include: Option<&str>) -> Region {
+        Region {
+            start: start.to_string(),
+            end: end.to_string(),
+            style,
+            delimiter_style: None,
+            literal: false,
+            tokens: Vec::new(),
+            regions: Vec::new(),
+            include: include.map(|name| name.to_string()),
+        }
+    }
+
+    fn style(color: u8) -> Style {
+        Style::from(ColorCode::Index(color))
+    }
+
+    fn build_tokens() -> Vec<(String, Style)> {
         SYNTAX_TOKENS
             .iter()
-            .map(|(token, color)| (token.to_string(), *color))
+            .map(|(token, color)| (token.to_string(), Style::from(ColorCode::Index(*color))))
             .collect()
     }
 }
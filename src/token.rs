@@ -1,12 +1,16 @@
 //! Tokenization for syntax coloring.
 
 use crate::buffer::Buffer;
+use crate::color::Style;
 use crate::etc;
-use crate::syntax::Syntax;
+use crate::syntax::{Rule, Syntax};
 use std::cell::RefCell;
 use std::cmp;
+use std::collections::HashMap;
 use std::ops::{ControlFlow, Range};
 use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
 
 /// A means of tokenizing the contents of a [`Buffer`].
 pub struct Tokenizer {
@@ -18,6 +22,17 @@ pub struct Tokenizer {
 
     /// The list of token spans generated during tokenization.
     spans: Vec<Span>,
+
+    /// The context stacks referenced by [`spans`](Self::spans).
+    stacks: Stacks,
+
+    /// The range of positions affected by changes since the last tokenization, or
+    /// `None` if no changes occurred.
+    dirty: Option<Range<usize>>,
+
+    /// Indicates that spans do not reflect the contents of the buffer, which is the
+    /// case when tokenization is deferred following a [`reset`](Self::reset).
+    approximate: bool,
 }
 
 pub type TokenizerRef = Rc<RefCell<Tokenizer>>;
@@ -32,9 +47,9 @@ pub struct Cursor {
     /// The applicable token corresponding to [`pos`](Self::pos).
     token: Token,
 
-    /// The foreground color associated with this token or `None` if the token
-    /// represents a gap.
-    color: Option<u8>,
+    /// The style associated with this token or `None` if the token represents a
+    /// gap that is not contained in a styled region.
+    style: Option<Style>,
 }
 
 /// A token is essentially a [`Span`] that is decorated with the starting and ending
@@ -51,19 +66,98 @@ struct Token {
     end_pos: usize,
 }
 
-/// A span represents a slice of text that matchs a token `id`.
+/// A background job that tokenizes a snapshot of a [`Buffer`], started via
+/// [`Tokenizer::spawn`].
+pub struct Job {
+    receiver: Receiver<Tokens>,
+}
+
+/// The state of a background [`Job`].
+pub enum Progress {
+    /// Tokenization is still in progress.
+    Pending,
+
+    /// Tokenization finished with the given result.
+    Finished(Tokens),
+
+    /// The background thread terminated without producing a result.
+    Failed,
+}
+
+/// The result of tokenizing a snapshot of a [`Buffer`] in the background, which is
+/// applied via [`Tokenizer::accept`].
+pub struct Tokens {
+    /// The number of characters in the snapshot.
+    chars: usize,
+
+    /// The list of token spans generated during tokenization.
+    spans: Vec<Span>,
+
+    /// The context stacks referenced by [`spans`](Self::spans).
+    stacks: Stacks,
+}
+
+/// A span represents a slice of text that matchs a token `id`, along with the stack
+/// of contexts in effect at the start of the span.
 struct Span {
     id: usize,
     len: usize,
+    stack: usize,
+}
+
+/// An interned collection of context stacks, which allows spans to refer to stacks
+/// by index. The stack at index `0` contains only the root context.
+struct Stacks {
+    list: Vec<Vec<usize>>,
+    map: HashMap<Vec<usize>, usize>,
 }
 
 impl Span {
-    fn gap(len: usize) -> Span {
-        Span { id: 0, len }
+    fn gap(len: usize, stack: usize) -> Span {
+        Span { id: 0, len, stack }
+    }
+
+    fn token(id: usize, len: usize, stack: usize) -> Span {
+        Span { id, len, stack }
+    }
+}
+
+impl Stacks {
+    fn new() -> Stacks {
+        let mut this = Stacks {
+            list: Vec::new(),
+            map: HashMap::new(),
+        };
+        this.intern(&[Syntax::ROOT]);
+        this
     }
 
-    fn token(id: usize, len: usize) -> Span {
-        Span { id, len }
+    /// Returns the index of `stack`, adding it if not already present.
+    fn intern(&mut self, stack: &[usize]) -> usize {
+        if let Some(index) = self.map.get(stack) {
+            *index
+        } else {
+            let index = self.list.len();
+            self.list.push(stack.to_vec());
+            self.map.insert(stack.to_vec(), index);
+            index
+        }
+    }
+
+    /// Returns the stack at `index`.
+    fn get(&self, index: usize) -> &[usize] {
+        &self.list[index]
+    }
+}
+
+impl Job {
+    /// Returns the progress of the job without blocking.
+    pub fn poll(&self) -> Progress {
+        match self.receiver.try_recv() {
+            Ok(tokens) => Progress::Finished(tokens),
+            Err(TryRecvError::Empty) => Progress::Pending,
+            Err(TryRecvError::Disconnected) => Progress::Failed,
+        }
     }
 }
 
@@ -75,21 +169,32 @@ impl Token {
 }
 
 impl Cursor {
-    /// Returns the applicable foreground color at this cursor position or `None` if
-    /// the cursor is contained inside a gap.
+    /// Returns the applicable style at this cursor position or `None` if the cursor
+    /// is contained inside a gap that is not part of a styled region.
     #[inline(always)]
-    pub fn color(&self) -> Option<u8> {
-        self.color
+    pub fn style(&self) -> Option<Style> {
+        self.style
     }
 }
 
 impl Tokenizer {
+    /// Maximum depth of nested regions.
+    const STACK_LIMIT: usize = 32;
+
+    /// Initial number of characters beyond the last change that are scanned during
+    /// incremental tokenization, which doubles until new spans realign with existing
+    /// spans.
+    const SCAN_WINDOW: usize = 4096;
+
     /// Creates a new tokenizer using the `syntax` configuration.
     pub fn new(syntax: Syntax) -> Tokenizer {
         Tokenizer {
             syntax,
             chars: 0,
             spans: Vec::new(),
+            stacks: Stacks::new(),
+            dirty: None,
+            approximate: false,
         }
     }
 
@@ -103,52 +208,307 @@ impl Tokenizer {
         &self.syntax
     }
 
+    /// Returns `true` if spans do not reflect the contents of the buffer because
+    /// tokenization was deferred.
+    pub fn is_approximate(&self) -> bool {
+        self.approximate
+    }
+
     /// Tokenizes `buffer` and returns a cursor at position `0`.
+    ///
+    /// If `buffer` was previously tokenized, then only the region affected by changes
+    /// since recorded via [`insert`](Self::insert) and [`remove`](Self::remove) is
+    /// tokenized again. Scanning resumes from the start of the span preceding the one
+    /// containing the character before the earliest change, and stops once new tokens
+    /// realign with existing tokens following the latest change.
     pub fn tokenize(&mut self, buffer: &Buffer) -> Cursor {
+        if self.spans.is_empty() || self.chars != buffer.size() {
+            self.tokenize_all(buffer)
+        } else {
+            if let Some(dirty) = self.dirty.take() {
+                // A change at the start of a span might affect the prior token, such
+                // as one that requires a word boundary, hence the character preceding
+                // the change. Scanning must also include the preceding span, since a
+                // new token could start inside a preceding gap, and the match of a
+                // preceding token might depend on the text that follows. Scanning
+                // therefore starts where the text of an entire span is unchanged, and
+                // realignment is verified against existing spans.
+                let cursor = self.find(self.origin(), dirty.start.saturating_sub(1));
+                let Token {
+                    index, start_pos, ..
+                } = cursor.token;
+                let (index, pos) = if index > 0 {
+                    (index - 1, start_pos - self.spans[index - 1].len)
+                } else {
+                    (index, start_pos)
+                };
+                self.scan(buffer, index, pos, dirty.end);
+            }
+            self.origin()
+        }
+    }
+
+    /// Tokenizes the entire `buffer` and returns a cursor at position `0`.
+    pub fn tokenize_all(&mut self, buffer: &Buffer) -> Cursor {
         self.spans.clear();
+        self.stacks = Stacks::new();
         self.chars = buffer.size();
-        if self.chars > 0 {
-            // Converting entire buffer to string is an unfortunate requirement since
-            // regex library provide iterator support.
-            let buf = buffer.iter().collect::<String>();
-
-            // Keep track of byte offset and character position following last span.
-            let mut offset = 0;
-            let mut pos = 0;
-
-            for cap in self.syntax.re.captures_iter(&buf) {
-                // Get token information associated with capture group.
-                let (id, Range { start, end }) = self.syntax.lookup(&cap);
-
-                // Byte offsets returned by regex library must be converted to their
-                // corresponding character positions.
-                let start_pos = pos + etc::offset_to_pos(&buf[offset..], start - offset);
-                let end_pos = start_pos + etc::offset_to_pos(&buf[start..], end - start);
-
-                // Insert gap span if non-zero distance exists between this token and
-                // prior token.
-                if start_pos > pos {
-                    self.spans.push(Span::gap(start_pos - pos));
-                }
+        self.scan(buffer, 0, 0, usize::MAX);
+        self.dirty = None;
+        self.approximate = false;
 
-                // Add new token span.
-                self.spans.push(Span::token(id, end_pos - start_pos));
-                offset = end;
-                pos = end_pos;
+        // Return cursor at position 0.
+        self.origin()
+    }
+
+    /// Resets the tokenizer such that `buffer` is covered by a single gap, which is
+    /// useful when tokenization of `buffer` is deferred, and returns a cursor at
+    /// position `0`.
+    ///
+    /// Spans are considered approximate until tokenization is performed.
+    pub fn reset(&mut self, buffer: &Buffer) -> Cursor {
+        self.chars = buffer.size();
+        self.spans = vec![Span::gap(self.chars, 0)];
+        self.stacks = Stacks::new();
+        self.dirty = None;
+        self.approximate = true;
+        self.origin()
+    }
+
+    /// Starts tokenizing a snapshot of `buffer` on a background thread, returning a
+    /// job whose result is applied via [`accept`](Self::accept).
+    pub fn spawn(&self, buffer: &Buffer) -> Job {
+        let syntax = self.syntax.clone();
+        let text = buffer.iter().collect::<String>();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let chars = text.chars().count();
+            let mut stacks = Stacks::new();
+            let (spans, _) = Self::scan_text(&syntax, &mut stacks, &text, 0, 0, 0, |_, _, _| false);
+            // Job may have been dropped, in which case the result is discarded.
+            let _ = sender.send(Tokens {
+                chars,
+                spans,
+                stacks,
+            });
+        });
+        Job { receiver }
+    }
+
+    /// Replaces all spans with `tokens` produced by a background job using a prior
+    /// snapshot of `buffer`, and returns a cursor at position `0`.
+    ///
+    /// Each of `changes` made to `buffer` since the snapshot was taken, expressed as
+    /// a tuple of position, length, and whether text was inserted or removed, is
+    /// applied to spans in order, followed by incremental tokenization of the
+    /// affected regions.
+    pub fn accept<I>(&mut self, tokens: Tokens, buffer: &Buffer, changes: I) -> Cursor
+    where
+        I: IntoIterator<Item = (usize, usize, bool)>,
+    {
+        self.chars = tokens.chars;
+        self.spans = tokens.spans;
+        self.stacks = tokens.stacks;
+        if self.spans.is_empty() {
+            self.spans.push(Span::gap(0, 0));
+        }
+        self.dirty = None;
+        self.approximate = false;
+
+        let mut cursor = self.origin();
+        for (pos, len, inserted) in changes {
+            cursor = self.find(cursor, pos);
+            cursor = if inserted {
+                self.insert(cursor, len)
+            } else {
+                self.remove(cursor, len)
+            };
+        }
+        self.tokenize(buffer)
+    }
+
+    /// Scans `buffer` starting at `pos`, which is also the starting position of the
+    /// span at `index`, and replaces existing spans with the newly generated spans.
+    ///
+    /// Scanning stops at the first token starting at or beyond `realign_pos` whose
+    /// identity and length match the existing span at the same position, as all
+    /// subsequent spans are known to be unaffected. Otherwise, scanning continues to
+    /// the end of the buffer.
+    ///
+    /// Only a window of the buffer extending [`SCAN_WINDOW`](Self::SCAN_WINDOW)
+    /// characters beyond `realign_pos` is initially scanned, and if realignment is
+    /// not detected within that window, the window doubles in size and scanning is
+    /// repeated.
+    fn scan(&mut self, buffer: &Buffer, index: usize, pos: usize, realign_pos: usize) {
+        // Scanning resumes with the context stack in effect at the start of the span.
+        let stack = self.spans.get(index).map_or(0, |span| span.stack);
+        let start_pos = pos.saturating_sub(1);
+        let mut window = Self::SCAN_WINDOW;
+        let (spans, end_index) = loop {
+            let end_pos = cmp::min(realign_pos.saturating_add(window), buffer.size());
+            let bounded = end_pos < buffer.size();
+
+            // Converting window of buffer to string is an unfortunate requirement
+            // since regex library does not provide iterator support. A single
+            // character of leading context is included so assertions, such as word
+            // boundaries, are evaluated correctly.
+            let buf = buffer
+                .forward(start_pos)
+                .take(end_pos - start_pos)
+                .collect::<String>();
+            let context = if pos > 0 {
+                buf.chars().next().map_or(0, |c| c.len_utf8())
+            } else {
+                0
+            };
+
+            // Keep track of position of existing span used for detecting realignment,
+            // though tokens ending near the edge of the window are never considered
+            // since their match might depend on text beyond the window.
+            let mut old_index = index;
+            let mut old_pos = pos;
+            let (spans, realigned) = {
+                let old_spans = &self.spans;
+                let realign = |start_pos, span: &Span, next_stack| {
+                    if start_pos >= realign_pos && (!bounded || start_pos + span.len < end_pos) {
+                        while old_index < old_spans.len()
+                            && old_pos + old_spans[old_index].len <= start_pos
+                        {
+                            old_pos += old_spans[old_index].len;
+                            old_index += 1;
+                        }
+                        old_index < old_spans.len()
+                            && old_pos == start_pos
+                            && old_spans[old_index].id == span.id
+                            && old_spans[old_index].len == span.len
+                            && old_spans[old_index].stack == span.stack
+                            && old_spans
+                                .get(old_index + 1)
+                                .is_none_or(|next| next.stack == next_stack)
+                    } else {
+                        false
+                    }
+                };
+                Self::scan_text(
+                    &self.syntax,
+                    &mut self.stacks,
+                    &buf,
+                    context,
+                    pos,
+                    stack,
+                    realign,
+                )
+            };
+            if realigned {
+                break (spans, old_index);
+            } else if !bounded {
+                break (spans, self.spans.len());
             }
+            window *= 2;
+        };
+        self.spans.splice(index..end_index, spans);
+
+        // An empty buffer requires zero-length gap to be appended to spans to ensure
+        // other functions work correctly.
+        if self.spans.is_empty() {
+            self.spans.push(Span::gap(0, 0));
+        }
+    }
 
-            // Add gap span if non-zero distance between last token and end of buffer.
-            if offset < buf.len() {
-                let end_pos = pos + etc::offset_to_pos(&buf[offset..], buf.len() - offset);
-                self.spans.push(Span::gap(end_pos - pos));
+    /// Scans `buf` starting at byte `offset`, which corresponds to position `pos`,
+    /// using the context stack at index `stack` in `stacks`, and returns the spans
+    /// generated along with an indication of whether scanning was stopped early.
+    ///
+    /// The `stop` function is given the position of each token span prior to being
+    /// added, along with the context stack in effect following the token, and
+    /// returning `true` stops scanning. Otherwise, scanning continues to the end of
+    /// `buf`, including a trailing gap if needed.
+    fn scan_text<F>(
+        syntax: &Syntax,
+        stacks: &mut Stacks,
+        buf: &str,
+        offset: usize,
+        pos: usize,
+        stack: usize,
+        mut stop: F,
+    ) -> (Vec<Span>, bool)
+    where
+        F: FnMut(usize, &Span, usize) -> bool,
+    {
+        // Keep track of byte offset and character position following last span, as
+        // well as byte offset where matching resumes, which differs only after an
+        // empty match.
+        let mut offset = offset;
+        let mut pos = pos;
+        let mut next = offset;
+        let mut spans = Vec::new();
+
+        // Keep track of context stack, which changes upon entering and leaving
+        // regions, along with its interned index.
+        let mut contexts = stacks.get(stack).to_vec();
+        let mut stack = stack;
+        let mut ctx = *contexts.last().unwrap_or(&Syntax::ROOT);
+
+        while let Some((rule, Range { start, end })) = syntax.find_at(ctx, buf, next) {
+            let id = syntax.token_id(ctx, rule);
+
+            // Byte offsets returned by regex library must be converted to their
+            // corresponding character positions.
+            let start_pos = pos + etc::offset_to_pos(&buf[offset..], start - offset);
+            let end_pos = start_pos + etc::offset_to_pos(&buf[start..], end - start);
+
+            // Insert gap span if non-zero distance exists between this token and
+            // prior token.
+            if start_pos > pos {
+                spans.push(Span::gap(start_pos - pos, stack));
             }
-        } else {
-            // An empty buffer requires zero-length gap to be appended to spans to
-            // ensure other functions work correctly.
-            self.spans.push(Span::gap(0));
+            // Determine context stack following token, which changes upon entering
+            // or leaving a region, though nesting is bounded to guard against
+            // runaway recursion.
+            let span = Span::token(id, end_pos - start_pos, stack);
+            match rule {
+                Rule::Start(c) if contexts.len() < Self::STACK_LIMIT => {
+                    contexts.push(c);
+                    stack = stacks.intern(&contexts);
+                    ctx = c;
+                }
+                Rule::End if contexts.len() > 1 => {
+                    contexts.pop();
+                    stack = stacks.intern(&contexts);
+                    ctx = *contexts.last().unwrap_or(&Syntax::ROOT);
+                }
+                _ => {}
+            }
+            if stop(start_pos, &span, stack) {
+                return (spans, true);
+            }
+
+            // Add new token span, though an empty match requires that scanning
+            // resume at the next character to avoid an infinite loop.
+            spans.push(span);
+            offset = end;
+            pos = end_pos;
+            next = if end > start {
+                end
+            } else if let Some(c) = buf[end..].chars().next() {
+                end + c.len_utf8()
+            } else {
+                break;
+            };
         }
 
-        // Return cursor at position 0.
+        // Add gap span if non-zero distance between last token and end of buffer.
+        if offset < buf.len() {
+            spans.push(Span::gap(
+                etc::offset_to_pos(&buf[offset..], buf.len() - offset),
+                stack,
+            ));
+        }
+        (spans, false)
+    }
+
+    /// Returns a cursor at position `0`.
+    pub fn origin(&self) -> Cursor {
         Cursor {
             pos: 0,
             token: Token {
@@ -156,7 +516,7 @@ impl Tokenizer {
                 start_pos: 0,
                 end_pos: self.spans[0].len,
             },
-            color: self.color(0),
+            style: self.style(0),
         }
     }
 
@@ -171,11 +531,11 @@ impl Tokenizer {
             } else {
                 self.find_forward(cursor.token, pos)
             };
-            let color = self.color(token.index);
+            let style = self.style(token.index);
             Cursor {
                 pos,
                 token,
-                color,
+                style,
                 ..cursor
             }
         }
@@ -203,7 +563,7 @@ impl Tokenizer {
             self.spans
                 .iter()
                 .skip(from.index + 1)
-                .try_fold(from, |token, Span { id: _, len }| {
+                .try_fold(from, |token, Span { len, .. }| {
                     if pos >= token.end_pos {
                         ControlFlow::Continue(Token {
                             index: token.index + 1,
@@ -225,20 +585,22 @@ impl Tokenizer {
     /// If `pos` does occur _before_ `from`, then this function will panic.
     fn find_backward(&self, from: Token, pos: usize) -> Token {
         debug_assert!(pos <= from.start_pos);
-        let result = self.spans.iter().take(from.index).rev().try_fold(
-            from,
-            |token, Span { id: _, len }| {
-                if pos < token.start_pos {
-                    ControlFlow::Continue(Token {
-                        index: token.index - 1,
-                        start_pos: token.start_pos - len,
-                        end_pos: token.start_pos,
-                    })
-                } else {
-                    ControlFlow::Break(token)
-                }
-            },
-        );
+        let result =
+            self.spans
+                .iter()
+                .take(from.index)
+                .rev()
+                .try_fold(from, |token, Span { len, .. }| {
+                    if pos < token.start_pos {
+                        ControlFlow::Continue(Token {
+                            index: token.index - 1,
+                            start_pos: token.start_pos - len,
+                            end_pos: token.start_pos,
+                        })
+                    } else {
+                        ControlFlow::Break(token)
+                    }
+                });
         match result {
             ControlFlow::Break(token) => token,
             ControlFlow::Continue(token) => token,
@@ -249,6 +611,14 @@ impl Tokenizer {
     /// of the underlying span, returning a new cursor at the same position.
     pub fn insert(&mut self, cursor: Cursor, len: usize) -> Cursor {
         if len > 0 {
+            self.dirty = Some(match self.dirty.take() {
+                Some(Range { start, end }) => {
+                    let end = if end >= cursor.pos { end + len } else { end };
+                    cmp::min(start, cursor.pos)..cmp::max(end, cursor.pos + len)
+                }
+                None => cursor.pos..cursor.pos + len,
+            });
+
             let token = &cursor.token;
             self.spans[token.index].len += len;
             self.chars += len;
@@ -273,6 +643,17 @@ impl Tokenizer {
             // length may be less if number of characters would extend beyond end.
             let end_cursor = self.find(cursor, cursor.pos + len);
             let len = end_cursor.pos - cursor.pos;
+            self.dirty = Some(match self.dirty.take() {
+                Some(Range { start, end }) => {
+                    let end = if end > cursor.pos {
+                        end - cmp::min(end - cursor.pos, len)
+                    } else {
+                        end
+                    };
+                    cmp::min(start, cursor.pos)..cmp::max(end, cursor.pos)
+                }
+                None => cursor.pos..cursor.pos,
+            });
             let token = &cursor.token;
             let end_token = &end_cursor.token;
 
@@ -288,6 +669,7 @@ impl Tokenizer {
                 // Removal includes at least one span but possibly many. Evaluate
                 // starting and ending boundaries to trim and/or include their
                 // corresponding spans for removal.
+                let start_stack = self.spans[token.index].stack;
                 let start_index = if cursor.pos > token.start_pos {
                     self.spans[token.index].len = cursor.pos - token.start_pos;
                     token.index + 1
@@ -310,10 +692,16 @@ impl Tokenizer {
 
                     // At least one span must always exist.
                     if self.spans.len() == 0 {
-                        self.spans.push(Span::gap(0));
+                        self.spans.push(Span::gap(0, 0));
                     }
                 }
 
+                // If start token was entirely removed, then the next token now starts
+                // at the same position, so it inherits the context stack.
+                if start_index == token.index && start_index < self.spans.len() {
+                    self.spans[start_index].stack = start_stack;
+                }
+
                 if start_index < self.spans.len() {
                     // Because start token is either truncated or entirely removed,
                     // start position of next token is always cursor position.
@@ -338,25 +726,40 @@ impl Tokenizer {
             Cursor {
                 pos: cursor.pos,
                 token,
-                color: self.color(token.index),
+                style: self.style(token.index),
             }
         } else {
             cursor
         }
     }
 
-    /// Returns the foreground color associated with the span at `index` or `None` if
-    /// the span is a gap.
-    fn color(&self, index: usize) -> Option<u8> {
-        let Span { id, len: _ } = self.spans[index];
-        self.syntax.color(id)
+    /// Returns `true` if `cursor` is contained inside literal text, such as a string
+    /// or comment, as marked by the syntax.
+    pub fn is_literal(&self, cursor: Cursor) -> bool {
+        let Span { id, stack, .. } = self.spans[cursor.token.index];
+        let ctx = *self.stacks.get(stack).last().unwrap_or(&Syntax::ROOT);
+        self.syntax.is_literal(ctx, id)
+    }
+
+    /// Returns the style associated with the span at `index`, which for gaps is
+    /// the style of unmatched text in the innermost context, if any.
+    fn style(&self, index: usize) -> Option<Style> {
+        let Span { id, stack, .. } = self.spans[index];
+        let id = if id > 0 {
+            id
+        } else {
+            let ctx = *self.stacks.get(stack).last().unwrap_or(&Syntax::ROOT);
+            self.syntax.content_id(ctx)
+        };
+        self.syntax.style(id)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::syntax::tests::{build_empty_syntax, build_syntax};
+    use crate::color::ColorCode;
+    use crate::syntax::tests::{build_empty_syntax, build_region_syntax, build_syntax};
 
     const TOKENS: [(&str, u8); 3] = [
         (r#"-?\d+(?:\.\d+)?(?:[eE]-?\d+)?"#, 1),
@@ -448,10 +851,37 @@ mod tests {
             assert!(cursor.token.start_pos <= p.0);
             assert!(cursor.token.end_pos > p.0);
             assert_eq!(cursor.token.end_pos - cursor.token.start_pos, len);
-            assert_eq!(cursor.color, color_of(id));
+            assert_eq!(cursor.style, style_of(id));
         }
     }
 
+    #[test]
+    fn cursor_literal() {
+        // Tokens are not literal unless marked as such by the syntax.
+        let mut tz = build_tokenizer();
+        let buf = build_buffer();
+        let mut cursor = tz.tokenize(&buf);
+        while cursor.pos < tz.chars {
+            assert!(!tz.is_literal(cursor));
+            cursor = tz.forward(cursor, 1);
+        }
+
+        // Text inside the literal string region is literal, including delimiters
+        // and escapes, but not interpolated expressions or their delimiters.
+        let mut tz = Tokenizer::new(build_region_syntax());
+        let mut buf = Buffer::new();
+        buf.insert_str("foo \"a\\\"{bar 1}\" 2");
+        let cursor = tz.tokenize(&buf);
+        let literals = (0..buf.size())
+            .map(|pos| tz.is_literal(tz.find(cursor, pos)))
+            .collect::<Vec<_>>();
+        let mut expect = vec![false; buf.size()];
+        for pos in [4, 5, 6, 7, 15] {
+            expect[pos] = true;
+        }
+        assert_eq!(literals, expect);
+    }
+
     #[test]
     fn cursor_forward() {
         let mut tz = build_tokenizer();
@@ -464,7 +894,7 @@ mod tests {
             assert!(cursor.token.start_pos <= cursor.pos);
             assert!(cursor.token.end_pos > cursor.pos);
             assert_eq!(cursor.token.end_pos - cursor.token.start_pos, len);
-            assert_eq!(cursor.color, color_of(id));
+            assert_eq!(cursor.style, style_of(id));
             cursor = tz.forward(cursor, 1);
         }
     }
@@ -488,7 +918,7 @@ mod tests {
             }
 
             assert_eq!(cursor.token.end_pos - cursor.token.start_pos, len);
-            assert_eq!(cursor.color, color_of(id));
+            assert_eq!(cursor.style, style_of(id));
             cursor = tz.backward(cursor, 1);
         }
     }
@@ -517,7 +947,7 @@ mod tests {
         assert_eq!(cursor.pos, POS);
         assert_eq!(cursor.token.start_pos, POS);
         assert_eq!(cursor.token.end_pos, POS + len + LEN);
-        assert_eq!(cursor.color, color_of(id));
+        assert_eq!(cursor.style, style_of(id));
     }
 
     #[test]
@@ -545,7 +975,7 @@ mod tests {
         assert_eq!(cursor.pos, POS);
         assert_eq!(cursor.token.start_pos, START_POS);
         assert_eq!(cursor.token.end_pos, START_POS + len + LEN);
-        assert_eq!(cursor.color, color_of(id));
+        assert_eq!(cursor.style, style_of(id));
     }
 
     #[test]
@@ -573,7 +1003,7 @@ mod tests {
         assert_eq!(cursor.pos, POS);
         assert_eq!(cursor.token.start_pos, START_POS);
         assert_eq!(cursor.token.end_pos, START_POS + (len - LEN));
-        assert_eq!(cursor.color, color_of(id));
+        assert_eq!(cursor.style, style_of(id));
     }
 
     #[test]
@@ -600,7 +1030,7 @@ mod tests {
         assert_eq!(cursor.pos, POS);
         assert_eq!(cursor.token.start_pos, POS);
         assert_eq!(cursor.token.end_pos, POS + len);
-        assert_eq!(cursor.color, color_of(id));
+        assert_eq!(cursor.style, style_of(id));
     }
 
     #[test]
@@ -627,7 +1057,7 @@ mod tests {
         assert_eq!(cursor.pos, POS);
         assert_eq!(cursor.token.start_pos, POS);
         assert_eq!(cursor.token.end_pos, POS + len);
-        assert_eq!(cursor.color, color_of(id));
+        assert_eq!(cursor.style, style_of(id));
     }
 
     #[test]
@@ -660,7 +1090,7 @@ mod tests {
         assert_eq!(cursor.pos, POS);
         assert_eq!(cursor.token.start_pos, POS);
         assert_eq!(cursor.token.end_pos, POS + len);
-        assert_eq!(cursor.color, color_of(id));
+        assert_eq!(cursor.style, style_of(id));
     }
 
     #[test]
@@ -689,6 +1119,234 @@ mod tests {
         assert_eq!(cursor.pos, 0);
     }
 
+    #[test]
+    fn tokenize_incremental_insert() {
+        // Tuples of (pos, text) insertions, each of which is applied to the buffer
+        // produced by prior insertions.
+        const INSERTS: [(usize, &str); 8] = [
+            (9, "3"),
+            (0, "foo "),
+            (28, "\""),
+            (29, "closed\" "),
+            (38, "bar"),
+            (3, "d"),
+            (58, "7"),
+            (62, "\"unterminated"),
+        ];
+
+        let mut tz = build_tokenizer();
+        let mut buf = build_buffer();
+        let mut cursor = tz.tokenize(&buf);
+        for (pos, text) in INSERTS {
+            buf.set_pos(pos);
+            buf.insert_str(text);
+            cursor = tz.find(cursor, pos);
+            tz.insert(cursor, text.chars().count());
+            cursor = tz.tokenize(&buf);
+            assert_spans(&tz, &buf);
+        }
+    }
+
+    #[test]
+    fn tokenize_incremental_remove() {
+        // Pairs of (pos, len) removals, each of which is applied to the buffer
+        // produced by prior removals.
+        const REMOVES: [(usize, usize); 7] =
+            [(28, 1), (6, 2), (9, 4), (0, 5), (12, 6), (3, 1), (0, 30)];
+
+        let mut tz = build_tokenizer();
+        let mut buf = build_buffer();
+        let mut cursor = tz.tokenize(&buf);
+        for (pos, len) in REMOVES {
+            buf.set_pos(pos);
+            let len = buf.remove(len).len();
+            cursor = tz.find(cursor, pos);
+            tz.remove(cursor, len);
+            cursor = tz.tokenize(&buf);
+            assert_spans(&tz, &buf);
+        }
+    }
+
+    #[test]
+    fn tokenize_incremental_multiple_changes() {
+        let mut tz = build_tokenizer();
+        let mut buf = build_buffer();
+        let cursor = tz.tokenize(&buf);
+
+        // Several changes precede tokenization, which must cover the entire range of
+        // changes.
+        buf.set_pos(40);
+        buf.insert_str("\"");
+        let cursor = tz.find(cursor, 40);
+        let cursor = tz.insert(cursor, 1);
+        buf.set_pos(6);
+        buf.remove(3);
+        let cursor = tz.find(cursor, 6);
+        let cursor = tz.remove(cursor, 3);
+        buf.set_pos(20);
+        buf.insert_str("12");
+        let cursor = tz.find(cursor, 20);
+        tz.insert(cursor, 2);
+        tz.tokenize(&buf);
+        assert_spans(&tz, &buf);
+    }
+
+    #[test]
+    fn tokenize_incremental_preceding_token() {
+        let mut tz = build_tokenizer();
+        let mut buf = build_buffer();
+        let cursor = tz.tokenize(&buf);
+        assert!(!tz.is_approximate());
+
+        // Change following "1.2" is rescanned from start of preceding gap.
+        buf.set_pos(9);
+        buf.insert_str("3");
+        let cursor = tz.find(cursor, 9);
+        let cursor = tz.insert(cursor, 1);
+        tz.tokenize(&buf);
+        assert_spans(&tz, &buf);
+        assert!(!tz.is_approximate());
+
+        // Change following "\n" is rescanned from start of preceding token "1.23".
+        buf.set_pos(11);
+        buf.insert_str("5");
+        let cursor = tz.find(cursor, 11);
+        tz.insert(cursor, 1);
+        tz.tokenize(&buf);
+        assert_spans(&tz, &buf);
+        assert!(!tz.is_approximate());
+
+        // Change completes token "foo" directly preceded by token "34".
+        let mut buf = Buffer::new();
+        buf.insert_str("34fo 1");
+        let cursor = tz.tokenize_all(&buf);
+        buf.set_pos(4);
+        buf.insert_str("o");
+        let cursor = tz.find(cursor, 4);
+        tz.insert(cursor, 1);
+        tz.tokenize(&buf);
+        assert_spans(&tz, &buf);
+        assert!(!tz.is_approximate());
+    }
+
+    #[test]
+    fn tokenize_incremental_window() {
+        // Buffer spans several scan windows.
+        let line = "foo 1.2 \"bar\" baz\n";
+        let lines = 3 * Tokenizer::SCAN_WINDOW / line.len();
+        let mut tz = build_tokenizer();
+        let mut buf = Buffer::new();
+        buf.insert_str(&line.repeat(lines));
+        let cursor = tz.tokenize(&buf);
+
+        // Change realigns within first window.
+        buf.set_pos(4);
+        buf.insert_str("9");
+        let cursor = tz.find(cursor, 4);
+        let cursor = tz.insert(cursor, 1);
+        tz.tokenize(&buf);
+        assert_spans(&tz, &buf);
+
+        // Unterminated string at top changes all spans, so window grows to end of
+        // buffer.
+        buf.set_pos(0);
+        buf.insert_str("\"");
+        let cursor = tz.find(cursor, 0);
+        let cursor = tz.insert(cursor, 1);
+        tz.tokenize(&buf);
+        assert_spans(&tz, &buf);
+
+        // Window following change near end of buffer is bounded by end of buffer.
+        let pos = buf.size() - line.len();
+        buf.set_pos(pos);
+        buf.insert_str("\"");
+        let cursor = tz.find(cursor, pos);
+        tz.insert(cursor, 1);
+        tz.tokenize(&buf);
+        assert_spans(&tz, &buf);
+    }
+
+    #[test]
+    fn tokenize_background() {
+        let mut tz = build_tokenizer();
+        let mut buf = build_buffer();
+        tz.reset(&buf);
+        assert!(tz.is_approximate());
+        let job = tz.spawn(&buf);
+
+        // Changes made after snapshot must be rebased onto result of job.
+        buf.set_pos(40);
+        buf.insert_str("\"");
+        buf.set_pos(6);
+        buf.remove(3);
+        let tokens = loop {
+            match job.poll() {
+                Progress::Finished(tokens) => break tokens,
+                Progress::Pending => thread::yield_now(),
+                Progress::Failed => panic!("job failed"),
+            }
+        };
+        tz.accept(tokens, &buf, [(40, 1, true), (6, 3, false)]);
+        assert_spans(&tz, &buf);
+    }
+
+    #[test]
+    fn tokenize_regions() {
+        let mut tz = Tokenizer::new(build_region_syntax());
+        let mut buf = Buffer::new();
+        buf.insert_str("foo \"a\\\"{bar 1}\" 2");
+        let cursor = tz.tokenize(&buf);
+
+        // Styles of spans follow context in which text occurs, where text inside
+        // string region not matching any token is styled as string.
+        let styles = (0..buf.size())
+            .map(|pos| tz.find(cursor, pos).style())
+            .collect::<Vec<_>>();
+        let keyword = tz.syntax.style(2);
+        let string = tz.syntax.style(3);
+        let escape = tz.syntax.style(4);
+        let interp = tz.syntax.style(5);
+        let number = tz.syntax.style(1);
+        assert_eq!(
+            styles,
+            vec![
+                keyword, keyword, keyword, None, string, string, escape, escape, interp, keyword,
+                keyword, keyword, None, number, interp, string, None, number,
+            ]
+        );
+
+        // Opening a string earlier changes context of all subsequent text, and then
+        // closing it restores the original context.
+        buf.set_pos(3);
+        buf.insert_str("\"");
+        let cursor = tz.find(cursor, 3);
+        let cursor = tz.insert(cursor, 1);
+        tz.tokenize(&buf);
+        assert_spans(&tz, &buf);
+        buf.set_pos(3);
+        buf.remove(1);
+        let cursor = tz.find(cursor, 3);
+        tz.remove(cursor, 1);
+        tz.tokenize(&buf);
+        assert_spans(&tz, &buf);
+        assert_eq!(tz.find(tz.origin(), 13).style(), number);
+    }
+
+    /// Asserts that spans in `tz` are identical to those produced by tokenizing `buf`
+    /// from scratch.
+    fn assert_spans(tz: &Tokenizer, buf: &Buffer) {
+        let mut expect = Tokenizer::new(tz.syntax.clone());
+        expect.tokenize(buf);
+        assert_eq!(tz.chars, expect.chars);
+        let spans_of = |tz: &Tokenizer| {
+            tz.spans
+                .iter()
+                .map(|span| (span.id, span.len, tz.stacks.get(span.stack).to_vec()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(spans_of(tz), spans_of(&expect));
+    }
+
     fn build_tokenizer() -> Tokenizer {
         Tokenizer::new(build_syntax())
     }
@@ -700,9 +1358,9 @@ mod tests {
         buf
     }
 
-    fn color_of(id: usize) -> Option<u8> {
+    fn style_of(id: usize) -> Option<Style> {
         if id > 0 {
-            Some(TOKENS[id - 1].1)
+            Some(Style::from(ColorCode::Index(TOKENS[id - 1].1)))
         } else {
             None
         }
@@ -0,0 +1,330 @@
+//! Display widths of Unicode characters.
+//!
+//! Widths are derived from a compact set of tables based on the East Asian Width
+//! property and the categories of characters that occupy no space on the terminal,
+//! such as combining marks. These tables are not exhaustive, but are intended to
+//! cover the ranges in common use without resorting to an external dependency.
+
+use std::cmp::Ordering;
+
+/// Zero-width joiner, which joins adjacent characters into a single grapheme cluster.
+pub const ZWJ: char = '\u{200d}';
+
+/// Ranges of characters that occupy zero columns, sorted in ascending order.
+const ZERO_WIDTH: [(u32, u32); 109] = [
+    (0x0300, 0x036f),
+    (0x0483, 0x0489),
+    (0x0591, 0x05bd),
+    (0x05bf, 0x05bf),
+    (0x05c1, 0x05c2),
+    (0x05c4, 0x05c5),
+    (0x05c7, 0x05c7),
+    (0x0610, 0x061a),
+    (0x064b, 0x065f),
+    (0x0670, 0x0670),
+    (0x06d6, 0x06dc),
+    (0x06df, 0x06e4),
+    (0x06e7, 0x06e8),
+    (0x06ea, 0x06ed),
+    (0x0711, 0x0711),
+    (0x0730, 0x074a),
+    (0x07a6, 0x07b0),
+    (0x07eb, 0x07f3),
+    (0x0816, 0x0819),
+    (0x081b, 0x0823),
+    (0x0825, 0x0827),
+    (0x0829, 0x082d),
+    (0x0859, 0x085b),
+    (0x08d3, 0x08e1),
+    (0x08e3, 0x0902),
+    (0x093a, 0x093a),
+    (0x093c, 0x093c),
+    (0x0941, 0x0948),
+    (0x094d, 0x094d),
+    (0x0951, 0x0957),
+    (0x0962, 0x0963),
+    (0x0981, 0x0981),
+    (0x09bc, 0x09bc),
+    (0x09c1, 0x09c4),
+    (0x09cd, 0x09cd),
+    (0x09e2, 0x09e3),
+    (0x0a01, 0x0a02),
+    (0x0a3c, 0x0a3c),
+    (0x0a41, 0x0a42),
+    (0x0a47, 0x0a48),
+    (0x0a4b, 0x0a4d),
+    (0x0a70, 0x0a71),
+    (0x0a81, 0x0a82),
+    (0x0abc, 0x0abc),
+    (0x0ac1, 0x0ac5),
+    (0x0ac7, 0x0ac8),
+    (0x0acd, 0x0acd),
+    (0x0b01, 0x0b01),
+    (0x0b3c, 0x0b3c),
+    (0x0b3f, 0x0b3f),
+    (0x0b41, 0x0b44),
+    (0x0b4d, 0x0b4d),
+    (0x0bc0, 0x0bc0),
+    (0x0bcd, 0x0bcd),
+    (0x0c3e, 0x0c40),
+    (0x0c46, 0x0c48),
+    (0x0c4a, 0x0c4d),
+    (0x0cbc, 0x0cbc),
+    (0x0ccc, 0x0ccd),
+    (0x0d41, 0x0d44),
+    (0x0d4d, 0x0d4d),
+    (0x0e31, 0x0e31),
+    (0x0e34, 0x0e3a),
+    (0x0e47, 0x0e4e),
+    (0x0eb1, 0x0eb1),
+    (0x0eb4, 0x0ebc),
+    (0x0ec8, 0x0ecd),
+    (0x0f18, 0x0f19),
+    (0x0f35, 0x0f35),
+    (0x0f37, 0x0f37),
+    (0x0f39, 0x0f39),
+    (0x0f71, 0x0f7e),
+    (0x0f80, 0x0f84),
+    (0x0f86, 0x0f87),
+    (0x102d, 0x1030),
+    (0x1032, 0x1037),
+    (0x1039, 0x103a),
+    (0x1160, 0x11ff),
+    (0x135d, 0x135f),
+    (0x1712, 0x1714),
+    (0x17b4, 0x17b5),
+    (0x17b7, 0x17bd),
+    (0x17c6, 0x17c6),
+    (0x17c9, 0x17d3),
+    (0x180b, 0x180d),
+    (0x18a9, 0x18a9),
+    (0x1ab0, 0x1aff),
+    (0x1dc0, 0x1dff),
+    (0x200b, 0x200f),
+    (0x202a, 0x202e),
+    (0x2060, 0x2064),
+    (0x20d0, 0x20ff),
+    (0x302a, 0x302d),
+    (0x3099, 0x309a),
+    (0xa66f, 0xa672),
+    (0xa674, 0xa67d),
+    (0xa69e, 0xa69f),
+    (0xa6f0, 0xa6f1),
+    (0xa802, 0xa802),
+    (0xa806, 0xa806),
+    (0xa80b, 0xa80b),
+    (0xfe00, 0xfe0f),
+    (0xfe20, 0xfe2f),
+    (0xfeff, 0xfeff),
+    (0x1d167, 0x1d169),
+    (0x1d17b, 0x1d182),
+    (0xe0001, 0xe0001),
+    (0xe0020, 0xe007f),
+    (0xe0100, 0xe01ef),
+];
+
+/// Ranges of characters that occupy two columns, sorted in ascending order.
+const WIDE: [(u32, u32); 98] = [
+    (0x1100, 0x115f),
+    (0x231a, 0x231b),
+    (0x2329, 0x232a),
+    (0x23e9, 0x23ec),
+    (0x23f0, 0x23f0),
+    (0x23f3, 0x23f3),
+    (0x25fd, 0x25fe),
+    (0x2614, 0x2615),
+    (0x2648, 0x2653),
+    (0x267f, 0x267f),
+    (0x2693, 0x2693),
+    (0x26a1, 0x26a1),
+    (0x26aa, 0x26ab),
+    (0x26bd, 0x26be),
+    (0x26c4, 0x26c5),
+    (0x26ce, 0x26ce),
+    (0x26d4, 0x26d4),
+    (0x26ea, 0x26ea),
+    (0x26f2, 0x26f3),
+    (0x26f5, 0x26f5),
+    (0x26fa, 0x26fa),
+    (0x26fd, 0x26fd),
+    (0x2705, 0x2705),
+    (0x270a, 0x270b),
+    (0x2728, 0x2728),
+    (0x274c, 0x274c),
+    (0x274e, 0x274e),
+    (0x2753, 0x2755),
+    (0x2757, 0x2757),
+    (0x2795, 0x2797),
+    (0x27b0, 0x27b0),
+    (0x27bf, 0x27bf),
+    (0x2b1b, 0x2b1c),
+    (0x2b50, 0x2b50),
+    (0x2b55, 0x2b55),
+    (0x2e80, 0x3029),
+    (0x302e, 0x303e),
+    (0x3041, 0x3098),
+    (0x309b, 0x33ff),
+    (0x3400, 0x4dbf),
+    (0x4e00, 0x9fff),
+    (0xa000, 0xa4cf),
+    (0xa960, 0xa97f),
+    (0xac00, 0xd7a3),
+    (0xf900, 0xfaff),
+    (0xfe10, 0xfe19),
+    (0xfe30, 0xfe6f),
+    (0xff00, 0xff60),
+    (0xffe0, 0xffe6),
+    (0x16fe0, 0x16fe4),
+    (0x17000, 0x18aff),
+    (0x1b000, 0x1b16f),
+    (0x1f004, 0x1f004),
+    (0x1f0cf, 0x1f0cf),
+    (0x1f18e, 0x1f18e),
+    (0x1f191, 0x1f19a),
+    (0x1f200, 0x1f202),
+    (0x1f210, 0x1f23b),
+    (0x1f240, 0x1f248),
+    (0x1f250, 0x1f251),
+    (0x1f260, 0x1f265),
+    (0x1f300, 0x1f320),
+    (0x1f32d, 0x1f335),
+    (0x1f337, 0x1f37c),
+    (0x1f37e, 0x1f393),
+    (0x1f3a0, 0x1f3ca),
+    (0x1f3cf, 0x1f3d3),
+    (0x1f3e0, 0x1f3f0),
+    (0x1f3f4, 0x1f3f4),
+    (0x1f3f8, 0x1f43e),
+    (0x1f440, 0x1f440),
+    (0x1f442, 0x1f4fc),
+    (0x1f4ff, 0x1f53d),
+    (0x1f54b, 0x1f54e),
+    (0x1f550, 0x1f567),
+    (0x1f57a, 0x1f57a),
+    (0x1f595, 0x1f596),
+    (0x1f5a4, 0x1f5a4),
+    (0x1f5fb, 0x1f64f),
+    (0x1f680, 0x1f6c5),
+    (0x1f6cc, 0x1f6cc),
+    (0x1f6d0, 0x1f6d2),
+    (0x1f6d5, 0x1f6d7),
+    (0x1f6eb, 0x1f6ec),
+    (0x1f6f4, 0x1f6fc),
+    (0x1f7e0, 0x1f7eb),
+    (0x1f90c, 0x1f93a),
+    (0x1f93c, 0x1f945),
+    (0x1f947, 0x1f9ff),
+    (0x1fa70, 0x1fa7c),
+    (0x1fa80, 0x1fa88),
+    (0x1fa90, 0x1fabd),
+    (0x1fabf, 0x1fac5),
+    (0x1face, 0x1fadb),
+    (0x1fae0, 0x1fae8),
+    (0x1faf0, 0x1faf8),
+    (0x20000, 0x2fffd),
+    (0x30000, 0x3fffd),
+];
+
+/// Returns the number of columns occupied by `c` on the terminal, which is either
+/// `0`, `1`, or `2`.
+///
+/// Note that control characters are presumed to occupy a single column, as the
+/// editor displays such characters using an alternate representation.
+pub fn char_width(c: char) -> u32 {
+    if (c as u32) < 0x300 {
+        1
+    } else if is_zero_width(c) {
+        0
+    } else if is_wide(c) {
+        2
+    } else {
+        1
+    }
+}
+
+/// Returns the number of columns occupied by `chars` on the terminal.
+pub fn str_width(chars: &str) -> u32 {
+    chars.chars().map(char_width).sum()
+}
+
+/// Returns `true` if `c` occupies zero columns, such as combining marks that attach
+/// to the preceding character.
+#[inline]
+pub fn is_zero_width(c: char) -> bool {
+    in_table(c, &ZERO_WIDTH)
+}
+
+/// Returns `true` if `c` occupies two columns.
+#[inline]
+pub fn is_wide(c: char) -> bool {
+    in_table(c, &WIDE)
+}
+
+/// Returns `true` if `c` joins with the preceding character `prev` to form part of a
+/// single grapheme cluster.
+///
+/// Combining marks and other zero-width characters always join with the preceding
+/// character, as does any character following [`ZWJ`], with the exception that
+/// `\n` never joins in either direction.
+pub fn is_joined(prev: char, c: char) -> bool {
+    prev != '\n' && c != '\n' && (prev == ZWJ || is_zero_width(c))
+}
+
+fn in_table(c: char, table: &[(u32, u32)]) -> bool {
+    let c = c as u32;
+    table
+        .binary_search_by(|&(start, end)| {
+            if c < start {
+                Ordering::Greater
+            } else if c > end {
+                Ordering::Less
+            } else {
+                Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_sorted() {
+        for table in [&ZERO_WIDTH[..], &WIDE[..]] {
+            for (start, end) in table {
+                assert!(start <= end);
+            }
+            for pair in table.windows(2) {
+                assert!(pair[0].1 < pair[1].0);
+            }
+        }
+    }
+
+    #[test]
+    fn widths() {
+        assert_eq!(char_width('a'), 1);
+        assert_eq!(char_width('\t'), 1);
+        assert_eq!(char_width('é'), 1);
+        assert_eq!(char_width('\u{301}'), 0);
+        assert_eq!(char_width(ZWJ), 0);
+        assert_eq!(char_width('\u{fe0f}'), 0);
+        assert_eq!(char_width('中'), 2);
+        assert_eq!(char_width('한'), 2);
+        assert_eq!(char_width('Ａ'), 2);
+        assert_eq!(char_width('😀'), 2);
+        assert_eq!(char_width('\u{3000}'), 2);
+        assert_eq!(char_width('\u{303f}'), 1);
+        assert_eq!(str_width("a中e\u{301}"), 4);
+    }
+
+    #[test]
+    fn joined() {
+        assert!(is_joined('e', '\u{301}'));
+        assert!(is_joined(ZWJ, '😀'));
+        assert!(is_joined('😀', ZWJ));
+        assert!(!is_joined('a', 'b'));
+        assert!(!is_joined('\n', '\u{301}'));
+        assert!(!is_joined(ZWJ, '\n'));
+    }
+}
@@ -4,11 +4,13 @@
 //! _banner_ for displaying other relevant bits of information.
 
 use crate::canvas::{Canvas, CanvasRef};
-use crate::color::Color;
+use crate::color::{Color, ColorCode, Shade};
 use crate::config::ConfigurationRef;
+use crate::format::{BannerFormat, Field, Piece};
 use crate::size::{Point, Size};
 use crate::source::Source;
 use crate::sys;
+use crate::width;
 use std::cell::RefCell;
 use std::ops::Range;
 use std::rc::Rc;
@@ -19,18 +21,27 @@ pub struct Banner {
     dirty_area: Option<u32>,
     source_area: Option<Range<u32>>,
     loc_area: Option<Range<u32>>,
-    active_bg: u8,
-    inactive_bg: u8,
+    banner_fg: Shade,
+    accent_fg: Shade,
+    active_bg: Shade,
+    inactive_bg: Shade,
     banner_color: Color,
     accent_color: Color,
+    format: Option<BannerFormat>,
     dirty: bool,
     source: Source,
     syntax: String,
     loc: Point,
+    pos: usize,
+    chars: usize,
+    tab_hard: bool,
 }
 
 pub type BannerRef = Rc<RefCell<Banner>>;
 
+/// A section of banner text as a series of colored pieces.
+type Section = Vec<(String, Color)>;
+
 impl Banner {
     /// Minimum number of window columns required to show any banner information,
     /// otherwise everything is clipped.
@@ -71,28 +82,44 @@ impl Banner {
     /// Number of columns allocated to location area.
     const LOCATION_COLS: u32 = Self::LINE_COLS + Self::COL_COLS + 1;
 
+    /// Name of the only supported character encoding.
+    const ENCODING: &str = "utf-8";
+
     fn new(origin: Point, cols: u32, config: ConfigurationRef) -> Banner {
         // Determine which areas of banner will be shown based on available number of
-        // columns.
-        let (dirty_area, source_area, loc_area) = Self::calc_areas(cols);
-        let active_bg = config.theme.active_bg;
-        let inactive_bg = config.theme.inactive_bg;
-        let banner_color = Color::new(config.theme.banner_fg, inactive_bg);
-        let accent_color = Color::new(config.theme.accent_fg, inactive_bg);
+        // columns, none of which apply when a custom format is specified.
+        let format = config.settings.banner_format.clone();
+        let (dirty_area, source_area, loc_area) = if format.is_some() {
+            (None, None, None)
+        } else {
+            Self::calc_areas(cols)
+        };
+        let banner_fg = config.theme().banner_fg;
+        let accent_fg = config.theme().accent_fg;
+        let active_bg = config.theme().active_bg;
+        let inactive_bg = config.theme().inactive_bg;
+        let banner_color = Color::from_shades(banner_fg, inactive_bg);
+        let accent_color = Color::from_shades(accent_fg, inactive_bg);
 
         let mut this = Banner {
             canvas: Canvas::new(origin, Size::new(1, cols)),
             dirty_area,
             source_area,
             loc_area,
+            banner_fg,
+            accent_fg,
             active_bg,
             inactive_bg,
             banner_color,
             accent_color,
+            format,
             dirty: false,
             source: Source::Null,
             syntax: String::new(),
             loc: Point::ORIGIN,
+            pos: 0,
+            chars: 0,
+            tab_hard: false,
         };
         this.clear();
         this
@@ -104,14 +131,20 @@ impl Banner {
             dirty_area: None,
             source_area: None,
             loc_area: None,
-            active_bg: 0,
-            inactive_bg: 0,
+            banner_fg: Shade::new(ColorCode::Index(0)),
+            accent_fg: Shade::new(ColorCode::Index(0)),
+            active_bg: Shade::new(ColorCode::Index(0)),
+            inactive_bg: Shade::new(ColorCode::Index(0)),
             banner_color: Color::ZERO,
             accent_color: Color::ZERO,
+            format: None,
             dirty: false,
             source: Source::Null,
             syntax: String::new(),
             loc: Point::ORIGIN,
+            pos: 0,
+            chars: 0,
+            tab_hard: false,
         }
     }
 
@@ -131,6 +164,7 @@ impl Banner {
         self.draw_dirty();
         self.draw_source();
         self.draw_location();
+        self.draw_format();
         self.canvas.draw();
     }
 
@@ -141,8 +175,8 @@ impl Banner {
         } else {
             self.inactive_bg
         };
-        self.banner_color.bg = bg;
-        self.accent_color.bg = bg;
+        self.banner_color = Color::from_shades(self.banner_fg, bg);
+        self.accent_color = Color::from_shades(self.accent_fg, bg);
         self.redraw();
     }
 
@@ -150,6 +184,7 @@ impl Banner {
         if dirty != self.dirty {
             self.dirty = dirty;
             self.draw_dirty();
+            self.draw_format();
         }
         self
     }
@@ -157,18 +192,41 @@ impl Banner {
     pub fn set_source(&mut self, source: Source) -> &mut Banner {
         self.source = source;
         self.draw_source();
+        self.draw_format();
         self
     }
 
     pub fn set_syntax(&mut self, syntax: String) -> &mut Banner {
         self.syntax = syntax;
         self.draw_source();
+        self.draw_format();
         self
     }
 
     pub fn set_location(&mut self, loc: Point) -> &mut Banner {
         self.loc = loc;
         self.draw_location();
+        self.draw_format();
+        self
+    }
+
+    /// Sets the buffer position of the cursor and the number of characters in the
+    /// buffer, neither of which are shown unless referenced by a custom format.
+    pub fn set_position(&mut self, pos: usize, chars: usize) -> &mut Banner {
+        if pos != self.pos || chars != self.chars {
+            self.pos = pos;
+            self.chars = chars;
+            self.draw_format();
+        }
+        self
+    }
+
+    /// Sets the tab mode, which is not shown unless referenced by a custom format.
+    pub fn set_tab(&mut self, hard: bool) -> &mut Banner {
+        if hard != self.tab_hard {
+            self.tab_hard = hard;
+            self.draw_format();
+        }
         self
     }
 
@@ -251,6 +309,126 @@ impl Banner {
         }
     }
 
+    /// Draws the banner according to the custom format, if specified.
+    fn draw_format(&mut self) {
+        let cols = self.canvas.size().cols;
+        let (left, right) = match self.format {
+            Some(ref format) if cols >= Self::MIN_COLS => self.layout(format, cols),
+            _ => return,
+        };
+
+        self.clear();
+        let mut col = Self::LEFT_MARGIN_COLS;
+        for (text, color) in left {
+            col += self.canvas.write_str(0, col, &text, color);
+        }
+        let mut col = cols - Self::RIGHT_MARGIN_COLS - Self::width_of(&right);
+        for (text, color) in right {
+            col += self.canvas.write_str(0, col, &text, color);
+        }
+    }
+
+    /// Returns the left and right sections of `format` expanded into text and color
+    /// such that both fit on a banner with `cols` columns.
+    ///
+    /// If both sections do not fit within the available columns, the right section is
+    /// removed first. Thereafter, the path is shortened to its file name, then
+    /// truncated with an ellipsis, and finally the left section is clipped.
+    fn layout(&self, format: &BannerFormat, cols: u32) -> (Section, Section) {
+        let avail_cols = cols - Self::LEFT_MARGIN_COLS - Self::RIGHT_MARGIN_COLS;
+        let path = self.source.to_string();
+        let mut left = self.expand(format.left(), &path);
+
+        // Right section is clipped entirely if window is too narrow or if both
+        // sections do not fit.
+        let mut right = if cols < Self::MIN_COLS_FOR_LOCATION {
+            Vec::new()
+        } else {
+            self.expand(format.right(), &path)
+        };
+        if !right.is_empty()
+            && Self::width_of(&left) + Self::GAP_COLS + Self::width_of(&right) > avail_cols
+        {
+            right.clear();
+        }
+
+        // Successively shorten path until left section fits within available area,
+        // though final attempt might still require clipping.
+        if Self::width_of(&left) > avail_cols {
+            let mut path = if let Source::File(path, _) = &self.source {
+                sys::file_name(path)
+            } else {
+                path
+            };
+            left = self.expand(format.left(), &path);
+            let excess = Self::width_of(&left).saturating_sub(avail_cols) as usize;
+            let path_len = path.chars().count();
+            if excess > 0 && path_len > excess + Self::SOURCE_ELLIPSIS.len() {
+                let suffix = path.chars().skip(excess + Self::SOURCE_ELLIPSIS.len());
+                path = Self::SOURCE_ELLIPSIS.chars().chain(suffix).collect();
+                left = self.expand(format.left(), &path);
+            }
+        }
+
+        // Clipping stops at the first character that does not fit, so that narrower
+        // characters which follow are not shown out of order.
+        let mut col = 0;
+        let mut clipped = Vec::new();
+        'clip: for (text, color) in left {
+            let mut s = String::new();
+            for c in text.chars() {
+                col += width::char_width(c);
+                if col > avail_cols {
+                    clipped.push((s, color));
+                    break 'clip;
+                }
+                s.push(c);
+            }
+            clipped.push((s, color));
+        }
+        (clipped, right)
+    }
+
+    /// Returns the pieces of a banner section expanded into text and color, where
+    /// `path` is the value used for [`Field::Path`].
+    fn expand(&self, pieces: &[Piece], path: &str) -> Section {
+        pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => (text.clone(), self.banner_color),
+                Piece::Field(field) => {
+                    let color = match field {
+                        Field::Dirty | Field::Syntax => self.accent_color,
+                        _ => self.banner_color,
+                    };
+                    (self.value_of(*field, path), color)
+                }
+            })
+            .collect()
+    }
+
+    fn value_of(&self, field: Field, path: &str) -> String {
+        match field {
+            Field::Path => path.to_string(),
+            Field::Dirty => (if self.dirty { "*" } else { " " }).to_string(),
+            Field::Syntax => self.syntax.clone(),
+            Field::Line => format!("{}", self.loc.row + 1),
+            Field::Col => format!("{}", self.loc.col + 1),
+            Field::Percent => {
+                let percent = (self.pos * 100).checked_div(self.chars).unwrap_or(0);
+                format!("{percent}")
+            }
+            Field::Encoding => Self::ENCODING.to_string(),
+            Field::TabMode => (if self.tab_hard { "hard" } else { "soft" }).to_string(),
+            Field::Chars => format!("{}", self.chars),
+            Field::Pos => format!("{}", self.pos),
+        }
+    }
+
+    fn width_of(section: &[(String, Color)]) -> u32 {
+        section.iter().map(|(text, _)| width::str_width(text)).sum()
+    }
+
     fn calc_areas(cols: u32) -> (Option<u32>, Option<Range<u32>>, Option<Range<u32>>) {
         if cols < Self::MIN_COLS {
             (None, None, None)
@@ -349,3 +527,76 @@ impl Window {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Configuration;
+
+    /// Returns the text of the left and right sections of a banner with `cols`
+    /// columns, which is formatted using `format` and shows `path`.
+    fn layout(format: &str, path: &str, cols: u32) -> (String, String) {
+        let mut config = Configuration::default();
+        config.settings.banner_format = Some(BannerFormat::parse(format).unwrap());
+        let mut banner = Banner::new(Point::ORIGIN, cols, config.to_ref());
+        banner
+            .set_source(Source::as_file(path, None))
+            .set_syntax("Rust".to_string());
+        let format = banner.format.clone().unwrap();
+        let (left, right) = banner.layout(&format, cols);
+        let text = |section: Section| section.into_iter().map(|(s, _)| s).collect();
+        (text(left), text(right))
+    }
+
+    fn pair(left: &str, right: &str) -> (String, String) {
+        (left.to_string(), right.to_string())
+    }
+
+    const FORMAT: &str = "{path} ({syntax}){>}{line}:{col}";
+
+    #[test]
+    fn layout_fits() {
+        assert_eq!(layout(FORMAT, "/a/b.rs", 40), pair("/a/b.rs (Rust)", "1:1"));
+    }
+
+    #[test]
+    fn layout_removes_right_first() {
+        // Left section alone fits in 37 columns, but not together with right section.
+        let path = "/abcdefghij/klmnopqrstu/b.rs";
+        assert_eq!(
+            layout(FORMAT, path, 40),
+            pair(&format!("{path} (Rust)"), "")
+        );
+    }
+
+    #[test]
+    fn layout_shortens_path() {
+        let path = "/abcdefghij/klmnopqrstu/vwxyz/b.rs";
+        assert_eq!(layout(FORMAT, path, 40), pair("b.rs (Rust)", ""));
+
+        let path = "/a/abcdefghijklmnopqrstuvwxyz0123456789.rs";
+        assert_eq!(
+            layout(FORMAT, path, 40),
+            pair("...mnopqrstuvwxyz0123456789.rs (Rust)", "")
+        );
+    }
+
+    #[test]
+    fn layout_clips_left() {
+        assert_eq!(
+            layout(
+                "({syntax}) abcdefghijklmnopqrstuvwxyz{>}{line}",
+                "/a/b.rs",
+                32
+            ),
+            pair("(Rust) abcdefghijklmnopqrstuv", "")
+        );
+
+        // Wide character that does not fit ends the section, even though narrower
+        // characters that follow would fit.
+        assert_eq!(
+            layout("abcdefghijkl\u{65e5}{syntax}", "/a/b.rs", 16),
+            pair("abcdefghijkl", "")
+        );
+    }
+}
@@ -8,6 +8,8 @@ use crate::window::{Window, WindowRef};
 use crate::writer::Writer;
 use std::cell::RefCell;
 use std::cmp;
+use std::mem;
+use std::ops::Range;
 use std::rc::Rc;
 
 /// Placement directive when adding new [`View`]s to a [`Workspace`].
@@ -24,26 +26,412 @@ pub enum Placement {
 
     /// Place directly below the view referenced by the contained _id_.
     Below(u32),
+
+    /// Place directly to the left of the view referenced by the contained _id_.
+    Left(u32),
+
+    /// Place directly to the right of the view referenced by the contained _id_.
+    Right(u32),
 }
 
 /// A view inside a [`Workspace`].
 pub struct View {
     pub id: u32,
     pub window: WindowRef,
+    origin: Point,
+    size: Size,
 }
 
 impl View {
-    fn new(id: u32, window: WindowRef) -> View {
-        View { id, window }
+    fn new(id: u32, window: WindowRef, origin: Point, size: Size) -> View {
+        View {
+            id,
+            window,
+            origin,
+            size,
+        }
+    }
+
+    /// Returns `true` if the view is visible, which is not the case when hidden by
+    /// another view that is maximized.
+    pub fn is_visible(&self) -> bool {
+        self.size != Size::ZERO
+    }
+
+    /// Returns the span of rows and columns occupied by the view, ordered such that
+    /// the first span runs along the axis of movement implied by `dir`.
+    fn spans(&self, dir: Direction) -> (Range<u32>, Range<u32>) {
+        let rows = self.origin.row..self.origin.row + self.size.rows;
+        let cols = self.origin.col..self.origin.col + self.size.cols;
+        match dir {
+            Direction::Up | Direction::Down => (rows, cols),
+            Direction::Left | Direction::Right => (cols, rows),
+        }
+    }
+}
+
+/// Direction of movement from one [`View`] to an adjacent view.
+#[derive(Copy, Clone, Debug)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// The manner in which a [`Layout::Split`] divides its area among children.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Split {
+    /// Children are stacked vertically, each spanning the width of the area.
+    Rows,
+
+    /// Children are arranged side by side, each spanning the height of the area.
+    Cols,
+}
+
+impl Split {
+    /// Returns the number of rows or columns in `size` along the axis of the split.
+    fn extent(self, size: Size) -> u32 {
+        match self {
+            Split::Rows => size.rows,
+            Split::Cols => size.cols,
+        }
+    }
+
+    /// Returns the minimum number of rows or columns allocated to each child.
+    fn min_extent(self) -> u32 {
+        match self {
+            Split::Rows => Workspace::MIN_VIEW_ROWS,
+            Split::Cols => Workspace::MIN_VIEW_COLS,
+        }
+    }
+
+    /// Returns a size derived from `size` whose extent along the axis of the split is
+    /// replaced with `extent`.
+    fn with_extent(self, size: Size, extent: u32) -> Size {
+        match self {
+            Split::Rows => Size::new(extent, size.cols),
+            Split::Cols => Size::new(size.rows, extent),
+        }
+    }
+
+    /// Returns the number of rows or columns occupied by dividers between `n`
+    /// children.
+    fn dividers(self, n: u32) -> u32 {
+        match self {
+            Split::Rows => 0,
+            Split::Cols => n.saturating_sub(1),
+        }
+    }
+
+    /// Returns the extent along the axis of the split allocated to each of `nodes`
+    /// when given the area defined by `size`.
+    ///
+    /// Extents are distributed in proportion to the weight of each node, though nodes
+    /// towards the top or left receive an additional row or column if the division
+    /// leaves a residual.
+    fn allocate(self, size: Size, nodes: &[Node]) -> Vec<u32> {
+        let total = self
+            .extent(size)
+            .saturating_sub(self.dividers(nodes.len() as u32));
+        let weight = nodes.iter().map(|n| n.weight).sum::<f64>();
+        let mut extents = nodes
+            .iter()
+            .map(|n| (total as f64 * n.weight / weight) as u32)
+            .collect::<Vec<_>>();
+        let residual = total.saturating_sub(extents.iter().sum::<u32>());
+        for extent in extents.iter_mut().take(residual as usize) {
+            *extent += 1;
+        }
+        extents
+    }
+}
+
+/// A child of a [`Layout::Split`] whose `weight` determines its share of the area
+/// relative to its siblings.
+#[derive(Clone, Debug, PartialEq)]
+struct Node {
+    layout: Layout,
+    weight: f64,
+}
+
+impl Node {
+    fn new(layout: Layout) -> Node {
+        Node {
+            layout,
+            weight: 1.0,
+        }
+    }
+
+    /// Sets the weight of each of `nodes` to the same value, which results in an
+    /// equal allocation of the area.
+    fn balance(nodes: &mut [Node]) {
+        for node in nodes {
+            node.weight = 1.0;
+        }
+    }
+}
+
+/// A tree describing the arrangement of [`View`]s in a [`Workspace`], where leaves
+/// refer to views and interior nodes split their area among children.
+///
+/// Trees are kept in a normalized form such that splits always contain at least
+/// `2` children, and no split contains a child split of the same kind.
+#[derive(Clone, Debug, PartialEq)]
+enum Layout {
+    View(u32),
+    Split(Split, Vec<Node>),
+}
+
+impl Layout {
+    fn is_view(&self, id: u32) -> bool {
+        matches!(self, Layout::View(v_id) if *v_id == id)
+    }
+
+    /// Appends the view ids of this layout to `ids` in order of appearance, which is
+    /// top to bottom and left to right.
+    fn view_ids(&self, ids: &mut Vec<u32>) {
+        match self {
+            Layout::View(id) => ids.push(*id),
+            Layout::Split(_, nodes) => {
+                for node in nodes {
+                    node.layout.view_ids(ids);
+                }
+            }
+        }
+    }
+
+    /// Places `new_id` at the edge of this layout, either before or after all
+    /// existing views according to `after`, along the axis of `split`.
+    ///
+    /// If the new view joins an existing split, then the area of that split is
+    /// equally distributed among its children.
+    fn wrap(&mut self, new_id: u32, split: Split, after: bool) {
+        let node = Node::new(Layout::View(new_id));
+        match self {
+            Layout::Split(s, nodes) if *s == split => {
+                if after {
+                    nodes.push(node);
+                } else {
+                    nodes.insert(0, node);
+                }
+                Node::balance(nodes);
+            }
+            _ => {
+                let this = Node::new(mem::replace(self, Layout::View(new_id)));
+                let nodes = if after {
+                    vec![this, node]
+                } else {
+                    vec![node, this]
+                };
+                *self = Layout::Split(split, nodes);
+            }
+        }
+    }
+
+    /// Places `new_id` next to the view referenced by `id`, either before or after
+    /// according to `after`, along the axis of `split`, and returns `true` if `id`
+    /// was found.
+    ///
+    /// If `id` is already contained in a split of the same kind, then `new_id` joins
+    /// that split and its area is equally distributed among its children, otherwise
+    /// the view is replaced with a new split containing both.
+    fn insert(&mut self, id: u32, new_id: u32, split: Split, after: bool) -> bool {
+        match self {
+            Layout::View(v_id) => {
+                if *v_id == id {
+                    self.wrap(new_id, split, after);
+                    true
+                } else {
+                    false
+                }
+            }
+            Layout::Split(s, nodes) => {
+                if *s == split {
+                    if let Some(i) = nodes.iter().position(|n| n.layout.is_view(id)) {
+                        let i = if after { i + 1 } else { i };
+                        nodes.insert(i, Node::new(Layout::View(new_id)));
+                        Node::balance(nodes);
+                        return true;
+                    }
+                }
+                nodes
+                    .iter_mut()
+                    .any(|n| n.layout.insert(id, new_id, split, after))
+            }
+        }
+    }
+
+    /// Removes the view referenced by `id` and returns `true` if found.
+    ///
+    /// The area of the removed view is distributed among its siblings in proportion
+    /// to their existing allocations. Note that the root of the layout is never
+    /// removed, even if it refers to `id`.
+    fn remove(&mut self, id: u32) -> bool {
+        match self {
+            Layout::View(_) => false,
+            Layout::Split(_, nodes) => {
+                if let Some(i) = nodes.iter().position(|n| n.layout.is_view(id)) {
+                    nodes.remove(i);
+                } else if !nodes.iter_mut().any(|n| n.layout.remove(id)) {
+                    return false;
+                }
+                self.normalize();
+                true
+            }
+        }
+    }
+
+    /// Collapses splits with only `1` child and merges child splits of the same kind
+    /// into their parent, scaling weights of merged children such that allocations
+    /// are preserved.
+    fn normalize(&mut self) {
+        if let Layout::Split(split, nodes) = self {
+            let mut flat = Vec::new();
+            for node in nodes.drain(..) {
+                match node.layout {
+                    Layout::Split(s, children) if s == *split => {
+                        let weight = children.iter().map(|n| n.weight).sum::<f64>();
+                        flat.extend(children.into_iter().map(|n| Node {
+                            layout: n.layout,
+                            weight: node.weight * n.weight / weight,
+                        }));
+                    }
+                    _ => flat.push(node),
+                }
+            }
+            if flat.len() == 1 {
+                *self = flat.remove(0).layout;
+            } else {
+                *nodes = flat;
+            }
+        }
     }
+
+    /// Restores an equal allocation of area among children of all splits.
+    fn balance(&mut self) {
+        if let Layout::Split(_, nodes) = self {
+            Node::balance(nodes);
+            for node in nodes {
+                node.layout.balance();
+            }
+        }
+    }
+
+    /// Changes the area allocated to the view referenced by `id` by `delta` rows or
+    /// columns, depending on the kind of split in which the view is contained, when
+    /// given the area of this layout defined by `size`, and returns `true` if `id`
+    /// was found in a split.
+    ///
+    /// A positive `delta` takes area from siblings, starting with those nearest to
+    /// the view, though never reducing any sibling below its minimum size. A negative
+    /// `delta` gives area to the nearest sibling.
+    ///
+    /// Note that the resulting layout may violate minimum size constraints, which is
+    /// the responsibility of the caller to verify.
+    fn adjust(&mut self, id: u32, delta: i32, size: Size) -> bool {
+        match self {
+            Layout::View(_) => false,
+            Layout::Split(split, nodes) => {
+                let extents = split.allocate(size, nodes);
+                if let Some(i) = nodes.iter().position(|n| n.layout.is_view(id)) {
+                    // Reset weights to current allocations so that delta can be
+                    // applied in units of rows or columns.
+                    for (node, extent) in nodes.iter_mut().zip(extents.iter()) {
+                        node.weight = *extent as f64;
+                    }
+
+                    // Visit siblings in order of proximity to view.
+                    let n = nodes.len();
+                    let siblings = (1..n)
+                        .flat_map(|d| [i + d, i.wrapping_sub(d)])
+                        .filter(|j| *j < n)
+                        .collect::<Vec<_>>();
+
+                    if delta > 0 {
+                        let mut remaining = delta as u32;
+                        for j in siblings {
+                            let avail = extents[j].saturating_sub(split.min_extent());
+                            let take = cmp::min(avail, remaining);
+                            nodes[j].weight -= take as f64;
+                            nodes[i].weight += take as f64;
+                            remaining -= take;
+                        }
+                        remaining < delta as u32
+                    } else {
+                        let give = cmp::min(delta.unsigned_abs(), extents[i]);
+                        nodes[i].weight -= give as f64;
+                        nodes[siblings[0]].weight += give as f64;
+                        true
+                    }
+                } else {
+                    nodes.iter_mut().zip(extents).any(|(n, extent)| {
+                        n.layout.adjust(id, delta, split.with_extent(size, extent))
+                    })
+                }
+            }
+        }
+    }
+
+    /// Calculates the area of each view in this layout when given the area defined
+    /// by `origin` and `size`, returning `false` if any view would violate minimum
+    /// size constraints.
+    ///
+    /// Children of a [`Split::Cols`] are separated by dividers of `1` column.
+    fn arrange(&self, origin: Point, size: Size, arrangement: &mut Arrangement) -> bool {
+        match self {
+            Layout::View(id) => {
+                arrangement.areas.push((*id, origin, size));
+                true
+            }
+            Layout::Split(split, nodes) => {
+                let extents = split.allocate(size, nodes);
+                let mut origin = origin;
+                nodes
+                    .iter()
+                    .zip(extents)
+                    .enumerate()
+                    .all(|(i, (node, extent))| {
+                        let fits = extent >= split.min_extent()
+                            && node.layout.arrange(
+                                origin,
+                                split.with_extent(size, extent),
+                                arrangement,
+                            );
+                        origin = origin + split.with_extent(Size::ZERO, extent);
+                        if *split == Split::Cols && i + 1 < nodes.len() {
+                            arrangement.dividers.push((origin, size.rows));
+                            origin = origin + Size::cols(1);
+                        }
+                        fits
+                    })
+            }
+        }
+    }
+}
+
+/// The result of arranging a [`Layout`] inside the area of a [`Workspace`].
+#[derive(Default)]
+struct Arrangement {
+    /// Tuples containing the view id, origin, and size of each view.
+    areas: Vec<(u32, Point, Size)>,
+
+    /// Tuples containing the origin and number of rows of each divider.
+    dividers: Vec<(Point, u32)>,
 }
 
 /// A workspace is a collection of [`View`]s that encapsulate the entire editing
 /// experience.
 ///
-/// Mutiple views within a workspace are organized vertically with an equal number of
-/// rows. As views are added and removed, the resulting collection of views is resized
-/// accorndingly.
+/// Views within a workspace are arranged as a tree of splits, where each split
+/// either stacks its children vertically as rows or places them side by side as
+/// columns. The area of a split is allocated among its children in proportion to
+/// their weights, which are equal unless changed by growing or shrinking views. As
+/// views are added and removed, the resulting collection of views is resized
+/// accordingly.
+///
+/// A single view may also be _maximized_, which temporarily hides all other views
+/// without removing them. Hidden views are represented by zombie windows.
 ///
 /// A workspace always provides at least `1` view, which implies that the last
 /// remaining view can never be removed.
@@ -54,8 +442,9 @@ pub struct Workspace {
     views_size: Size,
     shared_origin: Point,
     shared_size: Size,
-    shared_color: Color,
     id_seq: u32,
+    layout: Layout,
+    maximized_id: Option<u32>,
     views: Vec<View>,
 }
 
@@ -72,10 +461,21 @@ impl Workspace {
     /// Minimum number of rows assigned to a view.
     const MIN_VIEW_ROWS: u32 = 2;
 
+    /// Minimum number of columns assigned to a view placed side by side with other
+    /// views.
+    const MIN_VIEW_COLS: u32 = 16;
+
+    /// Character used to draw dividers between views placed side by side.
+    const DIVIDER_CHAR: char = '\u{2502}';
+
     /// Creates a workspace with the given `config` and consuming the entire terminal.
     pub fn new(config: Configuration) -> Workspace {
-        let size = Self::query_size();
-        let shared_color = Color::new(config.theme.echo_fg, config.theme.text_bg);
+        Self::with_size(config, Self::query_size())
+    }
+
+    /// Creates a workspace with the given `config` and consuming an area of `size`
+    /// rather than the entire terminal.
+    pub fn with_size(config: Configuration, size: Size) -> Workspace {
         let mut this = Workspace {
             config: config.to_ref(),
             size,
@@ -83,11 +483,13 @@ impl Workspace {
             views_size: size - Self::VIEWS_SIZE_ADJUST,
             shared_origin: Point::ORIGIN + Size::rows(size.rows - 1),
             shared_size: Size::new(1, size.cols),
-            shared_color,
             id_seq: 0,
+            layout: Layout::View(0),
+            maximized_id: None,
             views: vec![],
         };
-        this.open_view(Placement::Top);
+        this.layout = Layout::View(this.next_id());
+        this.resize_views();
         this
     }
 
@@ -109,45 +511,50 @@ impl Workspace {
     ///
     /// Existing views will be resized as a side effect of opening a new view. However,
     /// the view will not be created, and resizing will not occur, if the resulting
-    /// number of rows would drop below [`Self::MIN_VIEW_ROWS`].
+    /// number of rows or columns of any view would drop below [`Self::MIN_VIEW_ROWS`]
+    /// or [`Self::MIN_VIEW_COLS`], respectively. If a view is maximized, then hidden
+    /// views are restored when the new view is created.
     ///
-    /// This function panics if the `id` specified in [`Placement::Above`] or
-    /// [`Placement::Below`] is not found, as this would indicate a correctness
-    /// problem by the caller.
+    /// This function panics if the `id` specified in [`Placement::Above`],
+    /// [`Placement::Below`], [`Placement::Left`], or [`Placement::Right`] is not found,
+    /// as this would indicate a correctness problem by the caller.
     pub fn open_view(&mut self, place: Placement) -> Option<u32> {
-        // Calculate number of rows that would need to be allocated to each view
-        // should another view be added.
-        let rows = self.views_size.rows / (self.views.len() + 1) as u32;
-        if rows < Self::MIN_VIEW_ROWS {
-            None
-        } else {
-            // Find correct index for insertion of new window.
-            let index = match place {
-                Placement::Top => 0,
-                Placement::Bottom => self.views.len(),
-                Placement::Above(id) => self
-                    .views
-                    .iter()
-                    .position(|v| v.id == id)
-                    .unwrap_or_else(|| panic!("{place:?}: view not found")),
-                Placement::Below(id) => self
-                    .views
-                    .iter()
-                    .position(|v| v.id == id)
-                    .map(|i| i + 1)
-                    .unwrap_or_else(|| panic!("{place:?}: view not found")),
-            };
-
-            // Insert zombie view in correct place before resizing views.
-            let view_id = self.next_id();
-            self.views.insert(index, self.create_zombie(view_id));
+        // Insert new view into copy of layout, which is discarded if the resulting
+        // arrangement violates minimum size constraints.
+        let view_id = self.id_seq;
+        let mut layout = self.layout.clone();
+        let found = match place {
+            Placement::Top => {
+                layout.wrap(view_id, Split::Rows, false);
+                true
+            }
+            Placement::Bottom => {
+                layout.wrap(view_id, Split::Rows, true);
+                true
+            }
+            Placement::Above(id) => layout.insert(id, view_id, Split::Rows, false),
+            Placement::Below(id) => layout.insert(id, view_id, Split::Rows, true),
+            Placement::Left(id) => layout.insert(id, view_id, Split::Cols, false),
+            Placement::Right(id) => layout.insert(id, view_id, Split::Cols, true),
+        };
+        if !found {
+            panic!("{place:?}: view not found");
+        }
+
+        if self.arrange(&layout).is_some() {
+            self.layout = layout;
+            self.maximized_id = None;
+            self.next_id();
             self.resize_views();
             Some(view_id)
+        } else {
+            None
         }
     }
 
     /// Closes the view referenced by `id` from the workspace, returning the _id_ of
-    /// the view above or `None` if the view could not be closed.
+    /// the view that precedes it or `None` if the view could not be closed. If
+    /// another view is maximized, then its _id_ is returned instead.
     ///
     /// Remaining views will be resized as a side effect of removal. However, the view
     /// will not be closed, and resizing will not occur, if `id` is the only remaining
@@ -162,12 +569,15 @@ impl Workspace {
                 .iter()
                 .position(|v| v.id == id)
                 .unwrap_or_else(|| panic!("{id}: view not found"));
-            self.views.remove(i);
+            self.layout.remove(id);
+            if self.maximized_id == Some(id) || self.views.len() <= 2 {
+                self.maximized_id = None;
+            }
             self.resize_views();
 
-            // Select view above the one removed.
+            // Select view preceding the one removed, unless it is hidden.
             let i = if i > 0 { i - 1 } else { 0 };
-            Some(self.views[i].id)
+            Some(self.maximized_id.unwrap_or(self.views[i].id))
         } else {
             None
         }
@@ -199,43 +609,30 @@ impl Workspace {
     pub fn resize(&mut self, keep_id: u32) -> Option<Vec<u32>> {
         let size = Self::query_size();
         if size != self.size {
-            // Update size of workspace and view areas, which drive calculation of total
-            // number of views and corresponding row allocations.
+            // Update size of workspace and view areas, which drive calculation of view
+            // allocations.
             self.size = size;
             self.views_size = size - Self::VIEWS_SIZE_ADJUST;
             self.shared_origin = Point::ORIGIN + Size::rows(size.rows - 1);
             self.shared_size = Size::new(1, size.cols);
 
-            // Calculate number of rows to allocate to each view, though revised workspace
-            // size might lead to violation of minimum view size constraint, which means
-            // total number of views must be reduced such that constraint is held.
-            let rows = self.views_size.rows / self.views.len() as u32;
-            let count = if rows < Self::MIN_VIEW_ROWS {
-                (self.views_size.rows / Self::MIN_VIEW_ROWS) as usize
-            } else {
-                self.views.len()
-            };
-
-            // If necessary, remove views from bottom to top, though do not remove view
-            // specified by caller regardless of where it exists in stack of views.
-            let removed_ids =
-                if count < self.views.len() {
-                    let n = self.views.len() - count;
-                    let indexes = self.views.iter().rev().enumerate().fold(
-                        Vec::new(),
-                        |mut indexes, (i, v)| {
-                            if indexes.len() < n && v.id != keep_id {
-                                // Index is flipped since views are being iterated back to
-                                // front.
-                                indexes.push(self.views.len() - i - 1);
-                            }
-                            indexes
-                        },
-                    );
-                    indexes.iter().map(|i| self.views.remove(*i).id).collect()
-                } else {
-                    vec![]
-                };
+            // Revised workspace size might lead to violation of minimum view size
+            // constraints, which means views must be removed, from bottom to top, until
+            // constraints are held. View specified by caller is never removed regardless
+            // of where it exists in layout, and since a single view always fits, this
+            // loop is guaranteed to terminate.
+            let mut removed_ids = Vec::new();
+            while self.arrange(&self.layout).is_none() {
+                let mut ids = Vec::new();
+                self.layout.view_ids(&mut ids);
+                if let Some(id) = ids.into_iter().rev().find(|id| *id != keep_id) {
+                    self.layout.remove(id);
+                    removed_ids.push(id);
+                }
+            }
+            if removed_ids.iter().any(|id| Some(*id) == self.maximized_id) {
+                self.maximized_id = None;
+            }
 
             self.resize_views();
             Some(removed_ids)
@@ -244,72 +641,230 @@ impl Workspace {
         }
     }
 
-    /// Resizes views with an equal distribution of `rows`, though views towards the top
-    /// will include an additional row if `residual_rows` is greater than 0.
+    /// Recreates all views in place without changing their size, which is necessary
+    /// when the theme changes since windows derive colors at the time of creation.
+    pub fn refresh(&mut self) {
+        self.resize_views();
+    }
+
+    /// Changes the size of the view referenced by `id` by `delta` rows or columns,
+    /// depending on whether the view is stacked vertically or placed side by side
+    /// with its siblings, returning `true` if the view was resized.
+    ///
+    /// A view cannot be resized if it is the only view, if a view is maximized, or if
+    /// the change would violate minimum size constraints of any view.
+    pub fn resize_view(&mut self, id: u32, delta: i32) -> bool {
+        if self.maximized_id.is_none() {
+            let mut layout = self.layout.clone();
+            if layout.adjust(id, delta, self.views_size) && self.arrange(&layout).is_some() {
+                self.layout = layout;
+                self.resize_views();
+                true
+            } else {
+                false
+            }
+        } else {
+            false
+        }
+    }
+
+    /// Restores an equal allocation of area among all views, and also restores hidden
+    /// views if a view is maximized.
+    pub fn balance_views(&mut self) {
+        self.layout.balance();
+        self.maximized_id = None;
+        self.resize_views();
+    }
+
+    /// Maximizes the view referenced by `id` such that it occupies the entire area
+    /// of views, hiding all other views, and returns `true` if the view was
+    /// maximized.
+    ///
+    /// A view cannot be maximized if it is the only view or if a view is already
+    /// maximized.
+    pub fn maximize_view(&mut self, id: u32) -> bool {
+        if self.views.len() > 1 && self.maximized_id.is_none() {
+            self.maximized_id = Some(id);
+            self.resize_views();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Restores views hidden by a prior call to [`maximize_view`](Self::maximize_view)
+    /// and returns `true` if views were restored.
+    pub fn restore_views(&mut self) -> bool {
+        if self.maximized_id.take().is_some() {
+            self.resize_views();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Recreates views according to the current layout and draws dividers between
+    /// views placed side by side.
+    ///
+    /// If a view is maximized, then it alone is arranged and all other views are
+    /// recreated as zombies.
     fn resize_views(&mut self) {
-        let count = self.views.len();
-        let rows = self.views_size.rows / count as u32;
-        let residual_rows = self.views_size.rows % count as u32;
-
-        let (views, _) = self.views.iter().enumerate().fold(
-            (Vec::new(), self.views_origin),
-            |(mut views, origin), (i, v)| {
-                // Give precedence of residual rows to top-most views.
-                let rows = if i >= residual_rows as usize {
-                    rows
+        let arrangement = match self.maximized_id {
+            Some(id) => self.arrange(&Layout::View(id)),
+            None => self.arrange(&self.layout),
+        }
+        .unwrap_or_else(|| panic!("layout must satisfy minimum size constraints"));
+
+        let mut ids = Vec::new();
+        self.layout.view_ids(&mut ids);
+        self.views = ids
+            .into_iter()
+            .map(|id| {
+                if let Some((_, origin, size)) = arrangement.areas.iter().find(|a| a.0 == id) {
+                    self.create_view(id, *origin, *size)
                 } else {
-                    rows + 1
-                };
+                    self.create_zombie(id)
+                }
+            })
+            .collect();
+        self.draw_dividers(&arrangement.dividers);
+    }
 
-                // Recreate view with new origin and size.
-                let view = self.create_view(v.id, origin, rows);
-                views.push(view);
+    /// Returns the arrangement of `layout` inside the area of views, or `None` if the
+    /// arrangement would violate minimum size constraints.
+    fn arrange(&self, layout: &Layout) -> Option<Arrangement> {
+        let mut arrangement = Arrangement::default();
+        if layout.arrange(self.views_origin, self.views_size, &mut arrangement) {
+            Some(arrangement)
+        } else {
+            None
+        }
+    }
 
-                // Update origin for next iteration of fold.
-                (views, origin + Size::rows(rows))
-            },
-        );
-        self.views = views;
+    fn draw_dividers(&self, dividers: &[(Point, u32)]) {
+        if !dividers.is_empty() {
+            let theme = self.config.theme();
+            let mut writer = Writer::new(Point::ORIGIN);
+            writer.set_color(Color::from_shades(theme.banner_fg, theme.inactive_bg));
+            for (origin, rows) in dividers {
+                for row in 0..*rows {
+                    writer
+                        .set_cursor(*origin + Size::rows(row))
+                        .write(Self::DIVIDER_CHAR);
+                }
+            }
+            writer.send();
+        }
     }
 
-    /// Returns the top-most [`View`] in the workspace.
+    /// Returns the top-most, left-most visible [`View`] in the workspace.
     pub fn top_view(&self) -> &View {
         self.views
-            .first()
+            .iter()
+            .find(|v| v.is_visible())
             .unwrap_or_else(|| panic!("at least one view must always exist"))
     }
 
-    /// Returns the bottom-most [`View`] in the workspace.
+    /// Returns the bottom-most, right-most visible [`View`] in the workspace.
     pub fn bottom_view(&self) -> &View {
         self.views
-            .last()
+            .iter()
+            .rev()
+            .find(|v| v.is_visible())
             .unwrap_or_else(|| panic!("at least one view must always exist"))
     }
 
-    /// Returns the [`View`] above `id`, which might be itself if only one view exists.
-    pub fn above_view(&self, id: u32) -> &View {
-        let i = self
-            .views
-            .iter()
-            .position(|v| v.id == id)
-            .unwrap_or_else(|| panic!("{id}: view not found"));
+    /// Returns the visible [`View`] preceding `id` in order of appearance, wrapping
+    /// around to the last view, which might be itself if only one view is visible.
+    pub fn prev_view(&self, id: u32) -> &View {
+        self.cycle_view(id, true)
+    }
+
+    /// Returns the visible [`View`] following `id` in order of appearance, wrapping
+    /// around to the first view, which might be itself if only one view is visible.
+    pub fn next_view(&self, id: u32) -> &View {
+        self.cycle_view(id, false)
+    }
+
+    /// Returns the [`View`] above `id`, which might be itself if no views are stacked
+    /// vertically.
+    pub fn up_view(&self, id: u32) -> &View {
+        self.adjacent_view(id, Direction::Up)
+    }
+
+    /// Returns the [`View`] below `id`, which might be itself if no views are stacked
+    /// vertically.
+    pub fn down_view(&self, id: u32) -> &View {
+        self.adjacent_view(id, Direction::Down)
+    }
+
+    /// Returns the [`View`] to the left of `id`, which might be itself if no views
+    /// are placed side by side.
+    pub fn left_view(&self, id: u32) -> &View {
+        self.adjacent_view(id, Direction::Left)
+    }
 
-        let n = self.views.len();
-        let i = if i == 0 { n - 1 } else { i - 1 };
-        &self.views[i]
+    /// Returns the [`View`] to the right of `id`, which might be itself if no views
+    /// are placed side by side.
+    pub fn right_view(&self, id: u32) -> &View {
+        self.adjacent_view(id, Direction::Right)
     }
 
-    /// Returns the [`View`] below `id`, which might be itself if only one view exists.
-    pub fn below_view(&self, id: u32) -> &View {
-        let i = self
+    /// Returns the visible [`View`] adjacent to `id` in order of appearance, which is
+    /// either preceding or following according to `rev`.
+    fn cycle_view(&self, id: u32, rev: bool) -> &View {
+        let views = self
             .views
+            .iter()
+            .filter(|v| v.is_visible())
+            .collect::<Vec<_>>();
+        let i = views
             .iter()
             .position(|v| v.id == id)
             .unwrap_or_else(|| panic!("{id}: view not found"));
 
-        let n = self.views.len();
-        let i = (i + 1) % n;
-        &self.views[i]
+        let n = views.len();
+        let i = if rev { (i + n - 1) % n } else { (i + 1) % n };
+        views[i]
+    }
+
+    /// Returns the nearest [`View`] from `id` in the direction of `dir`, wrapping
+    /// around the edge of the workspace if necessary.
+    ///
+    /// Only views overlapping `id` across the axis of movement are considered, and
+    /// if several are equally near, preference is given to the view aligned with the
+    /// top or left edge of `id`. If no such view exists, then `id` itself is returned.
+    fn adjacent_view(&self, id: u32, dir: Direction) -> &View {
+        let view = self.get_view(id);
+        let (along, across) = view.spans(dir);
+        let extent = match dir {
+            Direction::Up | Direction::Down => self.views_size.rows,
+            Direction::Left | Direction::Right => self.views_size.cols,
+        };
+
+        self.views
+            .iter()
+            .filter_map(|v| {
+                let (v_along, v_across) = v.spans(dir);
+                if v.id != id && v_across.start < across.end && across.start < v_across.end {
+                    // Distance is measured in direction of movement, wrapping around
+                    // edge of workspace.
+                    let dist = match dir {
+                        Direction::Up | Direction::Left => {
+                            (along.start + extent - v_along.end) % extent
+                        }
+                        Direction::Down | Direction::Right => {
+                            (v_along.start + extent - along.end) % extent
+                        }
+                    };
+                    Some((v, (dist, !v_across.contains(&across.start), v_across.start)))
+                } else {
+                    None
+                }
+            })
+            .min_by_key(|(_, key)| *key)
+            .map(|(v, _)| v)
+            .unwrap_or(view)
     }
 
     /// Returns the view corresponding to `id`, which must exist.
@@ -326,8 +881,9 @@ impl Workspace {
     }
 
     pub fn clear_shared(&mut self) {
+        let theme = self.config.theme();
         Writer::new_at(self.shared_origin)
-            .set_color(self.shared_color)
+            .set_color(Color::from_shades(theme.echo_fg, theme.text_bg))
             .write_str(" ".repeat(self.size.cols as usize).as_str())
             .send();
     }
@@ -350,16 +906,420 @@ impl Workspace {
         id
     }
 
-    fn create_view(&self, id: u32, origin: Point, rows: u32) -> View {
-        let window = Window::new(
-            origin,
-            Size::new(rows, self.views_size.cols),
-            self.config.clone(),
-        );
-        View::new(id, window.to_ref())
+    fn create_view(&self, id: u32, origin: Point, size: Size) -> View {
+        let window = Window::new(origin, size, self.config.clone());
+        View::new(id, window.to_ref(), origin, size)
     }
 
     fn create_zombie(&self, id: u32) -> View {
-        View::new(id, Window::zombie().to_ref())
+        View::new(id, Window::zombie().to_ref(), Point::ORIGIN, Size::ZERO)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKSPACE_SIZE: Size = Size::new(25, 80);
+
+    #[test]
+    fn wrap_layout() {
+        let mut layout = Layout::View(0);
+        layout.wrap(1, Split::Rows, true);
+        assert_eq!(layout, rows(vec![view(0), view(1)]));
+
+        // Joins existing split of same kind.
+        layout.wrap(2, Split::Rows, false);
+        assert_eq!(layout, rows(vec![view(2), view(0), view(1)]));
+
+        // Replaces root with split of different kind.
+        layout.wrap(3, Split::Cols, true);
+        assert_eq!(
+            layout,
+            cols(vec![rows(vec![view(2), view(0), view(1)]), view(3)])
+        );
+    }
+
+    #[test]
+    fn insert_layout() {
+        let mut layout = rows(vec![view(0), view(1)]);
+        assert!(layout.insert(1, 2, Split::Rows, false));
+        assert_eq!(layout, rows(vec![view(0), view(2), view(1)]));
+
+        // View is replaced with split of different kind.
+        assert!(layout.insert(0, 3, Split::Cols, true));
+        assert_eq!(
+            layout,
+            rows(vec![cols(vec![view(0), view(3)]), view(2), view(1)])
+        );
+
+        // Nested split of same kind is joined.
+        assert!(layout.insert(3, 4, Split::Cols, true));
+        assert_eq!(
+            layout,
+            rows(vec![
+                cols(vec![view(0), view(3), view(4)]),
+                view(2),
+                view(1)
+            ])
+        );
+
+        assert!(!layout.insert(9, 5, Split::Rows, true));
+    }
+
+    #[test]
+    fn remove_layout() {
+        let mut layout = rows(vec![cols(vec![view(0), view(1)]), view(2)]);
+        assert!(!layout.remove(9));
+
+        // Split with single remaining child collapses.
+        assert!(layout.remove(2));
+        assert_eq!(layout, cols(vec![view(0), view(1)]));
+
+        // Root is never removed.
+        assert!(layout.remove(1));
+        assert_eq!(layout, view(0));
+        assert!(!layout.remove(0));
+        assert_eq!(layout, view(0));
+    }
+
+    #[test]
+    fn normalize_layout() {
+        // Removal collapses inner split, which is then merged into parent split of
+        // same kind with weights scaled to preserve allocations.
+        let mut layout = rows(vec![
+            cols(vec![rows(vec![view(0), view(1)]), view(2)]),
+            view(3),
+        ]);
+        assert!(layout.remove(2));
+        assert_eq!(
+            layout,
+            Layout::Split(
+                Split::Rows,
+                vec![
+                    weighted(view(0), 0.5),
+                    weighted(view(1), 0.5),
+                    node(view(3))
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn view_ids_in_order() {
+        let layout = rows(vec![
+            cols(vec![view(4), rows(vec![view(1), view(3)])]),
+            view(0),
+        ]);
+        let mut ids = Vec::new();
+        layout.view_ids(&mut ids);
+        assert_eq!(ids, vec![4, 1, 3, 0]);
+    }
+
+    #[test]
+    fn allocate_by_weight() {
+        let nodes = [node(view(0)), node(view(1)), node(view(2))];
+
+        // Residual rows go to top-most children.
+        assert_eq!(
+            Split::Rows.allocate(Size::new(10, 80), &nodes),
+            vec![4, 3, 3]
+        );
+
+        // Dividers are excluded from columns.
+        assert_eq!(
+            Split::Cols.allocate(Size::new(10, 80), &nodes),
+            vec![26, 26, 26]
+        );
+
+        let nodes = [weighted(view(0), 2.0), weighted(view(1), 1.0)];
+        assert_eq!(Split::Rows.allocate(Size::new(9, 80), &nodes), vec![6, 3]);
+    }
+
+    #[test]
+    fn arrange_layout() {
+        let layout = cols(vec![view(0), rows(vec![view(1), view(2)])]);
+        let mut arrangement = Arrangement::default();
+        assert!(layout.arrange(Point::ORIGIN, Size::new(24, 80), &mut arrangement));
+        assert_eq!(
+            arrangement.areas,
+            vec![
+                (0, Point::new(0, 0), Size::new(24, 40)),
+                (1, Point::new(0, 41), Size::new(12, 39)),
+                (2, Point::new(12, 41), Size::new(12, 39)),
+            ]
+        );
+        assert_eq!(arrangement.dividers, vec![(Point::new(0, 40), 24)]);
+    }
+
+    #[test]
+    fn arrange_violates_minimum() {
+        let layout = cols(vec![view(0), view(1), view(2)]);
+        let mut arrangement = Arrangement::default();
+        assert!(!layout.arrange(Point::ORIGIN, Size::new(24, 40), &mut arrangement));
+
+        let layout = rows(vec![view(0), view(1), view(2)]);
+        let mut arrangement = Arrangement::default();
+        assert!(!layout.arrange(Point::ORIGIN, Size::new(5, 80), &mut arrangement));
+    }
+
+    #[test]
+    fn open_and_close_views() {
+        let mut ws = workspace(Size::new(25, 40));
+        assert_eq!(ws.open_view(Placement::Right(0)), Some(1));
+
+        // Third view would be narrower than minimum.
+        assert_eq!(ws.open_view(Placement::Right(1)), None);
+        assert_eq!(
+            areas(&ws),
+            vec![
+                (0, Point::new(0, 0), Size::new(24, 20)),
+                (1, Point::new(0, 21), Size::new(24, 19)),
+            ]
+        );
+
+        assert_eq!(ws.open_view(Placement::Top), Some(2));
+        assert_eq!(view_ids(&ws), vec![2, 0, 1]);
+
+        // View preceding closed view is selected.
+        assert_eq!(ws.close_view(1), Some(0));
+        assert_eq!(ws.close_view(2), Some(0));
+        assert_eq!(ws.close_view(0), None);
+    }
+
+    #[test]
+    fn locate_views() {
+        let mut ws = workspace(WORKSPACE_SIZE);
+        ws.open_view(Placement::Right(0));
+        ws.open_view(Placement::Below(1));
+
+        assert_eq!(
+            ws.locate_view(Point::new(5, 10)),
+            Some((0, Point::new(5, 10)))
+        );
+        assert_eq!(
+            ws.locate_view(Point::new(2, 45)),
+            Some((1, Point::new(2, 4)))
+        );
+        assert_eq!(
+            ws.locate_view(Point::new(14, 79)),
+            Some((2, Point::new(2, 38)))
+        );
+
+        // Dividers, banners, and shared region are not part of any view.
+        assert_eq!(ws.locate_view(Point::new(5, 40)), None);
+        assert_eq!(ws.locate_view(Point::new(23, 10)), None);
+        assert_eq!(ws.locate_view(Point::new(11, 50)), None);
+        assert_eq!(ws.locate_view(Point::new(24, 10)), None);
+    }
+
+    #[test]
+    fn cycle_views() {
+        let mut ws = workspace(WORKSPACE_SIZE);
+        ws.open_view(Placement::Right(0));
+
+        // Views placed side by side are still cycled.
+        assert_eq!(ws.next_view(0).id, 1);
+        assert_eq!(ws.next_view(1).id, 0);
+        assert_eq!(ws.prev_view(0).id, 1);
+        assert_eq!(ws.prev_view(1).id, 0);
+
+        ws.open_view(Placement::Below(0));
+        assert_eq!(ws.next_view(0).id, 2);
+        assert_eq!(ws.next_view(2).id, 1);
+        assert_eq!(ws.prev_view(0).id, 1);
+    }
+
+    #[test]
+    fn adjacent_views() {
+        // Arrangement of views:
+        //   0 | 1
+        //     | 2
+        let mut ws = workspace(WORKSPACE_SIZE);
+        ws.open_view(Placement::Right(0));
+        ws.open_view(Placement::Below(1));
+
+        // Only view in column.
+        assert_eq!(ws.up_view(0).id, 0);
+        assert_eq!(ws.down_view(0).id, 0);
+
+        // Vertical movement wraps around.
+        assert_eq!(ws.up_view(2).id, 1);
+        assert_eq!(ws.down_view(1).id, 2);
+        assert_eq!(ws.down_view(2).id, 1);
+        assert_eq!(ws.up_view(1).id, 2);
+
+        // Preference given to view aligned with top edge.
+        assert_eq!(ws.right_view(0).id, 1);
+        assert_eq!(ws.left_view(0).id, 1);
+        assert_eq!(ws.left_view(2).id, 0);
+        assert_eq!(ws.right_view(2).id, 0);
+    }
+
+    #[test]
+    fn resize_stacked_views() {
+        let mut ws = workspace(WORKSPACE_SIZE);
+        assert!(!ws.resize_view(0, 1));
+
+        ws.open_view(Placement::Bottom);
+        assert!(ws.resize_view(0, 3));
+        assert_eq!(extents(&ws), vec![(15, 80), (9, 80)]);
+        assert!(ws.resize_view(1, -5));
+        assert_eq!(extents(&ws), vec![(20, 80), (4, 80)]);
+
+        // Growth is limited by minimum size of siblings.
+        assert!(ws.resize_view(0, 10));
+        assert_eq!(extents(&ws), vec![(22, 80), (2, 80)]);
+        assert!(!ws.resize_view(0, 1));
+        assert!(!ws.resize_view(1, -1));
+        assert_eq!(extents(&ws), vec![(22, 80), (2, 80)]);
+    }
+
+    #[test]
+    fn resize_nested_views() {
+        // Arrangement of views:
+        //   0 | 1
+        //     | 2
+        let mut ws = workspace(WORKSPACE_SIZE);
+        ws.open_view(Placement::Right(0));
+        ws.open_view(Placement::Below(1));
+
+        // Columns are adjusted for view placed side by side.
+        assert!(ws.resize_view(0, 5));
+        assert_eq!(extents(&ws), vec![(24, 45), (12, 34), (12, 34)]);
+
+        // Rows are adjusted for view stacked inside column.
+        assert!(ws.resize_view(2, 4));
+        assert_eq!(extents(&ws), vec![(24, 45), (8, 34), (16, 34)]);
+
+        // Columns cannot shrink below minimum.
+        assert!(ws.resize_view(0, 20));
+        assert_eq!(extents(&ws), vec![(24, 63), (8, 16), (16, 16)]);
+        assert!(!ws.resize_view(0, 1));
+
+        ws.balance_views();
+        assert_eq!(extents(&ws), vec![(24, 40), (12, 39), (12, 39)]);
+    }
+
+    #[test]
+    fn maximize_and_restore_views() {
+        let mut ws = workspace(WORKSPACE_SIZE);
+        assert!(!ws.maximize_view(0));
+        assert!(!ws.restore_views());
+
+        ws.open_view(Placement::Right(0));
+        ws.open_view(Placement::Below(1));
+        assert!(ws.maximize_view(1));
+        assert!(!ws.maximize_view(0));
+        assert_eq!(visible_ids(&ws), vec![1]);
+        assert_eq!(ws.get_view(1).size, Size::new(24, 80));
+        assert!(ws
+            .views
+            .iter()
+            .all(|v| v.id == 1 || v.window.borrow().is_zombie()));
+
+        // Only visible views take part in navigation and resizing.
+        assert_eq!(ws.next_view(1).id, 1);
+        assert_eq!(ws.top_view().id, 1);
+        assert_eq!(ws.bottom_view().id, 1);
+        assert_eq!(
+            ws.locate_view(Point::new(20, 5)),
+            Some((1, Point::new(20, 5)))
+        );
+        assert!(!ws.resize_view(1, 1));
+
+        assert!(ws.restore_views());
+        assert!(!ws.restore_views());
+        assert_eq!(visible_ids(&ws), vec![0, 1, 2]);
+        assert_eq!(extents(&ws), vec![(24, 40), (12, 39), (12, 39)]);
+
+        // Balancing also restores hidden views.
+        ws.maximize_view(0);
+        ws.balance_views();
+        assert_eq!(visible_ids(&ws), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn open_view_while_maximized() {
+        let mut ws = workspace(WORKSPACE_SIZE);
+        ws.open_view(Placement::Bottom);
+        ws.maximize_view(0);
+
+        // Hidden views are restored when new view is opened.
+        assert_eq!(ws.open_view(Placement::Right(0)), Some(2));
+        assert_eq!(ws.maximized_id, None);
+        assert_eq!(visible_ids(&ws), vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn close_view_while_maximized() {
+        let mut ws = workspace(WORKSPACE_SIZE);
+        ws.open_view(Placement::Bottom);
+        ws.open_view(Placement::Bottom);
+        ws.open_view(Placement::Bottom);
+        ws.maximize_view(2);
+
+        // Closing hidden view keeps maximized view and selects it.
+        assert_eq!(ws.close_view(3), Some(2));
+        assert_eq!(view_ids(&ws), vec![0, 1, 2]);
+        assert_eq!(visible_ids(&ws), vec![2]);
+
+        // Closing maximized view restores hidden views.
+        assert_eq!(ws.close_view(2), Some(1));
+        assert_eq!(ws.maximized_id, None);
+        assert_eq!(visible_ids(&ws), vec![0, 1]);
+
+        // Maximized view is no longer maximized once it becomes the only view.
+        ws.maximize_view(0);
+        assert_eq!(ws.close_view(1), Some(0));
+        assert_eq!(ws.maximized_id, None);
+        assert_eq!(visible_ids(&ws), vec![0]);
+    }
+
+    fn workspace(size: Size) -> Workspace {
+        Workspace::with_size(Configuration::default(), size)
+    }
+
+    fn view_ids(ws: &Workspace) -> Vec<u32> {
+        ws.views.iter().map(|v| v.id).collect()
+    }
+
+    fn visible_ids(ws: &Workspace) -> Vec<u32> {
+        ws.views
+            .iter()
+            .filter(|v| v.is_visible())
+            .map(|v| v.id)
+            .collect()
+    }
+
+    fn extents(ws: &Workspace) -> Vec<(u32, u32)> {
+        ws.views
+            .iter()
+            .map(|v| (v.size.rows, v.size.cols))
+            .collect()
+    }
+
+    fn areas(ws: &Workspace) -> Vec<(u32, Point, Size)> {
+        ws.views.iter().map(|v| (v.id, v.origin, v.size)).collect()
+    }
+
+    fn view(id: u32) -> Layout {
+        Layout::View(id)
+    }
+
+    fn rows(layouts: Vec<Layout>) -> Layout {
+        Layout::Split(Split::Rows, layouts.into_iter().map(node).collect())
+    }
+
+    fn cols(layouts: Vec<Layout>) -> Layout {
+        Layout::Split(Split::Cols, layouts.into_iter().map(node).collect())
+    }
+
+    fn node(layout: Layout) -> Node {
+        Node::new(layout)
+    }
+
+    fn weighted(layout: Layout, weight: f64) -> Node {
+        Node { layout, weight }
     }
 }
@@ -3,6 +3,7 @@
 
 use crate::ansi;
 use crate::color::Color;
+use crate::grid::Cell;
 use crate::size::Point;
 use std::io::{self, Write};
 
@@ -62,11 +63,27 @@ impl Writer {
         self
     }
 
+    /// Changes the color from `prev_color` to `color`, emitting only those aspects of
+    /// the color that differ.
+    pub fn change_color(&mut self, prev_color: Color, color: Color) -> &mut Writer {
+        self.out
+            .push_str(ansi::change_color(prev_color, color).as_str());
+        self
+    }
+
     pub fn write(&mut self, c: char) -> &mut Writer {
         self.out.push(c);
         self
     }
 
+    /// Writes the value of `cell` followed by any zero-width characters attached to
+    /// that cell.
+    pub fn write_cell(&mut self, cell: Cell) -> &mut Writer {
+        self.out.push(cell.value);
+        self.out.extend(cell.marks());
+        self
+    }
+
     pub fn write_str(&mut self, text: &str) -> &mut Writer {
         self.out.push_str(text);
         self